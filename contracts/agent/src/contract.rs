@@ -3,14 +3,17 @@ use std::{vec};
 
 #[cfg(not(feature="library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{to_binary, Addr, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Uint128, SubMsg, WasmMsg, Reply, SubMsgResult, Empty};
+use cosmwasm_std::{coin, from_binary, to_binary, Addr, BankMsg, Coin, CosmosMsg, Binary, Deps, DepsMut, Env, MessageInfo, Response, StdResult, Storage, Uint128, SubMsg, WasmMsg, Reply, SubMsgResult, Empty, Event, Order, Timestamp};
 use cw2::set_contract_version;
-use cw_utils::{one_coin, PaymentError, parse_reply_instantiate_data};
+use cw721::Cw721ReceiveMsg;
+use cw_storage_plus::Bound;
+use cw_utils::{one_coin, Duration, PaymentError, parse_reply_instantiate_data};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{STAKING, NFT, NFT_ID, CACHE_NFT,CacheNFT};
-use crate::wasm_query::{get_cw721_update_metadata_msg,get_cw721_mint_msg,get_cw721_burn_msg,get_nft_owner,get_nft_metadata,get_staking_bonded};
-use nft::contract::{Metadata, Status};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, PositionResponse, PositionsResponse, ReceiveNftMsg, Donation, DonationsResponse, BeneficiaryResponse, BeneficiariesResponse, EarlyExitFee, EarlyExitFeesResponse, NftRewardRoute, RewardRoutesResponse};
+use crate::state::{STAKING, NFT, NFT_ID, PENDING_OPS, NEXT_REPLY_ID, PendingOp, MANAGER, TREASURY, FEES, CLAIM_RECEIPTS, EARLY_EXIT_FEES, Fees, REWARD_ROUTES, DONATED, RewardRoute, BeneficiaryShare, BENEFICIARIES, Beneficiary, STATE_IMPORT, StateImport, ImportStep};
+use crate::wasm_query::{get_cw721_update_metadata_msg,get_cw721_mint_msg,get_cw721_burn_msg,get_nft_owner,get_nft_metadata,get_staking_bonded,get_staking_claim_msg,get_nft_all_info,get_nft_tokens,get_staking_claims,get_staking_denom_claims,get_cw721_transfer_msg,get_staking_merge_msg,get_staking_split_msg,get_staking_rewards,get_staking_claim_rewards_msg,get_staking_allocation,get_cw721_update_transfer_policy_msg,get_cw721_update_royalty_msg,get_cw721_update_collection_info_msg,get_cw721_update_minter_msg,get_staking_update_agent_msg,get_staking_unbond_msg,get_staking_unbond_denom_msg,get_staking_claim_denom_msg,get_staking_bonded_coins};
+use nft::contract::{Metadata, Status, PendingUnbonding};
+use nft::state::{CollectionInfo, Royalty, TransferPolicy};
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-agent-angel";
@@ -26,6 +29,15 @@ const EXECUTE_UNBOND_NFT_REPLY_ID: u64 = 6;
 const EXECUTE_UNBOND_STAKING_REPLY_ID: u64 = 7;
 const EXECUTE_CLAIM_NFT_REPLY_ID: u64 = 8;
 const EXECUTE_CLAIM_STAKING_REPLY_ID: u64 = 9;
+// Pagination of PositionsByOwner
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+// Staking submessages are sent with a unique reply id, starting here, that keys their PendingOp
+const FIRST_PENDING_REPLY_ID: u64 = 100;
+// Fee caps in basis points
+const MAX_DEPOSIT_FEE_BPS: u16 = 500;
+const MAX_REWARD_COMMISSION_BPS: u16 = 5000;
+const MAX_EARLY_EXIT_FEE_BPS: u16 = 2000;
 
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -40,48 +52,101 @@ pub fn instantiate(
     deps.api.addr_validate(&msg.treasury)?;
     set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
 
-    NFT_ID.save(deps.storage, &Uint128::zero())?;
+    let fees = msg.fees.unwrap_or_default();
+    check_fees(&fees)?;
+    FEES.save(deps.storage, &fees)?;
+    CLAIM_RECEIPTS.save(deps.storage, &msg.claim_receipts.unwrap_or(false))?;
+    MANAGER.save(deps.storage, &msg.manager)?;
+    TREASURY.save(deps.storage, &msg.treasury)?;
 
-    // TODO: Choose this implementation or the commented one.
-    let nft_msg= nft::contract::InstantiateMsg{
-        name: "angel_staking_nft".to_string(), 
-        symbol: "ASM".to_string(), 
-        minter: env.contract.address.clone().into() 
-    };
+    let mut res = Response::new().add_attribute("action", "instantiate");
+    let mut previous_agent: Option<String> = None;
 
-    // let nft_msg= cw721_base::msg::InstantiateMsg{ 
-    //     name: "angel_staking_nft".to_string(), 
-    //     symbol: "ASM".to_string(), 
-    //     minter: env.contract.address.clone().into() 
-    // };
-
-   let instantiate_nft_msg = WasmMsg::Instantiate {
-       code_id: msg.nft_code_id,
-       funds: vec![],
-       admin: Some(msg.admin.clone()),
-       label: "angel_staking_nft".to_string(),
-       msg: to_binary(&nft_msg)?,
-   };
-   let reply_msg_nft = SubMsg::reply_on_success(instantiate_nft_msg, INSTANTIATE_NFT_REPLY_ID);
-
-   let staking_msg= staking::msg::InstantiateMsg{
-    agent:env.contract.address.into(), 
-    manager: msg.manager, 
-    treasury: msg.treasury };
-    let instantiate_staking_msg = WasmMsg::Instantiate {
-        code_id: msg.staking_code_id,
-        funds: vec![],
-        admin: Some(msg.admin),
-        label: "angel_staking".to_string(),
-        msg: to_binary(&staking_msg)?,
-    };
-   let reply_msg_staking = SubMsg::reply_on_success(instantiate_staking_msg, INSTANTIATE_STAKING_REPLY_ID);
-   
-   Ok(Response::new()
-        .add_attribute("action", "instantiate")
-        .add_submessage(reply_msg_nft)
-        .add_submessage(reply_msg_staking)
-    )   
+    // An existing NFT contract is adopted if this contract mints its NFTs, or the agent minting them hands it over.
+    // New NFTs take ids past those of its tokens
+    if let Some(nft_contract) = msg.nft_contract {
+        let addr = deps.api.addr_validate(&nft_contract)?;
+        let minter: nft::msg::MinterResponse = deps.querier.query_wasm_smart(&addr, &nft::msg::QueryMsg::Minter {})?;
+        let num_tokens: cw721::NumTokensResponse = deps.querier.query_wasm_smart(&addr, &nft::msg::QueryMsg::NumTokens {})?;
+        let mut min_nft_id = Uint128::from(num_tokens.count);
+        if minter.minter != env.contract.address {
+            check_previous_agent(deps.as_ref(), &minter.minter, &addr, &QueryMsg::GetNFTAdress {  })?;
+            let next_nft_id: Uint128 = deps.querier.query_wasm_smart(&minter.minter, &QueryMsg::NextNftId {  })?;
+            min_nft_id = min_nft_id.max(next_nft_id);
+            previous_agent = Some(minter.minter);
+        }
+        let first_nft_id = msg.first_nft_id.unwrap_or(min_nft_id);
+        if first_nft_id < min_nft_id {
+            return Err(ContractError::FirstNftIdInUse { min: min_nft_id });
+        }
+        NFT_ID.save(deps.storage, &first_nft_id)?;
+        NFT.save(deps.storage, &addr.to_string())?;
+    } else {
+        NFT_ID.save(deps.storage, &msg.first_nft_id.unwrap_or_default())?;
+        let nft_msg= nft::contract::InstantiateMsg{
+            name: msg.nft_name.unwrap_or_else(|| "angel_staking_nft".to_string()),
+            symbol: msg.nft_symbol.unwrap_or_else(|| "ASM".to_string()),
+            minter: env.contract.address.clone().into(),
+            royalty: msg.royalty,
+            collection_info: Some(msg.collection_info.unwrap_or_else(|| CollectionInfo {
+                description: "Staking positions of Angel Protocol".to_string(),
+                ..Default::default()
+            })),
+        };
+        let instantiate_nft_msg = WasmMsg::Instantiate {
+            code_id: msg.nft_code_id,
+            funds: vec![],
+            admin: Some(msg.admin.clone()),
+            label: msg.nft_label.unwrap_or_else(|| "angel_staking_nft".to_string()),
+            msg: to_binary(&nft_msg)?,
+        };
+        res = res.add_submessage(SubMsg::reply_always(instantiate_nft_msg, INSTANTIATE_NFT_REPLY_ID));
+    }
+
+    // Same for the staking contract, which only takes bonds from its agent
+    if let Some(staking_contract) = msg.staking_contract {
+        let addr = deps.api.addr_validate(&staking_contract)?;
+        let agent: String = deps.querier.query_wasm_smart(&addr, &staking::msg::QueryMsg::Agent {})?;
+        if agent != env.contract.address {
+            check_previous_agent(deps.as_ref(), &agent, &addr, &QueryMsg::GetStakingAdress {  })?;
+            // Both contracts are handed over by the same agent
+            if previous_agent.as_ref().is_some_and(|previous_agent| *previous_agent != agent) {
+                return Err(ContractError::ForeignContract { contract: addr.to_string() });
+            }
+            previous_agent = Some(agent);
+        }
+        STAKING.save(deps.storage, &addr.to_string())?;
+    } else {
+        let staking_msg= staking::msg::InstantiateMsg{
+            agent:env.contract.address.into(),
+            manager: msg.manager,
+            treasury: msg.treasury };
+        let instantiate_staking_msg = WasmMsg::Instantiate {
+            code_id: msg.staking_code_id,
+            funds: vec![],
+            admin: Some(msg.admin),
+            label: msg.staking_label.unwrap_or_else(|| "angel_staking".to_string()),
+            msg: to_binary(&staking_msg)?,
+        };
+        res = res.add_submessage(SubMsg::reply_always(instantiate_staking_msg, INSTANTIATE_STAKING_REPLY_ID));
+    }
+
+    // The state of the previous agent is imported once it hands the contracts over
+    if let Some(agent) = previous_agent {
+        let agent = deps.api.addr_validate(&agent)?;
+        STATE_IMPORT.save(deps.storage, &StateImport { agent, step: ImportStep::EarlyExitFees, start_after: None })?;
+    }
+
+    Ok(res)
+}
+
+// An adopted contract managed by another agent must be one of its contracts. That agent hands it over after instantiation
+fn check_previous_agent(deps: Deps, agent: &str, contract: &Addr, query: &QueryMsg) -> Result<(), ContractError> {
+    let managed: StdResult<String> = deps.querier.query_wasm_smart(agent, query);
+    if managed.ok().as_deref() != Some(contract.as_str()) {
+        return Err(ContractError::ForeignContract { contract: contract.to_string() });
+    }
+    Ok(())
 }
 
 #[cfg_attr(not(feature = "library"), entry_point)]
@@ -91,14 +156,33 @@ pub fn execute(
     info: MessageInfo,
     msg: ExecuteMsg,
 ) -> Result<Response, ContractError> {
+    if !matches!(msg, ExecuteMsg::ImportState { .. }) && STATE_IMPORT.may_load(deps.storage)?.is_some() {
+        return Err(ContractError::ImportPending {  });
+    }
     match msg {
-        ExecuteMsg::Bond { nft_id } => execute_bond(deps, env, info, nft_id),
-        ExecuteMsg::Unbond { nft_id } => execute_unbond(deps, env, info, nft_id),
-        ExecuteMsg::Claim { nft_id } => execute_claim(deps, env, info, nft_id),
+        ExecuteMsg::Bond { nft_id, owner, lock, reward_route } => execute_bond(deps, env, info, nft_id, owner, lock, reward_route),
+        ExecuteMsg::Unbond { nft_id } => execute_unbond(deps, env, info, nft_id, false),
+        ExecuteMsg::EarlyUnbond { nft_id } => execute_unbond(deps, env, info, nft_id, true),
+        ExecuteMsg::Claim { nft_id, recipient } => execute_claim(deps, env, info, nft_id, recipient),
+        ExecuteMsg::BatchUnbond { nft_ids } => execute_batch_unbond(deps, env, info, nft_ids),
+        ExecuteMsg::BatchClaim { nft_ids, recipient } => execute_batch_claim(deps, env, info, nft_ids, recipient),
+        ExecuteMsg::ClaimRewards { nft_id } => execute_claim_rewards(deps, env, info, nft_id),
+        ExecuteMsg::Merge { nft_ids } => execute_merge(deps, env, info, nft_ids),
+        ExecuteMsg::Split { nft_id, amounts } => execute_split(deps, env, info, nft_id, amounts),
+        ExecuteMsg::UpdateFees { fees } => execute_update_fees(deps, env, info, fees),
+        ExecuteMsg::UpdateTransferPolicy { policy } => execute_update_transfer_policy(deps, env, info, policy),
+        ExecuteMsg::UpdateRoyalty { royalty } => execute_update_royalty(deps, env, info, royalty),
+        ExecuteMsg::UpdateCollectionInfo { collection_info } => execute_update_collection_info(deps, env, info, collection_info),
+        ExecuteMsg::HandOver { agent } => execute_hand_over(deps, env, info, agent),
+        ExecuteMsg::ImportState { limit } => execute_import_state(deps, env, info, limit),
+        ExecuteMsg::AddBeneficiary { address, name, description_url } => execute_add_beneficiary(deps, env, info, address, name, description_url),
+        ExecuteMsg::UpdateBeneficiary { address, name, description_url } => execute_update_beneficiary(deps, env, info, address, name, description_url),
+        ExecuteMsg::DeactivateBeneficiary { address } => execute_deactivate_beneficiary(deps, env, info, address),
+        ExecuteMsg::ReceiveNft(msg) => execute_receive_nft(deps, env, info, msg),
     }
 }
 
-pub fn execute_bond (deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Option<String>) -> Result<Response, ContractError>{
+pub fn execute_bond (deps: DepsMut, env: Env, info: MessageInfo, nft_id: Option<String>, owner: Option<String>, lock: Option<Duration>, reward_route: Option<RewardRoute>) -> Result<Response, ContractError>{
     let d_coin = match one_coin(&info) {
         Ok(coin) => coin,
         Err(err) => {
@@ -110,6 +194,26 @@ pub fn execute_bond (deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Option
         },
     };
 
+    // Deposit fee is sent to the treasury and the rest is bonded
+    let fees = FEES.may_load(deps.storage)?.unwrap_or_default();
+    let deposit_fee = fee_amount(d_coin.amount, fees.deposit_fee_bps);
+    let d_coin = coin((d_coin.amount - deposit_fee).u128(), d_coin.denom);
+    let bond_funds = vec![d_coin.clone()];
+    let denom = d_coin.denom.clone();
+    // Other denoms must be registered on the staking contract
+    let bond_denom = deps.querier.query_bonded_denom()?;
+
+    let lock_until = match lock {
+        // Timestamp::plus_seconds panics on overflow
+        Some(Duration::Time(seconds)) => Some(seconds.checked_mul(1_000_000_000)
+            .and_then(|nanos| env.block.time.nanos().checked_add(nanos))
+            .map(Timestamp::from_nanos)
+            .ok_or(ContractError::LockTooLong {  })?),
+        Some(Duration::Height(_)) => return Err(ContractError::InvalidLock {  }),
+        None => None,
+    };
+    let reward_route = reward_route.map(|reward_route| check_reward_route(deps.as_ref(), reward_route)).transpose()?;
+
     let nft_contract_addr = NFT.load(deps.storage)?;
     let staking_contract_addr= STAKING.load(deps.storage)?;
     let reply_key : u64;
@@ -117,48 +221,57 @@ pub fn execute_bond (deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Option
 
     let wasm_msg = match nft_id {
         Some(nft_id) => {
-            // Query the nft contract and the staking contract, get the current amount staked. See that they match.
-            let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
-            if owner != info.sender {
-                return Err(ContractError::NotOwnerNFT {  })
+            // Anyone can top-up a NFT, as it only adds value to it. It is never transferred
+            if owner.is_some() {
+                return Err(ContractError::OwnerOnRebond {  })
             }
+            // Query the nft contract and the staking contract, get the current amount staked. See that they match.
 
-            // NFT must have Status::Bonding
+            // NFT must be bonded or locked
             let mut extension = get_nft_metadata(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
-            if extension.status == Status::Unbonding {
+            if extension.status.is_unbonding() {
                 return Err(ContractError::UnbondingNFT {  })             
             }
+            if extension.status == Status::Claimed {
+                return Err(ContractError::ClaimedNFT { nft_id })
+            }
 
-             // Current implementation only lets the user bond one coin per nft. Denom/Amount can be restacked
-             // No second coin will be added and extension.native.len() == 1
-            if extension.native[0].denom != d_coin.denom{
-                return Err(ContractError::OnlyOneNativeCoinPerNFT {  } )                 
-            } 
             // This is reduntant but increases security of bugs in initial contract version. 
-            // nft and staking contract must be aligned on the amount stored on the nft. 
-            let extension_amount = extension.native[0].amount;
-            let staking_bonded_amount = get_staking_bonded(deps.as_ref(), nft_id.clone(), &staking_contract_addr)?;
-            if extension_amount != staking_bonded_amount {
-                return Err(ContractError::NFTStakingMismatch { staking: staking_bonded_amount.to_string(), nft: extension_amount.to_string() } )                   
-            }
+            // nft and staking contract must be aligned on the amounts stored on the nft. 
+            check_staking_bonded(deps.as_ref(), &nft_id, &extension.native, &staking_contract_addr)?;
 
-            extension.native[0].amount = extension.native[0].amount.checked_add(d_coin.amount).unwrap();
+            // The coin of the denom is topped up, or added to the NFT for a new denom
+            add_coin(&mut extension.native, &d_coin)?;
+            if d_coin.denom == bond_denom {
+                extension.total_bonded += d_coin.amount;
+            }
 
             let nft_id_uint128 = Uint128::from_str(&nft_id)?;
             // Create a new metadata, adding the amount.
             nft_id_info = format!("Rebond nft_id {}", nft_id.clone());
 
+            // Only the owner can lock its NFT or route its rewards. A lock is never shortened
+            if lock_until.is_some() || reward_route.is_some() {
+                let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+                if owner != info.sender {
+                    return Err(ContractError::NotOwnerNFT {  })
+                }
+                extension.lock_until = extension.lock_until.max(lock_until);
+            }
+            if let Some(reward_route) = &reward_route {
+                REWARD_ROUTES.save(deps.storage, &nft_id, reward_route)?;
+            }
+
             // Storing info to be used on the reply entry point
-            let extension = Metadata { native: extension.native, status: Status::Bonded };
-            let cache_nft = CacheNFT { sender: info.sender, nft_id, extension };
-            CACHE_NFT.save(deps.storage, &cache_nft )?;
+            let extension = Metadata { status: Status::bonded(extension.lock_until, env.block.time), ..extension };
+            let pending_op = PendingOp { step: EXECUTE_RE_BOND_STAKING_REPLY_ID, sender: info.sender, nft_id, extension };
+            reply_key = save_pending_op(deps.storage, &pending_op)?;
 
-            reply_key = EXECUTE_RE_BOND_STAKING_REPLY_ID;
             let bond_msg = staking::msg::ExecuteMsg::Bond { nft_id: nft_id_uint128 };
             WasmMsg::Execute {
-                contract_addr: staking_contract_addr.into(),
+                contract_addr: staking_contract_addr,
                 msg: to_binary(&bond_msg)?,
-                funds: info.funds,
+                funds: bond_funds,
             }
 
         },
@@ -169,191 +282,1061 @@ pub fn execute_bond (deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Option
                 Ok(nft_id + Uint128::from(1u128))
             })?;
             // Storing info to be used on the reply entry point
-            let extension = Metadata { native: vec![d_coin], status: Status::Bonded };
-            let cache_nft = CacheNFT { sender: info.sender, nft_id: current_nft_id.to_string(), extension };
-            CACHE_NFT.save(deps.storage, &cache_nft )?;
-            reply_key = EXECUTE_NEW_BOND_STAKING_REPLY_ID;
+            let total_bonded = if d_coin.denom == bond_denom { d_coin.amount } else { Uint128::zero() };
+            let extension = Metadata { total_bonded, native: vec![d_coin], status: Status::bonded(lock_until, env.block.time), lock_until, ..Default::default() };
+            let owner = match owner {
+                Some(owner) => deps.api.addr_validate(&owner)?,
+                None => info.sender,
+            };
+            if let Some(reward_route) = &reward_route {
+                REWARD_ROUTES.save(deps.storage, &current_nft_id.to_string(), reward_route)?;
+            }
+            let pending_op = PendingOp { step: EXECUTE_NEW_BOND_STAKING_REPLY_ID, sender: owner, nft_id: current_nft_id.to_string(), extension };
+            reply_key = save_pending_op(deps.storage, &pending_op)?;
             let bond_msg = staking::msg::ExecuteMsg::Bond { nft_id: current_nft_id };
             WasmMsg::Execute {
-                contract_addr: staking_contract_addr.into(),
+                contract_addr: staking_contract_addr,
                 msg: to_binary(&bond_msg)?,
-                funds: info.funds,
+                funds: bond_funds,
             }
         }
     };
-    let submsg:SubMsg<Empty> = SubMsg::reply_on_success(wasm_msg, reply_key);
-    Ok(Response::new()
+    let submsg:SubMsg<Empty> = SubMsg::reply_always(wasm_msg, reply_key);
+    let mut res = Response::new();
+    if !deposit_fee.is_zero() {
+        res = res.add_message(BankMsg::Send { to_address: TREASURY.load(deps.storage)?, amount: vec![coin(deposit_fee.u128(), denom)] });
+    }
+    Ok(res
         .add_attribute("action", "execute_bond")
         .add_attribute("nft_id_info", nft_id_info)
+        .add_attribute("deposit_fee", deposit_fee)
         .add_submessage(submsg)
     )
 }
 
-pub fn execute_unbond(deps: DepsMut, _env: Env, info: MessageInfo, nft_id: String)-> Result<Response, ContractError>{
+pub fn execute_unbond(deps: DepsMut, env: Env, info: MessageInfo, nft_id: String, early: bool)-> Result<Response, ContractError>{
     let nft_contract_addr = NFT.load(deps.storage)?;
     let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
     if owner != info.sender {
         return Err(ContractError::NotOwnerNFT {  })
     }
- 
+    let (submsgs, event) = unbond_nft(deps, env, info.sender, nft_id.clone(), early)?;
+    Ok(Response::new()
+        .add_attribute("action", "execute_unbond")
+        .add_attribute("nft_id", nft_id)
+        .add_submessages(submsgs)
+        .add_event(event)
+    )
+}
+
+// Unbonds the NFT on the staking contract and then updates its status. Ownership must be checked by the caller.
+// An early unbond of a locked NFT records the early exit fee, paid on claim.
+// Returns the staking submessages and the unbond_nft event of the NFT
+fn unbond_nft(deps: DepsMut, env: Env, sender: Addr, nft_id: String, early: bool)-> Result<(Vec<SubMsg>, Event), ContractError>{
+    let nft_contract_addr = NFT.load(deps.storage)?;
     let staking_contract_addr= STAKING.load(deps.storage)?;
     let mut extension = get_nft_metadata(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+    if !extension.status.is_bonded() {
+        return Err(ContractError::NFTNotBonded { nft_id })
+    }
   
-    let nft_amount = extension.native[0].amount;
+    let mut event = Event::new("unbond_nft")
+        .add_attribute("nft_id", &nft_id)
+        .add_attribute("amount", coins_string(&extension.native));
+    if let Err(err) = check_unlocked(&env, &nft_id, &extension) {
+        if !early {
+            return Err(err)
+        }
+        let fees = FEES.may_load(deps.storage)?.unwrap_or_default();
+        let early_exit_fee : Vec<Coin> = extension.native
+            .iter()
+            .map(|native| coin(fee_amount(native.amount, fees.early_exit_fee_bps).u128(), &native.denom))
+            .collect();
+        EARLY_EXIT_FEES.save(deps.storage, &nft_id, &early_exit_fee)?;
+        event = event.add_attribute("early_exit_fee", coins_string(&early_exit_fee));
+    }
     let nft_id_uint128 = Uint128::from_str(&nft_id)?;
+    let unbond_msgs = staking_unbond_msgs(deps.as_ref(), nft_id_uint128, &extension.native, &Addr::unchecked(staking_contract_addr))?;
     
-    // extension is updated and stored on the cache so that it can be used on reply entry point
-    extension.status =  Status::Unbonding;
-    let cache_nft = CacheNFT { sender: info.sender, nft_id: nft_id.clone(), extension };
-    CACHE_NFT.save(deps.storage, &cache_nft )?;
-
-    let unbond_msg= staking::msg::ExecuteMsg::Unbond { nft_id: nft_id_uint128, amount: nft_amount};
-    let unbond_wasm_msg = WasmMsg::Execute {
-        contract_addr: staking_contract_addr.into(),
-        msg: to_binary(&unbond_msg)?,
-        funds: vec![],
-    };
-    let submsg:SubMsg<Empty> = SubMsg::reply_on_success(unbond_wasm_msg, EXECUTE_UNBOND_STAKING_REPLY_ID);
-
-    Ok(Response::new()
-        .add_attribute("action", "execute_unbond")
-        .add_attribute("nft_id", nft_id)
-        .add_submessage(submsg)
-    )
+    // extension is updated and stored on the pending operation so that it can be used on reply entry point.
+    // The release time is set from the staking claims on reply
+    extension.status =  Status::Unbonding { release_at: env.block.time };
+    let pending_op = PendingOp { step: EXECUTE_UNBOND_STAKING_REPLY_ID, sender, nft_id: nft_id.clone(), extension };
+    let reply_key = save_pending_op(deps.storage, &pending_op)?;
+    Ok((staking_submsgs(unbond_msgs, reply_key), event))
 }
 
-pub fn execute_claim(deps: DepsMut, _env: Env, info: MessageInfo, nft_id:String)-> Result<Response, ContractError>{
+pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id:String, recipient: Option<String>)-> Result<Response, ContractError>{
     let nft_contract_addr = NFT.load(deps.storage)?;
     let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
     if owner != info.sender {
         return Err(ContractError::NotOwnerNFT {  })
     };
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+    claim_nft(deps, env, info.sender, nft_id, recipient)
+}
+
+// Claims the NFT tokens on the staking contract for recipient and then burns the NFT. Ownership must be checked by the caller
+fn claim_nft(mut deps: DepsMut, env: Env, sender: Addr, nft_id: String, recipient: Addr)-> Result<Response, ContractError>{
+    let nft_contract_addr = NFT.load(deps.storage)?;
     let staking_contract_addr= STAKING.load(deps.storage)?;
     let extension = get_nft_metadata(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
-    let nft_amount = extension.native[0].amount;
+    check_claimable(&env, &nft_id, &extension)?;
+    let nft_amount = coins_string(&extension.native);
     let nft_id_uint128 = Uint128::from_str(&nft_id)?;
 
-    let claim_msg= staking::msg::ExecuteMsg::Claim { nft_id:nft_id_uint128, sender:info.sender.clone().into(), amount:nft_amount };
-    let claim_wasm_msg = WasmMsg::Execute {
-        contract_addr: staking_contract_addr,
-        msg: to_binary(&claim_msg)?,
-        funds: vec![],
+    let (claim_to, payout_msgs, early_exit_fee) = claim_payout(deps.storage, &env, &nft_id, &extension.native, &recipient)?;
+    let claim_msgs = staking_claim_msgs(deps.as_ref(), nft_id_uint128, &claim_to, &extension.native, &Addr::unchecked(staking_contract_addr))?;
+    let pending_op = PendingOp { step: EXECUTE_CLAIM_STAKING_REPLY_ID, sender, nft_id: nft_id.clone(), extension };
+    let reply_key = save_pending_op(deps.storage, &pending_op)?;
+
+    let event = Event::new("claim_nft")
+        .add_attribute("nft_id", &nft_id)
+        .add_attribute("amount", nft_amount)
+        .add_attribute("recipient", &recipient)
+        .add_attribute("early_exit_fee", coins_string(&early_exit_fee));
+
+    let mut res = Response::new()
+    .add_attribute("action", "execute_claim")
+    .add_attribute("nft_id", &nft_id)
+    .add_submessages(staking_submsgs(claim_msgs, reply_key))
+    .add_messages(payout_msgs)
+    .add_event(event);
+
+    // Rewards not claimed yet are paid out with the claim
+    if let Some((rewards_msgs, rewards_event)) = rewards_payout(deps.branch(), &env, &nft_id, &recipient)? {
+        res = res.add_messages(rewards_msgs).add_event(rewards_event);
+    }
+    REWARD_ROUTES.remove(deps.storage, &nft_id);
+    Ok(res)
+}
+
+pub fn execute_claim_rewards(deps: DepsMut, env: Env, info: MessageInfo, nft_id:String)-> Result<Response, ContractError>{
+    let nft_contract_addr = NFT.load(deps.storage)?;
+    let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+    if owner != info.sender {
+        return Err(ContractError::NotOwnerNFT {  })
     };
-    let cache_nft = CacheNFT { sender: info.sender, nft_id: nft_id.clone(), extension };
-    CACHE_NFT.save(deps.storage, &cache_nft )?;
+    let rewards = get_staking_rewards(deps.as_ref(), nft_id.clone(), &STAKING.load(deps.storage)?)?;
+    let mut extension = get_nft_metadata(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+    extension.lifetime_rewards += rewards;
+    sync_staking_info(deps.as_ref(), &env, &nft_id, &mut extension)?;
+    let update_msg = get_cw721_update_metadata_msg(nft_id.clone(), None, extension, &Addr::unchecked(&nft_contract_addr))?;
+    let (rewards_msgs, event) = rewards_payout(deps, &env, &nft_id, &info.sender)?
+        .ok_or(ContractError::NoRewards { nft_id: nft_id.clone() })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_claim_rewards")
+        .add_attribute("nft_id", nft_id)
+        .add_messages(rewards_msgs)
+        .add_message(update_msg)
+        .add_event(event))
+}
 
-    let submsg:SubMsg<Empty> = SubMsg::reply_on_success(claim_wasm_msg, EXECUTE_CLAIM_STAKING_REPLY_ID);
+// Messages claiming the NFT rewards on the staking contract and paying them to recipient, None without rewards.
+// With a reward commission or a reward route the agent receives the rewards. It sends the owner share to recipient,
+// the beneficiary shares to the beneficiaries and the rest to the treasury, with the commission and rounding dust
+fn rewards_payout(deps: DepsMut, env: &Env, nft_id: &str, recipient: &Addr) -> Result<Option<(Vec<CosmosMsg>, Event)>, ContractError> {
+    let staking_contract_addr = STAKING.load(deps.storage)?;
+    let rewards = get_staking_rewards(deps.as_ref(), nft_id.to_string(), &staking_contract_addr)?;
+    if rewards.is_zero() {
+        return Ok(None)
+    }
+    let fees = FEES.may_load(deps.storage)?.unwrap_or_default();
+    let commission = fee_amount(rewards, fees.reward_commission_bps);
+    let reward_route = REWARD_ROUTES.may_load(deps.storage, nft_id)?;
+    let nft_id_uint128 = Uint128::from_str(nft_id)?;
+
+    let mut msgs : Vec<CosmosMsg> = vec![];
+    let mut event = Event::new("claim_rewards")
+        .add_attribute("nft_id", nft_id)
+        .add_attribute("rewards", rewards)
+        .add_attribute("recipient", recipient)
+        .add_attribute("reward_commission", commission);
+    if commission.is_zero() && reward_route.is_none() {
+        msgs.push(get_staking_claim_rewards_msg(nft_id_uint128, recipient, &Addr::unchecked(&staking_contract_addr))?.into());
+        return Ok(Some((msgs, event)))
+    }
+
+    let denom = deps.querier.query_bonded_denom()?;
+    msgs.push(get_staking_claim_rewards_msg(nft_id_uint128, &env.contract.address, &Addr::unchecked(&staking_contract_addr))?.into());
+    let routed = rewards - commission;
+    let mut to_treasury = commission;
+    let mut to_recipient = routed;
+    if let Some(reward_route) = reward_route {
+        to_recipient = fee_amount(routed, reward_route.owner_bps);
+        to_treasury += routed - to_recipient;
+        for beneficiary in reward_route.beneficiaries {
+            let donation = fee_amount(routed, beneficiary.bps);
+            let address = Addr::unchecked(beneficiary.address);
+            // Shares of deactivated beneficiaries go to the treasury
+            let active = is_active_beneficiary(deps.storage, &address)?;
+            if donation.is_zero() || !active {
+                continue;
+            }
+            to_treasury -= donation;
+            DONATED.update(deps.storage, &address, |donated| -> StdResult<_> { Ok(donated.unwrap_or_default() + donation) })?;
+            msgs.push(BankMsg::Send { to_address: address.to_string(), amount: vec![coin(donation.u128(), &denom)] }.into());
+            event = event.add_attribute("donation", format!("{}:{}", address, donation));
+        }
+    }
+    if !to_treasury.is_zero() {
+        msgs.push(BankMsg::Send { to_address: TREASURY.load(deps.storage)?, amount: vec![coin(to_treasury.u128(), &denom)] }.into());
+    }
+    if !to_recipient.is_zero() {
+        msgs.push(BankMsg::Send { to_address: recipient.to_string(), amount: vec![coin(to_recipient.u128(), &denom)] }.into());
+    }
+    Ok(Some((msgs, event.add_attribute("to_treasury", to_treasury))))
+}
+
+// Reward route shares add up to 10000 bps. Beneficiaries are active in the registry, not repeated and with a non zero share
+fn check_reward_route(deps: Deps, reward_route: RewardRoute) -> Result<RewardRoute, ContractError> {
+    let mut total_bps = reward_route.owner_bps as u32 + reward_route.treasury_bps as u32;
+    let mut beneficiaries : Vec<BeneficiaryShare> = vec![];
+    for mut beneficiary in reward_route.beneficiaries {
+        let address = deps.api.addr_validate(&beneficiary.address)?;
+        if !is_active_beneficiary(deps.storage, &address)? {
+            return Err(ContractError::BeneficiaryNotActive { address: address.into() })
+        }
+        beneficiary.address = address.into();
+        if beneficiary.bps == 0 || beneficiaries.iter().any(|b| b.address == beneficiary.address) {
+            return Err(ContractError::InvalidRewardRoute {  })
+        }
+        total_bps += beneficiary.bps as u32;
+        beneficiaries.push(beneficiary);
+    }
+    if total_bps != 10_000 {
+        return Err(ContractError::InvalidRewardRoute {  })
+    }
+    Ok(RewardRoute { beneficiaries, ..reward_route })
+}
+
+// Metadata of a bonded NFT owned by sender, checked against the staking contract
+fn load_bonded_nft(deps: Deps, sender: &Addr, nft_id: &str, nft_contract_addr: &String, staking_contract_addr: &String) -> Result<Metadata, ContractError> {
+    let owner = get_nft_owner(deps, nft_id.to_string(), nft_contract_addr)?;
+    if owner != *sender {
+        return Err(ContractError::NotOwnerNFT {  })
+    }
+    let extension = get_nft_metadata(deps, nft_id.to_string(), nft_contract_addr)?;
+    if !extension.status.is_bonded() {
+        return Err(ContractError::NFTNotBonded { nft_id: nft_id.to_string() })
+    }
+    check_staking_bonded(deps, nft_id, &extension.native, staking_contract_addr)?;
+    Ok(extension)
+}
+
+pub fn execute_merge(deps: DepsMut, env: Env, info: MessageInfo, nft_ids: Vec<String>)-> Result<Response, ContractError>{
+    check_batch(&nft_ids)?;
+    if nft_ids.len() < 2 {
+        return Err(ContractError::MergeTooFewNFTs {  })
+    }
+    let nft_contract_addr = NFT.load(deps.storage)?;
+    let staking_contract_addr= STAKING.load(deps.storage)?;
+
+    let mut extension = load_bonded_nft(deps.as_ref(), &info.sender, &nft_ids[0], &nft_contract_addr, &staking_contract_addr)?;
+    let mut merged_nft_ids : Vec<Uint128> = vec![];
+    let mut burn_msgs : Vec<WasmMsg> = vec![];
+    for nft_id in nft_ids[1..].iter() {
+        let merged_extension = load_bonded_nft(deps.as_ref(), &info.sender, nft_id, &nft_contract_addr, &staking_contract_addr)?;
+        for native in merged_extension.native.iter() {
+            add_coin(&mut extension.native, native)?;
+        }
+        // Merged NFT keeps the longest lock and the history of the merged ones
+        extension.lock_until = extension.lock_until.max(merged_extension.lock_until);
+        extension.total_bonded += merged_extension.total_bonded;
+        extension.lifetime_rewards += merged_extension.lifetime_rewards;
+        merged_nft_ids.push(Uint128::from_str(nft_id)?);
+        // Merged NFT keeps its reward route
+        REWARD_ROUTES.remove(deps.storage, nft_id);
+        // The minter only burns claimed NFTs. The merged ones have handed their tokens over
+        let claimed = Metadata { native: vec![], status: Status::Claimed, allocations: vec![], unbonding: vec![], ..merged_extension };
+        burn_msgs.push(get_cw721_update_metadata_msg(nft_id.clone(), None, claimed, &Addr::unchecked(&nft_contract_addr))?);
+        burn_msgs.push(get_cw721_burn_msg(nft_id.clone(), &Addr::unchecked(&nft_contract_addr))?);
+    }
+
+    let merge_msg = get_staking_merge_msg(Uint128::from_str(&nft_ids[0])?, merged_nft_ids, &Addr::unchecked(&staking_contract_addr))?;
+    // Delegations are not changed by the merge
+    sync_staking_info(deps.as_ref(), &env, &nft_ids[0], &mut extension)?;
+    let amount = coins_string(&extension.native);
+    let update_msg = get_cw721_update_metadata_msg(nft_ids[0].clone(), None, extension, &Addr::unchecked(&nft_contract_addr))?;
 
     Ok(Response::new()
-    .add_attribute("action", "execute_claim")
-    .add_attribute("nft_id", nft_id)
-    .add_submessage(submsg))
+        .add_attribute("action", "execute_merge")
+        .add_attribute("nft_id", &nft_ids[0])
+        .add_attribute("merged", nft_ids[1..].join(","))
+        .add_attribute("amount", amount)
+        .add_message(merge_msg)
+        .add_message(update_msg)
+        .add_messages(burn_msgs)
+    )
+}
+
+pub fn execute_split(deps: DepsMut, env: Env, info: MessageInfo, nft_id: String, amounts: Vec<Uint128>)-> Result<Response, ContractError>{
+    let nft_contract_addr = NFT.load(deps.storage)?;
+    let staking_contract_addr= STAKING.load(deps.storage)?;
+    let mut extension = load_bonded_nft(deps.as_ref(), &info.sender, &nft_id, &nft_contract_addr, &staking_contract_addr)?;
+    // Only the pooled delegations of the bond denom are split
+    if extension.native.len() != 1 || extension.native[0].denom != deps.querier.query_bonded_denom()? {
+        return Err(ContractError::OnlyOneNativeCoinPerNFT {  })
+    }
+
+    let split_amount = amounts.iter().try_fold(Uint128::zero(), |sum, amount| sum.checked_add(*amount))
+        .map_err(|_| ContractError::InvalidSplitAmounts {  })?;
+    if amounts.is_empty() || amounts.iter().any(|amount| amount.is_zero()) || split_amount >= extension.native[0].amount {
+        return Err(ContractError::InvalidSplitAmounts {  })
+    }
+
+    // New NFTs take the next ids
+    let first_nft_id = NFT_ID.load(deps.storage)?;
+    let new_nft_ids : Vec<Uint128> = (0..amounts.len() as u128).map(|i| first_nft_id + Uint128::from(i)).collect();
+    NFT_ID.save(deps.storage, &(first_nft_id + Uint128::from(amounts.len() as u128)))?;
+
+    // New NFTs inherit the reward route and the royalty, and take their share of the lifetime rewards
+    let reward_route = REWARD_ROUTES.may_load(deps.storage, &nft_id)?;
+    let mut mint_msgs : Vec<WasmMsg> = vec![];
+    let mut split_rewards = Uint128::zero();
+    for (new_nft_id, amount) in new_nft_ids.iter().zip(amounts.iter()) {
+        if let Some(reward_route) = &reward_route {
+            REWARD_ROUTES.save(deps.storage, &new_nft_id.to_string(), reward_route)?;
+        }
+        let lifetime_rewards = extension.lifetime_rewards.multiply_ratio(*amount, extension.native[0].amount);
+        split_rewards += lifetime_rewards;
+        let mut new_extension = Metadata {
+            native: vec![coin(amount.u128(), &extension.native[0].denom)],
+            status: extension.status.clone(),
+            lock_until: extension.lock_until,
+            total_bonded: *amount,
+            lifetime_rewards,
+            royalty_percentage: extension.royalty_percentage,
+            royalty_payment_address: extension.royalty_payment_address.clone(),
+            ..Default::default()
+        };
+        sync_staking_info(deps.as_ref(), &env, &new_nft_id.to_string(), &mut new_extension)?;
+        mint_msgs.push(get_cw721_mint_msg(&info.sender, new_nft_id.to_string(), None, new_extension, &Addr::unchecked(&nft_contract_addr))?);
+    }
+    // The new NFTs carry the split amounts and rewards, so the totals of the split positions add up
+    extension.native[0].amount -= split_amount;
+    extension.total_bonded = extension.total_bonded.saturating_sub(split_amount);
+    extension.lifetime_rewards -= split_rewards;
+    sync_staking_info(deps.as_ref(), &env, &nft_id, &mut extension)?;
+
+    let split_msg = get_staking_split_msg(Uint128::from_str(&nft_id)?, new_nft_ids.clone(), amounts, &Addr::unchecked(&staking_contract_addr))?;
+    let update_msg = get_cw721_update_metadata_msg(nft_id.clone(), None, extension, &Addr::unchecked(&nft_contract_addr))?;
+    let new_nft_ids : Vec<String> = new_nft_ids.iter().map(|new_nft_id| new_nft_id.to_string()).collect();
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_split")
+        .add_attribute("nft_id", nft_id)
+        .add_attribute("new_nft_ids", new_nft_ids.join(","))
+        .add_attribute("split_lifetime_rewards", split_rewards)
+        .add_message(split_msg)
+        .add_message(update_msg)
+        .add_messages(mint_msgs)
+    )
+}
+
+// NFT sent by its owner to the agent with SendNft. The agent acts for the owner, who is cw721 msg.sender
+pub fn execute_receive_nft(deps: DepsMut, env: Env, info: MessageInfo, msg: Cw721ReceiveMsg)-> Result<Response, ContractError>{
+    let nft_contract_addr = NFT.load(deps.storage)?;
+    if info.sender != nft_contract_addr {
+        return Err(ContractError::Unauthorized {  })
+    }
+    let owner = deps.api.addr_validate(&msg.sender)?;
+
+    let res = match from_binary(&msg.msg)? {
+        ReceiveNftMsg::Unbond {  } => {
+            // Sent after the unbond submessage and its reply, so the NFT goes back once updated
+            let transfer_msg = get_cw721_transfer_msg(msg.token_id.clone(), &owner, &Addr::unchecked(nft_contract_addr))?;
+            let (submsgs, event) = unbond_nft(deps, env, owner.clone(), msg.token_id.clone(), false)?;
+            Response::new()
+                .add_attribute("action", "execute_unbond")
+                .add_attribute("nft_id", msg.token_id)
+                .add_submessages(submsgs)
+                .add_message(transfer_msg)
+                .add_event(event)
+        },
+        ReceiveNftMsg::Claim { recipient } => {
+            let recipient = match recipient {
+                Some(recipient) => deps.api.addr_validate(&recipient)?,
+                None => owner.clone(),
+            };
+            // A receipt goes back to the owner once claimed
+            let claim_receipts = CLAIM_RECEIPTS.may_load(deps.storage)?.unwrap_or(false);
+            let transfer_msg = get_cw721_transfer_msg(msg.token_id.clone(), &owner, &Addr::unchecked(nft_contract_addr))?;
+            let res = claim_nft(deps, env, owner.clone(), msg.token_id, recipient)?;
+            match claim_receipts {
+                true => res.add_message(transfer_msg),
+                false => res,
+            }
+        },
+    };
+    Ok(res.add_attribute("received_from", owner))
+}
+
+// Stores the pending operation under a new unique reply id, which is returned
+fn save_pending_op(storage: &mut dyn Storage, pending_op: &PendingOp) -> Result<u64, ContractError> {
+    let reply_id = NEXT_REPLY_ID.may_load(storage)?.unwrap_or(FIRST_PENDING_REPLY_ID);
+    if PENDING_OPS.has(storage, reply_id) {
+        return Err(ContractError::StalePendingOp { id: reply_id.to_string() })
+    }
+    PENDING_OPS.save(storage, reply_id, pending_op)?;
+    NEXT_REPLY_ID.save(storage, &(reply_id + 1))?;
+    Ok(reply_id)
+}
+
+// Removes and returns the pending operation of a reply
+fn take_pending_op(storage: &mut dyn Storage, reply_id: u64) -> Result<PendingOp, ContractError> {
+    let pending_op = PENDING_OPS.may_load(storage, reply_id)?
+        .ok_or(ContractError::PendingOpNotFound { id: reply_id.to_string() })?;
+    PENDING_OPS.remove(storage, reply_id);
+    Ok(pending_op)
+}
+
+pub fn execute_update_fees(deps: DepsMut, _env: Env, info: MessageInfo, fees: Fees)-> Result<Response, ContractError>{
+    let manager = MANAGER.load(deps.storage)?;
+    if info.sender != manager {
+        return Err(ContractError::Unauthorized {  })
+    }
+    check_fees(&fees)?;
+    FEES.save(deps.storage, &fees)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_fees")
+        .add_attribute("deposit_fee_bps", fees.deposit_fee_bps.to_string())
+        .add_attribute("reward_commission_bps", fees.reward_commission_bps.to_string())
+        .add_attribute("early_exit_fee_bps", fees.early_exit_fee_bps.to_string())
+    )
+}
+
+// Forwarded to the NFT contract, whose minter is the agent
+pub fn execute_update_transfer_policy(deps: DepsMut, _env: Env, info: MessageInfo, policy: TransferPolicy)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let update_msg = get_cw721_update_transfer_policy_msg(policy, &Addr::unchecked(NFT.load(deps.storage)?))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_transfer_policy")
+        .add_message(update_msg)
+    )
+}
+
+// Forwarded to the NFT contract, as its default royalty
+pub fn execute_update_royalty(deps: DepsMut, _env: Env, info: MessageInfo, royalty: Option<Royalty>)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let update_msg = get_cw721_update_royalty_msg(royalty, &Addr::unchecked(NFT.load(deps.storage)?))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_royalty")
+        .add_message(update_msg)
+    )
+}
+
+// Forwarded to the NFT contract, as its collection metadata
+pub fn execute_update_collection_info(deps: DepsMut, _env: Env, info: MessageInfo, collection_info: CollectionInfo)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let update_msg = get_cw721_update_collection_info_msg(collection_info, &Addr::unchecked(NFT.load(deps.storage)?))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_collection_info")
+        .add_message(update_msg)
+    )
+}
+
+pub fn execute_hand_over(deps: DepsMut, _env: Env, info: MessageInfo, agent: String)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let agent = deps.api.addr_validate(&agent)?;
+    let minter_msg = get_cw721_update_minter_msg(&agent, &Addr::unchecked(NFT.load(deps.storage)?))?;
+    let agent_msg = get_staking_update_agent_msg(&agent, &Addr::unchecked(STAKING.load(deps.storage)?))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_hand_over")
+        .add_attribute("agent", agent)
+        .add_message(minter_msg)
+        .add_message(agent_msg)
+    )
+}
+
+// Imports up to limit entries of the previous agent, one map after the other. A map is done once a page of it comes short.
+// Donations are imported along with the beneficiaries, which are never removed
+pub fn execute_import_state(deps: DepsMut, env: Env, info: MessageInfo, limit: Option<u32>)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let mut import = STATE_IMPORT.load(deps.storage)?;
+    // The previous agent can not change the contracts nor its state past the hand over
+    let minter: nft::msg::MinterResponse = deps.querier.query_wasm_smart(NFT.load(deps.storage)?, &nft::msg::QueryMsg::Minter {})?;
+    let agent: String = deps.querier.query_wasm_smart(STAKING.load(deps.storage)?, &staking::msg::QueryMsg::Agent {})?;
+    if minter.minter != env.contract.address || agent != env.contract.address {
+        return Err(ContractError::HandOverPending { agent: import.agent.into() });
+    }
+
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT);
+    let mut imported = 0u32;
+    let done = loop {
+        let page_limit = limit - imported;
+        let start_after = import.start_after.take();
+        let keys = match import.step {
+            ImportStep::EarlyExitFees => {
+                let res: EarlyExitFeesResponse = deps.querier.query_wasm_smart(&import.agent, &QueryMsg::EarlyExitFees { start_after, limit: Some(page_limit) })?;
+                res.fees.into_iter()
+                    .map(|fee| EARLY_EXIT_FEES.save(deps.storage, &fee.nft_id, &fee.fee).map(|_| fee.nft_id))
+                    .collect::<StdResult<Vec<_>>>()?
+            },
+            ImportStep::RewardRoutes => {
+                let res: RewardRoutesResponse = deps.querier.query_wasm_smart(&import.agent, &QueryMsg::RewardRoutes { start_after, limit: Some(page_limit) })?;
+                res.routes.into_iter()
+                    .map(|route| REWARD_ROUTES.save(deps.storage, &route.nft_id, &route.route).map(|_| route.nft_id))
+                    .collect::<StdResult<Vec<_>>>()?
+            },
+            ImportStep::Beneficiaries => {
+                let res: BeneficiariesResponse = deps.querier.query_wasm_smart(&import.agent, &QueryMsg::Beneficiaries { start_after, limit: Some(page_limit) })?;
+                res.beneficiaries.into_iter()
+                    .map(|beneficiary| -> StdResult<String> {
+                        let address = deps.api.addr_validate(&beneficiary.address)?;
+                        BENEFICIARIES.save(deps.storage, &address, &Beneficiary { name: beneficiary.name, description_url: beneficiary.description_url, active: beneficiary.active })?;
+                        if !beneficiary.total_received.is_zero() {
+                            DONATED.save(deps.storage, &address, &beneficiary.total_received)?;
+                        }
+                        Ok(beneficiary.address)
+                    })
+                    .collect::<StdResult<Vec<_>>>()?
+            },
+        };
+
+        imported += keys.len() as u32;
+        if imported == limit {
+            import.start_after = keys.last().cloned();
+            break false;
+        }
+        import.step = match import.step {
+            ImportStep::EarlyExitFees => ImportStep::RewardRoutes,
+            ImportStep::RewardRoutes => ImportStep::Beneficiaries,
+            ImportStep::Beneficiaries => break true,
+        };
+    };
+
+    if done {
+        STATE_IMPORT.remove(deps.storage);
+    } else {
+        STATE_IMPORT.save(deps.storage, &import)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_import_state")
+        .add_attribute("imported", imported.to_string())
+        .add_attribute("done", done.to_string())
+    )
 }
 
+fn check_fees(fees: &Fees) -> Result<(), ContractError> {
+    let caps = [
+        ("deposit_fee", fees.deposit_fee_bps, MAX_DEPOSIT_FEE_BPS),
+        ("reward_commission", fees.reward_commission_bps, MAX_REWARD_COMMISSION_BPS),
+        ("early_exit_fee", fees.early_exit_fee_bps, MAX_EARLY_EXIT_FEE_BPS),
+    ];
+    for (fee, bps, max) in caps {
+        if bps > max {
+            return Err(ContractError::FeeAboveCap { fee: fee.to_string(), bps, max })
+        }
+    }
+    Ok(())
+}
+
+pub fn fee_amount(amount: Uint128, bps: u16) -> Uint128 {
+    amount.multiply_ratio(bps as u128, 10_000u128)
+}
+
+// Coins as an attribute value, like 100ujuno,50ibc/ATOM
+fn coins_string(coins: &[Coin]) -> String {
+    coins.iter().map(|coin| coin.to_string()).collect::<Vec<_>>().join(",")
+}
+
+// Adds amount to the coin of its denom, or as a new coin of the NFT
+fn add_coin(native: &mut Vec<Coin>, amount: &Coin) -> StdResult<()> {
+    match native.iter_mut().find(|native| native.denom == amount.denom) {
+        Some(native) => native.amount = native.amount.checked_add(amount.amount)?,
+        None => native.push(amount.clone()),
+    }
+    Ok(())
+}
+
+// Tokens of the bond denom, the only ones delegated to the validators
+fn bonded_amount(deps: Deps, native: &[Coin]) -> StdResult<Uint128> {
+    let bond_denom = deps.querier.query_bonded_denom()?;
+    Ok(native.iter().filter(|native| native.denom == bond_denom).map(|native| native.amount).sum())
+}
+
+// Every coin of the NFT must match the tokens of its denom bonded on the staking contract
+fn check_staking_bonded(deps: Deps, nft_id: &str, native: &[Coin], staking_contract_addr: &String) -> Result<(), ContractError> {
+    let mut staking_bonded = get_staking_bonded_coins(deps, nft_id.to_string(), staking_contract_addr)?;
+    let mut nft_bonded = native.to_vec();
+    staking_bonded.sort_by(|a, b| a.denom.cmp(&b.denom));
+    nft_bonded.sort_by(|a, b| a.denom.cmp(&b.denom));
+    if staking_bonded != nft_bonded {
+        return Err(ContractError::NFTStakingMismatch { staking: coins_string(&staking_bonded), nft: coins_string(&nft_bonded) })
+    }
+    Ok(())
+}
+
+// One staking message per coin of the NFT. The bond denom is undelegated and registered denoms are withdrawn from their adapter
+fn staking_unbond_msgs(deps: Deps, nft_id: Uint128, native: &[Coin], staking_contract_addr: &Addr) -> StdResult<Vec<WasmMsg>> {
+    let bond_denom = deps.querier.query_bonded_denom()?;
+    native
+        .iter()
+        .map(|native| match native.denom == bond_denom {
+            true => get_staking_unbond_msg(nft_id, native.amount, staking_contract_addr),
+            false => get_staking_unbond_denom_msg(nft_id, native.denom.clone(), native.amount, staking_contract_addr),
+        })
+        .collect()
+}
+
+fn staking_claim_msgs(deps: Deps, nft_id: Uint128, sender: &Addr, native: &[Coin], staking_contract_addr: &Addr) -> StdResult<Vec<WasmMsg>> {
+    let bond_denom = deps.querier.query_bonded_denom()?;
+    native
+        .iter()
+        .map(|native| match native.denom == bond_denom {
+            true => get_staking_claim_msg(nft_id, sender, native.amount, staking_contract_addr),
+            false => get_staking_claim_denom_msg(nft_id, sender, native.denom.clone(), native.amount, staking_contract_addr),
+        })
+        .collect()
+}
+
+// Staking messages run in order and only the last one replies, once every coin of the NFT is handled
+fn staking_submsgs(msgs: Vec<WasmMsg>, reply_id: u64) -> Vec<SubMsg> {
+    let last = msgs.len().saturating_sub(1);
+    msgs.into_iter()
+        .enumerate()
+        .map(|(i, msg)| match i == last {
+            true => SubMsg::reply_always(msg, reply_id),
+            false => SubMsg::new(msg),
+        })
+        .collect()
+}
+
+// Where the staking contract sends the claimed tokens and the messages paying them out.
+// With an early exit fee the agent receives the tokens, sends the fee to the treasury and the rest to recipient
+fn claim_payout(storage: &mut dyn Storage, env: &Env, nft_id: &str, claimed: &[Coin], recipient: &Addr) -> Result<(Addr, Vec<BankMsg>, Vec<Coin>), ContractError> {
+    let early_exit_fee = match EARLY_EXIT_FEES.may_load(storage, nft_id)? {
+        Some(early_exit_fee) => early_exit_fee,
+        None => return Ok((recipient.clone(), vec![], claimed.iter().map(|claimed| coin(0, &claimed.denom)).collect())),
+    };
+    EARLY_EXIT_FEES.remove(storage, nft_id);
+
+    // Each fee is taken from the claimed coin of its denom
+    let mut to_treasury : Vec<Coin> = vec![];
+    let mut to_recipient : Vec<Coin> = vec![];
+    for claimed in claimed {
+        let fee = early_exit_fee.iter().find(|fee| fee.denom == claimed.denom).map_or(Uint128::zero(), |fee| fee.amount);
+        if !fee.is_zero() {
+            to_treasury.push(coin(fee.u128(), &claimed.denom));
+        }
+        to_recipient.push(coin(claimed.amount.saturating_sub(fee).u128(), &claimed.denom));
+    }
+    let mut payout_msgs = vec![];
+    if !to_treasury.is_empty() {
+        payout_msgs.push(BankMsg::Send { to_address: TREASURY.load(storage)?, amount: to_treasury });
+    }
+    to_recipient.retain(|coin| !coin.amount.is_zero());
+    if !to_recipient.is_empty() {
+        payout_msgs.push(BankMsg::Send { to_address: recipient.to_string(), amount: to_recipient });
+    }
+    Ok((env.contract.address.clone(), payout_msgs, early_exit_fee))
+}
+
+pub fn execute_add_beneficiary(deps: DepsMut, _env: Env, info: MessageInfo, address: String, name: String, description_url: String)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    if is_active_beneficiary(deps.storage, &address)? {
+        return Err(ContractError::BeneficiaryAlreadyRegistered { address: address.into() })
+    }
+    BENEFICIARIES.save(deps.storage, &address, &Beneficiary { name, description_url, active: true })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_add_beneficiary")
+        .add_attribute("beneficiary", address)
+    )
+}
+
+pub fn execute_update_beneficiary(deps: DepsMut, _env: Env, info: MessageInfo, address: String, name: Option<String>, description_url: Option<String>)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut beneficiary = BENEFICIARIES.may_load(deps.storage, &address)?
+        .ok_or(ContractError::BeneficiaryNotFound { address: address.to_string() })?;
+    if let Some(name) = name {
+        beneficiary.name = name;
+    }
+    if let Some(description_url) = description_url {
+        beneficiary.description_url = description_url;
+    }
+    BENEFICIARIES.save(deps.storage, &address, &beneficiary)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_update_beneficiary")
+        .add_attribute("beneficiary", address)
+    )
+}
+
+pub fn execute_deactivate_beneficiary(deps: DepsMut, _env: Env, info: MessageInfo, address: String)-> Result<Response, ContractError>{
+    check_manager(deps.as_ref(), &info)?;
+    let address = deps.api.addr_validate(&address)?;
+    let mut beneficiary = BENEFICIARIES.may_load(deps.storage, &address)?
+        .ok_or(ContractError::BeneficiaryNotFound { address: address.to_string() })?;
+    if !beneficiary.active {
+        return Err(ContractError::BeneficiaryNotActive { address: address.into() })
+    }
+    beneficiary.active = false;
+    BENEFICIARIES.save(deps.storage, &address, &beneficiary)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_deactivate_beneficiary")
+        .add_attribute("beneficiary", address)
+    )
+}
+
+// Claimed NFTs are kept as receipts. Others can be claimed once their unbonding is released
+fn check_claimable(env: &Env, nft_id: &str, extension: &Metadata) -> Result<(), ContractError> {
+    match extension.status_at(env.block.time) {
+        Status::Claimable => Ok(()),
+        Status::Claimed => Err(ContractError::ClaimedNFT { nft_id: nft_id.to_string() }),
+        _ => Err(ContractError::NFTNotClaimable { nft_id: nft_id.to_string() }),
+    }
+}
+
+fn is_active_beneficiary(storage: &dyn Storage, address: &Addr) -> StdResult<bool> {
+    Ok(BENEFICIARIES.may_load(storage, address)?.is_some_and(|beneficiary| beneficiary.active))
+}
+
+fn check_manager(deps: Deps, info: &MessageInfo) -> Result<(), ContractError> {
+    if info.sender != MANAGER.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {  })
+    }
+    Ok(())
+}
+
+// Validator allocation and pending unbondings of the NFT, as recorded by the staking contract.
+// Bonded NFTs are Locked until their lock expires and unbonding ones are released with their last claim
+fn sync_staking_info(deps: Deps, env: &Env, nft_id: &str, extension: &mut Metadata) -> StdResult<()> {
+    let staking_contract_addr = STAKING.load(deps.storage)?;
+    extension.allocations = match extension.status.is_bonded() {
+        true => get_staking_allocation(deps, bonded_amount(deps, &extension.native)?, &staking_contract_addr)?,
+        false => vec![],
+    };
+    extension.unbonding = get_staking_claims(deps, nft_id.to_string(), &staking_contract_addr)?
+        .into_iter()
+        .map(|claim| PendingUnbonding { amount: claim.amount, release_at: claim.release_at, denom: None })
+        .collect();
+    extension.unbonding.extend(get_staking_denom_claims(deps, nft_id.to_string(), &staking_contract_addr)?
+        .into_iter()
+        .map(|claim| PendingUnbonding { amount: claim.amount, release_at: claim.release_at, denom: Some(claim.denom) }));
+    if extension.status.is_bonded() {
+        extension.status = Status::bonded(extension.lock_until, env.block.time);
+    } else if extension.status.is_unbonding() {
+        let release_at = extension.unbonding.iter().map(|unbonding| unbonding.release_at).max().unwrap_or(env.block.time);
+        extension.status = Status::Unbonding { release_at };
+        extension.status = extension.status_at(env.block.time);
+    }
+    Ok(())
+}
+
+// Claimed NFTs are burnt, or kept as receipts of the claimed tokens
+fn claimed_nft_msg(storage: &dyn Storage, nft_id: String, extension: Metadata, nft_contract_addr: &Addr) -> StdResult<WasmMsg> {
+    if CLAIM_RECEIPTS.may_load(storage)?.unwrap_or(false) {
+        let extension = Metadata { status: Status::Claimed, allocations: vec![], unbonding: vec![], ..extension };
+        get_cw721_update_metadata_msg(nft_id, None, extension, nft_contract_addr)
+    } else {
+        get_cw721_burn_msg(nft_id, nft_contract_addr)
+    }
+}
+
+// Locked NFTs can not be unbonded before the lock expires
+fn check_unlocked(env: &Env, nft_id: &str, extension: &Metadata) -> Result<(), ContractError> {
+    match extension.lock_until {
+        Some(until) if until > env.block.time => Err(ContractError::LockedNFT { nft_id: nft_id.to_string(), until }),
+        _ => Ok(()),
+    }
+}
+
+// Batch NFT ids can not be empty nor repeated
+fn check_batch(nft_ids: &[String]) -> Result<(), ContractError> {
+    if nft_ids.is_empty() {
+        return Err(ContractError::EmptyNFTBatch {  })
+    }
+    for (i, nft_id) in nft_ids.iter().enumerate() {
+        if nft_ids[..i].contains(nft_id) {
+            return Err(ContractError::DuplicateNFT { nft_id: nft_id.clone() })
+        }
+    }
+    Ok(())
+}
+
+// Each NFT is unbonded as on Unbond, its metadata being updated on the staking reply. All in the same transaction.
+// If any of them fails, the whole batch is reverted.
+pub fn execute_batch_unbond(mut deps: DepsMut, env: Env, info: MessageInfo, nft_ids: Vec<String>)-> Result<Response, ContractError>{
+    check_batch(&nft_ids)?;
+    let nft_contract_addr = NFT.load(deps.storage)?;
+
+    let mut submsgs : Vec<SubMsg> = vec![];
+    let mut events : Vec<Event> = vec![];
+    for nft_id in nft_ids.iter() {
+        let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+        if owner != info.sender {
+            return Err(ContractError::NotOwnerNFT {  })
+        }
+        let (nft_submsgs, event) = unbond_nft(deps.branch(), env.clone(), info.sender.clone(), nft_id.clone(), false)?;
+        submsgs.extend(nft_submsgs);
+        events.push(event);
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_batch_unbond")
+        .add_attribute("nft_count", nft_ids.len().to_string())
+        .add_submessages(submsgs)
+        .add_events(events)
+    )
+}
+
+// Staking messages go first and NFT burns after them, all in the same transaction.
+// If any of them fails, the whole batch is reverted.
+pub fn execute_batch_claim(mut deps: DepsMut, env: Env, info: MessageInfo, nft_ids: Vec<String>, recipient: Option<String>)-> Result<Response, ContractError>{
+    check_batch(&nft_ids)?;
+    let recipient = match recipient {
+        Some(recipient) => deps.api.addr_validate(&recipient)?,
+        None => info.sender.clone(),
+    };
+    let nft_contract_addr = NFT.load(deps.storage)?;
+    let staking_contract_addr= STAKING.load(deps.storage)?;
+
+    let mut staking_msgs : Vec<WasmMsg> = vec![];
+    let mut nft_msgs : Vec<WasmMsg> = vec![];
+    let mut bank_msgs : Vec<CosmosMsg> = vec![];
+    let mut events : Vec<Event> = vec![];
+    for nft_id in nft_ids.iter() {
+        let owner = get_nft_owner(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+        if owner != info.sender {
+            return Err(ContractError::NotOwnerNFT {  })
+        }
+        let extension = get_nft_metadata(deps.as_ref(), nft_id.clone(), &nft_contract_addr)?;
+        check_claimable(&env, nft_id, &extension)?;
+        let nft_amount = coins_string(&extension.native);
+        let nft_id_uint128 = Uint128::from_str(nft_id)?;
+
+        let (claim_to, payout_msgs, early_exit_fee) = claim_payout(deps.storage, &env, nft_id, &extension.native, &recipient)?;
+        staking_msgs.extend(staking_claim_msgs(deps.as_ref(), nft_id_uint128, &claim_to, &extension.native, &Addr::unchecked(&staking_contract_addr))?);
+        nft_msgs.push(claimed_nft_msg(deps.storage, nft_id.clone(), extension.clone(), &Addr::unchecked(&nft_contract_addr))?);
+        bank_msgs.extend(payout_msgs.into_iter().map(CosmosMsg::from));
+        if let Some((rewards_msgs, rewards_event)) = rewards_payout(deps.branch(), &env, nft_id, &recipient)? {
+            bank_msgs.extend(rewards_msgs);
+            events.push(rewards_event);
+        }
+        REWARD_ROUTES.remove(deps.storage, nft_id);
+        events.push(Event::new("claim_nft")
+            .add_attribute("nft_id", nft_id)
+            .add_attribute("amount", nft_amount)
+            .add_attribute("recipient", &recipient)
+            .add_attribute("early_exit_fee", coins_string(&early_exit_fee))
+        );
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "execute_batch_claim")
+        .add_attribute("nft_count", nft_ids.len().to_string())
+        .add_messages(staking_msgs)
+        .add_messages(nft_msgs)
+        .add_messages(bank_msgs)
+        .add_events(events)
+    )
+}
 
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn query(deps: Deps, _env: Env, msg: QueryMsg) -> StdResult<Binary> {
+pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
    
     match msg {
         QueryMsg::GetNFTAdress {  } => to_binary(&NFT.load(deps.storage)?),
         QueryMsg::GetStakingAdress {  } => to_binary(&STAKING.load(deps.storage)?),
-
+        QueryMsg::Position { nft_id } => to_binary(&query_position(deps, env, nft_id)?),
+        QueryMsg::PositionsByOwner { owner, start_after, limit } => to_binary(&query_positions_by_owner(deps, env, owner, start_after, limit)?),
+        QueryMsg::NextNftId {  } => to_binary(&NFT_ID.load(deps.storage)?),
+        QueryMsg::Fees {  } => to_binary(&FEES.may_load(deps.storage)?.unwrap_or_default()),
+        QueryMsg::Beneficiary { address } => to_binary(&query_beneficiary(deps, address)?),
+        QueryMsg::Beneficiaries { start_after, limit } => to_binary(&query_beneficiaries(deps, start_after, limit)?),
+        QueryMsg::Donations { start_after, limit } => to_binary(&query_donations(deps, start_after, limit)?),
+        QueryMsg::EarlyExitFees { start_after, limit } => to_binary(&query_early_exit_fees(deps, start_after, limit)?),
+        QueryMsg::RewardRoutes { start_after, limit } => to_binary(&query_reward_routes(deps, start_after, limit)?),
     }
 }
 
+// Status is the one at the current block time
+pub fn query_position(deps: Deps, env: Env, nft_id: String) -> StdResult<PositionResponse> {
+    let all_nft_info = get_nft_all_info(deps, nft_id.clone(), &NFT.load(deps.storage)?)?;
+    let staking = STAKING.load(deps.storage)?;
+    Ok(PositionResponse {
+        owner: all_nft_info.access.owner,
+        status: all_nft_info.info.extension.status_at(env.block.time),
+        metadata: all_nft_info.info.extension,
+        bonded: get_staking_bonded(deps, nft_id.clone(), &staking)?,
+        bonded_coins: get_staking_bonded_coins(deps, nft_id.clone(), &staking)?,
+        claims: get_staking_claims(deps, nft_id.clone(), &staking)?,
+        denom_claims: get_staking_denom_claims(deps, nft_id.clone(), &staking)?,
+        rewards: get_staking_rewards(deps, nft_id.clone(), &staking)?,
+        reward_route: REWARD_ROUTES.may_load(deps.storage, &nft_id)?,
+        nft_id,
+    })
+}
+
+pub fn query_positions_by_owner(deps: Deps, env: Env, owner: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<PositionsResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT);
+    let nft_ids = get_nft_tokens(deps, owner.into(), start_after, Some(limit), &NFT.load(deps.storage)?)?;
+    let positions = nft_ids
+        .into_iter()
+        .map(|nft_id| query_position(deps, env.clone(), nft_id))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(PositionsResponse { positions })
+}
+
+pub fn query_beneficiary(deps: Deps, address: String) -> StdResult<BeneficiaryResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let beneficiary = BENEFICIARIES.load(deps.storage, &address)?;
+    beneficiary_response(deps, address, beneficiary)
+}
+
+pub fn query_beneficiaries(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<BeneficiariesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(Addr::unchecked);
+    let beneficiaries = BENEFICIARIES
+        .range(deps.storage, start_after.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.and_then(|(address, beneficiary)| beneficiary_response(deps, address, beneficiary)))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(BeneficiariesResponse { beneficiaries })
+}
+
+fn beneficiary_response(deps: Deps, address: Addr, beneficiary: Beneficiary) -> StdResult<BeneficiaryResponse> {
+    Ok(BeneficiaryResponse {
+        total_received: DONATED.may_load(deps.storage, &address)?.unwrap_or_default(),
+        address: address.into(),
+        name: beneficiary.name,
+        description_url: beneficiary.description_url,
+        active: beneficiary.active,
+    })
+}
+
+pub fn query_donations(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<DonationsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(Addr::unchecked);
+    let donations = DONATED
+        .range(deps.storage, start_after.as_ref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(beneficiary, amount)| Donation { beneficiary: beneficiary.into(), amount }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(DonationsResponse { donations })
+}
+
+pub fn query_early_exit_fees(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<EarlyExitFeesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let fees = EARLY_EXIT_FEES
+        .range(deps.storage, start_after.as_deref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(nft_id, fee)| EarlyExitFee { nft_id, fee }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(EarlyExitFeesResponse { fees })
+}
+
+pub fn query_reward_routes(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<RewardRoutesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let routes = REWARD_ROUTES
+        .range(deps.storage, start_after.as_deref().map(Bound::exclusive), None, Order::Ascending)
+        .take(limit)
+        .map(|item| item.map(|(nft_id, route)| NftRewardRoute { nft_id, route }))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(RewardRoutesResponse { routes })
+}
+
 #[cfg_attr(not(feature = "library"), entry_point)]
-pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, ContractError> {
+pub fn reply(deps: DepsMut, env: Env, reply: Reply) -> Result<Response, ContractError> {
     let wasm_msg : WasmMsg;
     let reply_key: u64;
     let submsg:SubMsg<Empty> ;
     let mut vec_submsg : Vec<SubMsg<Empty>> = vec![];
 
-    match (reply.clone().id, reply.clone().result) {
-        (INSTANTIATE_NFT_REPLY_ID, SubMsgResult::Ok(_))=> {
+    // Staking submessages reply with a unique id. Their pending operation holds the step to handle
+    let pending_op = if reply.id >= FIRST_PENDING_REPLY_ID {
+        Some(take_pending_op(deps.storage, reply.id)?)
+    } else {
+        None
+    };
+    let step = pending_op.as_ref().map_or(reply.id, |pending_op| pending_op.step);
+
+    match (step, reply.clone().result, pending_op) {
+        (INSTANTIATE_NFT_REPLY_ID, SubMsgResult::Ok(_), None)=> {
             let res = parse_reply_instantiate_data(reply.clone()).unwrap();  
             let addr = deps.api.addr_validate(res.contract_address.clone().as_str())?;
             NFT.save(deps.storage, &addr.to_string())?;
         },
-        (INSTANTIATE_STAKING_REPLY_ID, SubMsgResult::Ok(_))=>{
+        (INSTANTIATE_STAKING_REPLY_ID, SubMsgResult::Ok(_), None)=>{
             let res = parse_reply_instantiate_data(reply.clone()).unwrap();  
             let addr = deps.api.addr_validate(res.contract_address.clone().as_str())?;
             STAKING.save(deps.storage, &addr.to_string())?;
         },
-        (EXECUTE_NEW_BOND_STAKING_REPLY_ID, SubMsgResult::Ok(_))=>{
-            let cache_nft = CACHE_NFT.load(deps.storage)?;
+        (EXECUTE_NEW_BOND_STAKING_REPLY_ID, SubMsgResult::Ok(_), Some(mut pending_op))=>{
             reply_key = EXECUTE_NEW_BOND_NFT_REPLY_ID;
+            sync_staking_info(deps.as_ref(), &env, &pending_op.nft_id, &mut pending_op.extension)?;
             wasm_msg = get_cw721_mint_msg(
-                &cache_nft.sender, 
-                cache_nft.nft_id, 
+                &pending_op.sender, 
+                pending_op.nft_id, 
                 None,
-                cache_nft.extension, 
+                pending_op.extension, 
                 &Addr::unchecked(NFT.load(deps.storage)?)
             )?;
             submsg= SubMsg::reply_always(wasm_msg, reply_key);
             vec_submsg.push(submsg);
-
-            // Cleaning Cache
-            let blank_cache = CacheNFT{ sender: Addr::unchecked("blank"), nft_id: String::from("blank"), extension: Metadata { native: vec![], status: Status::Bonded }};
-            CACHE_NFT.save(deps.storage,&blank_cache)?;
         },
-        (EXECUTE_RE_BOND_STAKING_REPLY_ID, SubMsgResult::Ok(_))=>{
-            let cache_nft = CACHE_NFT.load(deps.storage)?;
+        (EXECUTE_RE_BOND_STAKING_REPLY_ID, SubMsgResult::Ok(_), Some(mut pending_op))=>{
             reply_key = EXECUTE_RE_BOND_NFT_REPLY_ID;
+            sync_staking_info(deps.as_ref(), &env, &pending_op.nft_id, &mut pending_op.extension)?;
             wasm_msg = get_cw721_update_metadata_msg(
-                cache_nft.nft_id, 
+                pending_op.nft_id, 
                 None, 
-                cache_nft.extension, 
+                pending_op.extension, 
                 &Addr::unchecked(NFT.load(deps.storage)?)
             )?;
             submsg= SubMsg::reply_always(wasm_msg, reply_key);  
             vec_submsg.push(submsg);
-            
-            // Cleaning Cache
-            let blank_cache = CacheNFT{ sender: Addr::unchecked("blank"), nft_id: String::from("blank"), extension: Metadata { native: vec![], status: Status::Bonded }};
-            CACHE_NFT.save(deps.storage,&blank_cache)?;
         },
-        (EXECUTE_UNBOND_STAKING_REPLY_ID, SubMsgResult::Ok(_))=>{
-            let cache_nft = CACHE_NFT.load(deps.storage)?;
+        (EXECUTE_UNBOND_STAKING_REPLY_ID, SubMsgResult::Ok(_), Some(mut pending_op))=>{
             reply_key = EXECUTE_UNBOND_NFT_REPLY_ID;
+            sync_staking_info(deps.as_ref(), &env, &pending_op.nft_id, &mut pending_op.extension)?;
             wasm_msg = get_cw721_update_metadata_msg(
-                cache_nft.nft_id, 
+                pending_op.nft_id, 
                 None, 
-                cache_nft.extension, 
+                pending_op.extension, 
                 &Addr::unchecked(NFT.load(deps.storage)?)
             )?;
             submsg= SubMsg::reply_always(wasm_msg, reply_key);  
             vec_submsg.push(submsg);
-            
-            // Cleaning Cache
-            let blank_cache = CacheNFT{ sender: Addr::unchecked("blank"), nft_id: String::from("blank"), extension: Metadata { native: vec![], status: Status::Bonded }};
-            CACHE_NFT.save(deps.storage,&blank_cache)?;
         },
-        (EXECUTE_CLAIM_STAKING_REPLY_ID, SubMsgResult::Ok(_))=>{
-            let cache_nft = CACHE_NFT.load(deps.storage)?;
+        (EXECUTE_CLAIM_STAKING_REPLY_ID, SubMsgResult::Ok(_), Some(pending_op))=>{
             reply_key = EXECUTE_CLAIM_NFT_REPLY_ID;
-            wasm_msg = get_cw721_burn_msg(cache_nft.nft_id, &Addr::unchecked(NFT.load(deps.storage)?))?;
+            wasm_msg = claimed_nft_msg(deps.storage, pending_op.nft_id, pending_op.extension, &Addr::unchecked(NFT.load(deps.storage)?))?;
             submsg= SubMsg::reply_always(wasm_msg, reply_key);  
             vec_submsg.push(submsg);
-            
-            // Cleaning Cache
-            let blank_cache = CacheNFT{ sender: Addr::unchecked("blank"), nft_id: String::from("blank"), extension: Metadata { native: vec![], status: Status::Bonded }};
-            CACHE_NFT.save(deps.storage,&blank_cache)?;
         },
-        (EXECUTE_NEW_BOND_NFT_REPLY_ID, SubMsgResult::Ok(_))=>{},
-        (EXECUTE_RE_BOND_NFT_REPLY_ID, SubMsgResult::Ok(_))=>{},
-        (EXECUTE_UNBOND_NFT_REPLY_ID, SubMsgResult::Ok(_))=>{},
-        (EXECUTE_CLAIM_NFT_REPLY_ID, SubMsgResult::Ok(_))=>{},
-        // (INSTANTIATE_NFT_REPLY_ID, SubMsgResult::Err(_))=> {return Err(ContractError::NFTContractNotInstantiated {  });},
-        // (INSTANTIATE_STAKING_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::StakingContractNotInstantiated {  })},
-        // (EXECUTE_NEW_BOND_NFT_REPLY_ID, SubMsgResult::Err(_))=>{ return Err(ContractError::UnableMintNFT {  } ) },
-        // (EXECUTE_RE_BOND_NFT_REPLY_ID, SubMsgResult::Err(_))=>{  return Err(ContractError::UnableUpdateNFTMetadata {  }) },
-        // (EXECUTE_UNBOND_NFT_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToUnbondNFT {  })}, 
-        // (EXECUTE_CLAIM_NFT_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToBurnNFT {  }) },       
-        // (EXECUTE_NEW_BOND_STAKING_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToStakeBondNewNFT {  })},
-        // (EXECUTE_RE_BOND_STAKING_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToStakeReBondNFT {  })},
-        // (EXECUTE_UNBOND_STAKING_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToUnbondStaking {  }) },
- //       (EXECUTE_CLAIM_STAKING_REPLY_ID, SubMsgResult::Err(_))=>{return Err(ContractError::UnableToClaimStaking {  }) },
-        (_ , _) => { return Err(ContractError::UnknownReplyIdSubMsgResult { id: reply.id.to_string() });   },
+        (EXECUTE_NEW_BOND_NFT_REPLY_ID, SubMsgResult::Ok(_), None)=>{},
+        (EXECUTE_RE_BOND_NFT_REPLY_ID, SubMsgResult::Ok(_), None)=>{},
+        (EXECUTE_UNBOND_NFT_REPLY_ID, SubMsgResult::Ok(_), None)=>{},
+        (EXECUTE_CLAIM_NFT_REPLY_ID, SubMsgResult::Ok(_), None)=>{},
+        (INSTANTIATE_NFT_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::NFTContractNotInstantiated {  }) },
+        (INSTANTIATE_STAKING_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::StakingContractNotInstantiated {  }) },
+        (EXECUTE_NEW_BOND_STAKING_REPLY_ID, SubMsgResult::Err(_), Some(_))=> { return Err(ContractError::UnableToStakeBondNewNFT {  }) },
+        (EXECUTE_RE_BOND_STAKING_REPLY_ID, SubMsgResult::Err(_), Some(_))=> { return Err(ContractError::UnableToStakeReBondNFT {  }) },
+        (EXECUTE_UNBOND_STAKING_REPLY_ID, SubMsgResult::Err(_), Some(_))=> { return Err(ContractError::UnableToUnbondStaking {  }) },
+        (EXECUTE_CLAIM_STAKING_REPLY_ID, SubMsgResult::Err(_), Some(_))=> { return Err(ContractError::UnableToClaimStaking {  }) },
+        // Failing NFT steps abort the whole transaction, reverting the staking step that preceded them
+        (EXECUTE_NEW_BOND_NFT_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::UnableMintNFT {  }) },
+        (EXECUTE_RE_BOND_NFT_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::UnableUpdateNFTMetadata {  }) },
+        (EXECUTE_UNBOND_NFT_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::UnableToUnbondNFT {  }) },
+        (EXECUTE_CLAIM_NFT_REPLY_ID, SubMsgResult::Err(_), None)=> { return Err(ContractError::UnableToBurnNFT {  }) },
+        (_ , _, _) => { return Err(ContractError::UnknownReplyIdSubMsgResult { id: reply.id.to_string() });   },
       }
 
       Ok(Response::new()
@@ -369,5 +1352,81 @@ pub fn reply(deps: DepsMut, _env: Env, reply: Reply) -> Result<Response, Contrac
 
 #[cfg(test)]
 mod tests {
- 
+    use super::*;
+    use cosmwasm_std::testing::{mock_dependencies, mock_env};
+    use cosmwasm_std::{coin, ContractResult, SubMsgResponse, SystemResult};
+
+    fn staking_reply(id: u64) -> Reply {
+        Reply { id, result: SubMsgResult::Ok(SubMsgResponse { events: vec![], data: None }) }
+    }
+
+    fn pending_op() -> PendingOp {
+        PendingOp {
+            step: EXECUTE_UNBOND_STAKING_REPLY_ID,
+            sender: Addr::unchecked("owner"),
+            nft_id: "1".to_string(),
+            extension: Metadata { native: vec![coin(1000, "TOKEN")], status: Status::Unbonding { release_at: Default::default() }, lock_until: None, ..Default::default() },
+        }
+    }
+
+    #[test]
+    fn pending_ops_unique_reply_ids() {
+        let mut deps = mock_dependencies();
+        let first = save_pending_op(deps.as_mut().storage, &pending_op()).unwrap();
+        let second = save_pending_op(deps.as_mut().storage, &pending_op()).unwrap();
+        assert_eq!(first, FIRST_PENDING_REPLY_ID);
+        assert_eq!(second, FIRST_PENDING_REPLY_ID + 1);
+
+        // Each entry is removed when its reply is handled
+        let op = take_pending_op(deps.as_mut().storage, first).unwrap();
+        assert_eq!(op, pending_op());
+        assert!(!PENDING_OPS.has(deps.as_ref().storage, first));
+        assert!(PENDING_OPS.has(deps.as_ref().storage, second));
+    }
+
+    #[test]
+    fn reply_rejects_missing_and_stale_pending_ops() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        NFT.save(deps.as_mut().storage, &"nft".to_string()).unwrap();
+        STAKING.save(deps.as_mut().storage, &"staking".to_string()).unwrap();
+        // Staking contract without claims for the unbonded NFT
+        deps.querier.update_wasm(|_| SystemResult::Ok(ContractResult::Ok(to_binary(&staking::msg::NftClaimsResponse { claims: vec![] }).unwrap())));
+
+        // No pending operation for the reply id
+        let err = reply(deps.as_mut(), env.clone(), staking_reply(FIRST_PENDING_REPLY_ID)).unwrap_err();
+        assert_eq!(err, ContractError::PendingOpNotFound { id: FIRST_PENDING_REPLY_ID.to_string() });
+
+        // An entry left under the next reply id is stale, new operations are rejected instead of overwriting it
+        PENDING_OPS.save(deps.as_mut().storage, FIRST_PENDING_REPLY_ID, &pending_op()).unwrap();
+        let err = save_pending_op(deps.as_mut().storage, &pending_op()).unwrap_err();
+        assert_eq!(err, ContractError::StalePendingOp { id: FIRST_PENDING_REPLY_ID.to_string() });
+        PENDING_OPS.remove(deps.as_mut().storage, FIRST_PENDING_REPLY_ID);
+
+        // Operation from the current block is handled once
+        let id = save_pending_op(deps.as_mut().storage, &pending_op()).unwrap();
+        let res = reply(deps.as_mut(), env.clone(), staking_reply(id)).unwrap();
+        assert_eq!(res.messages.len(), 1);
+        assert_eq!(res.messages[0].id, EXECUTE_UNBOND_NFT_REPLY_ID);
+        let err = reply(deps.as_mut(), env, staking_reply(id)).unwrap_err();
+        assert_eq!(err, ContractError::PendingOpNotFound { id: id.to_string() });
+    }
+
+    #[test]
+    fn reply_maps_submsg_failures() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let failed = |id| Reply { id, result: SubMsgResult::Err("failure".to_string()) };
+
+        let id = save_pending_op(deps.as_mut().storage, &pending_op()).unwrap();
+        let err = reply(deps.as_mut(), env.clone(), failed(id)).unwrap_err();
+        assert_eq!(err, ContractError::UnableToUnbondStaking {  });
+
+        let err = reply(deps.as_mut(), env.clone(), failed(EXECUTE_NEW_BOND_NFT_REPLY_ID)).unwrap_err();
+        assert_eq!(err, ContractError::UnableMintNFT {  });
+        let err = reply(deps.as_mut(), env.clone(), failed(EXECUTE_CLAIM_NFT_REPLY_ID)).unwrap_err();
+        assert_eq!(err, ContractError::UnableToBurnNFT {  });
+        let err = reply(deps.as_mut(), env, failed(INSTANTIATE_STAKING_REPLY_ID)).unwrap_err();
+        assert_eq!(err, ContractError::StakingContractNotInstantiated {  });
+    }
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use cosmwasm_std::{StdError};
+use cosmwasm_std::{StdError, Timestamp, Uint128};
 use thiserror::Error;
 
 #[derive(Error, Debug, PartialEq)]
@@ -36,7 +36,7 @@ pub enum ContractError {
     #[error("Can not rebond and unbonding NFT")]
     UnbondingNFT {},
 
-    #[error("NFT only supports one native coin")]
+    #[error("Only NFTs holding the bond denom alone support this operation")]
     OnlyOneNativeCoinPerNFT {},
 
     #[error("Unable to mint NFT")]
@@ -64,5 +64,74 @@ pub enum ContractError {
     UnableToClaimStaking {},
 
     #[error("NFT amount mismatch nft: {nft}  staking: {staking}")]
-    NFTStakingMismatch { staking: String, nft:String }
+    NFTStakingMismatch { staking: String, nft:String },
+
+    #[error("No NFT ids sent")]
+    EmptyNFTBatch {},
+
+    #[error("NFT {nft_id} is repeated in the batch")]
+    DuplicateNFT { nft_id: String },
+
+    #[error("Merge needs at least two NFTs")]
+    MergeTooFewNFTs {},
+
+    #[error("NFT {nft_id} is not bonded")]
+    NFTNotBonded { nft_id: String },
+
+    #[error("NFT {nft_id} was already claimed")]
+    ClaimedNFT { nft_id: String },
+
+    #[error("NFT {nft_id} is not done unbonding")]
+    NFTNotClaimable { nft_id: String },
+
+    #[error("Split amounts must be non zero and leave part of the NFT amount")]
+    InvalidSplitAmounts {},
+
+    #[error("Lock must be a time duration")]
+    InvalidLock {},
+
+    #[error("Lock duration is too long")]
+    LockTooLong {},
+
+    #[error("NFT {nft_id} is locked until {until}")]
+    LockedNFT { nft_id: String, until: Timestamp },
+
+    #[error("{fee} of {bps} bps is above the cap of {max} bps")]
+    FeeAboveCap { fee: String, bps: u16, max: u16 },
+
+    #[error("Owner can only be set when minting a new NFT")]
+    OwnerOnRebond {},
+
+    #[error("Reward route shares must add up to 10000 bps, with distinct and non zero beneficiaries")]
+    InvalidRewardRoute {},
+
+    #[error("Beneficiary {address} is already registered")]
+    BeneficiaryAlreadyRegistered { address: String },
+
+    #[error("Beneficiary {address} is not registered")]
+    BeneficiaryNotFound { address: String },
+
+    #[error("Beneficiary {address} is not active")]
+    BeneficiaryNotActive { address: String },
+
+    #[error("NFT {nft_id} has no rewards to claim")]
+    NoRewards { nft_id: String },
+
+    #[error("No pending operation for reply_id: {id}")]
+    PendingOpNotFound { id: String },
+
+    #[error("Stale pending operation for reply_id: {id}")]
+    StalePendingOp { id: String },
+
+    #[error("Contract {contract} is not managed by this agent")]
+    ForeignContract { contract: String },
+
+    #[error("First NFT id must be at least {min}")]
+    FirstNftIdInUse { min: Uint128 },
+
+    #[error("Contracts adopted from {agent} must be handed over first")]
+    HandOverPending { agent: String },
+
+    #[error("State of the previous agent must be imported first")]
+    ImportPending {},
 }
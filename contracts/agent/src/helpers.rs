@@ -17,7 +17,7 @@ impl AgentContract {
         Ok(WasmMsg::Execute {
             contract_addr: self.addr().into(),
             msg,
-            funds: funds,
+            funds,
         }
         .into())
     }
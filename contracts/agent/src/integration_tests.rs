@@ -1,17 +1,21 @@
 #[cfg(test)]
 mod tests {
     use crate::error::ContractError;
-    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-    use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor, StakingInfo};
+    use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, PositionResponse, PositionsResponse, ReceiveNftMsg, Donation, DonationsResponse, BeneficiaryResponse, BeneficiariesResponse, EarlyExitFee, EarlyExitFeesResponse, NftRewardRoute, RewardRoutesResponse};
+    use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor, StakingInfo, StakingSudo, SudoMsg};
     use nft::contract::{Metadata, Status};
+    use nft::state::{CollectionInfo, Royalty, TransferPolicy};
     use crate::helpers::{AgentContract };
-    use cosmwasm_std::{Addr, Coin, Empty, Uint128, Decimal, Validator, coin,};
+    use crate::state::{Fees, RewardRoute, BeneficiaryShare};
+    use cosmwasm_std::{Addr, Coin, Empty, Uint128, Decimal, Validator, coin, to_binary, DepsMut, Env, MessageInfo, Response, StdError, StdResult};
+    use cw_storage_plus::Item;
     // use cosmwasm_std::testing::{ mock_env};
     use cosmwasm_std::testing::mock_env;
-    use cw_utils::WEEK;
+    use cw_utils::{Duration, DAY, WEEK};
 
 
     const NATIVE_DENOM: &str = "ujunox";
+    const LSD_DENOM: &str = "stujunox";
    const MANAGER1: &str = "juno148v3g2dpjeq6hwnlagmvq8pnqe5r9wjcrvel8u";
     // const MANAGER1: &str = "manager";
     const AGENT1: &str = "juno15urq2dtp9qce4fyc85m6upwm9xul30492fasy3";
@@ -58,6 +62,31 @@ mod tests {
         Box::new(contract)
     }
 
+    // NFT contract whose executions fail while switched on through sudo
+    const NFT_FAILING: Item<bool> = Item::new("nft_failing");
+
+    fn failing_nft_execute(deps: DepsMut, env: Env, info: MessageInfo, msg: nft::msg::ExecuteMsg) -> Result<Response, nft::contract::ContractError> {
+        if NFT_FAILING.may_load(deps.storage)?.unwrap_or(false) {
+            return Err(StdError::generic_err("nft failure").into());
+        }
+        nft::contract::entry::execute(deps, env, info, msg)
+    }
+
+    fn failing_nft_sudo(deps: DepsMut, _env: Env, failing: bool) -> StdResult<Response> {
+        NFT_FAILING.save(deps.storage, &failing)?;
+        Ok(Response::new())
+    }
+
+    pub fn contract_failing_nft() -> Box<dyn Contract<Empty>> {
+        let contract = ContractWrapper::new(
+            failing_nft_execute,
+            nft::contract::entry::instantiate,
+            nft::contract::entry::query,
+        )
+        .with_sudo(failing_nft_sudo);
+        Box::new(contract)
+    }
+
     fn mock_app() -> App {
         AppBuilder::new().build(|router, api, storage| {
             let env = mock_env();
@@ -80,6 +109,9 @@ mod tests {
                     vec![Coin {
                         denom: NATIVE_DENOM.to_string(),
                         amount: Uint128::new(4000),
+                    }, Coin {
+                        denom: LSD_DENOM.to_string(),
+                        amount: Uint128::new(1000),
                     }],
                 )
                 .unwrap();
@@ -171,8 +203,12 @@ mod tests {
         (app, code_id_agent, code_id_staking, code_id_nft)
     }
 
+    fn default_instantiate_msg(nft_code_id: u64, staking_code_id: u64) -> InstantiateMsg {
+        InstantiateMsg{ nft_code_id, staking_code_id, admin: ADMIN.to_string(), manager: MANAGER1.to_string(), treasury: TREASURY1.to_string(), fees: None, claim_receipts: None, royalty: None, nft_name: None, nft_symbol: None, nft_label: None, staking_label: None, collection_info: None, nft_contract: None, staking_contract: None, first_nft_id: None }
+    }
+
     pub fn agent_angel_instantiate(app: &mut App, agent_code_id: u64, nft_code_id: u64, staking_code_id: u64, manager: String, treasury: String,) -> AgentContract {
-        let msg = InstantiateMsg{ nft_code_id, staking_code_id, admin: ADMIN.to_string(), manager, treasury };
+        let msg = InstantiateMsg { manager, treasury, ..default_instantiate_msg(nft_code_id, staking_code_id) };
         let contract = app
             .instantiate_contract(
                 agent_code_id,
@@ -199,18 +235,24 @@ mod tests {
 
     fn add_3_validators(
         app: &mut App,
-        staking_contract_addr: &String,
+        staking_contract_addr: &str,
         sender: Addr,
         val1: String,
         val2: String,
         val3: String,
     ) {
-        let msg = staking::msg::ExecuteMsg::AddValidator { address: val1.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
-        app.execute_contract(sender.clone(), Addr::unchecked(&staking_contract_addr.clone()), &msg, &[]).unwrap();
-        let msg = staking::msg::ExecuteMsg::AddValidator { address: val2.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
-        app.execute_contract(sender.clone(), Addr::unchecked(&staking_contract_addr.clone()), &msg, &[]).unwrap();
-        let msg = staking::msg::ExecuteMsg::AddValidator { address: val3.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
-        app.execute_contract(sender.clone(), Addr::unchecked(&staking_contract_addr.clone()), &msg, &[]).unwrap();
+        let msg = staking::msg::ExecuteMsg::AddValidator { address: val1, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        app.execute_contract(sender.clone(), Addr::unchecked(staking_contract_addr), &msg, &[]).unwrap();
+        let msg = staking::msg::ExecuteMsg::AddValidator { address: val2, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        app.execute_contract(sender.clone(), Addr::unchecked(staking_contract_addr), &msg, &[]).unwrap();
+        let msg = staking::msg::ExecuteMsg::AddValidator { address: val3, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        app.execute_contract(sender.clone(), Addr::unchecked(staking_contract_addr), &msg, &[]).unwrap();
+    }
+
+    // Status of an NFT unbonded on the current block from validators with a week unbonding period
+    fn unbonding_status(app: &App) -> Status {
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        Status::Unbonding { release_at: app.block_info().time.plus_seconds(week) }
     }
 
     fn get_nft_all_info(app: &App, nft_contract_addr: String, token_id: String) -> cw721::AllNftInfoResponse<Metadata> {
@@ -259,7 +301,7 @@ mod tests {
         add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
 
         //USER 1 BONDS NFT_ID 0  with 600 tokens
-        let msg = ExecuteMsg::Bond { nft_id: None };
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
         app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
 
         // NFT minted, bonded, right amount and right owner
@@ -267,6 +309,8 @@ mod tests {
         assert_eq!(all_nft_info.access.owner, String::from(USER1));
         assert_eq!(all_nft_info.info.extension.native, vec![coin(600u128, NATIVE_DENOM)]);
         assert_eq!(all_nft_info.info.extension.status, Status::Bonded);
+        // Rendered on chain by the NFT contract
+        assert_eq!(all_nft_info.info.token_uri, Some(nft::token_uri::token_uri("angel_staking_nft", "0", &all_nft_info.info.extension).unwrap()));
 
         query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(0u128), Uint128::from(0u128));
         // Staking contract has delegated the 600 tokens to VALIDATOR1
@@ -274,7 +318,7 @@ mod tests {
         // assert_eq!(full_delegation.amount.amount,Uint128::from(600u128));       
 
         //USER 2 BONDS NFT_ID 1  with 400 tokens
-        let msg = ExecuteMsg::Bond { nft_id: None };
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
         app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
 
         // NFT minted, bonded, right amount and right owner
@@ -287,7 +331,7 @@ mod tests {
         query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(400u128), Uint128::from(0u128));
 
         //USER 3 BONDS NFT_ID 2  with 200 tokens
-        let msg = ExecuteMsg::Bond { nft_id: None };
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
         app.execute_contract(Addr::unchecked(USER3), agent_contract.addr(), &msg, &[coin(200, NATIVE_DENOM.to_string())]).unwrap();
 
         // NFT minted, bonded, right amount and right owner
@@ -300,7 +344,7 @@ mod tests {
         query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(400u128), Uint128::from(200u128));
 
         //USER 1 Re-BONDS NFT_ID 0  with 1000 tokens. Bonded to the validator with the least amount of bonded tokens
-        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()) };
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
         app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
 
         let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
@@ -310,10 +354,10 @@ mod tests {
 
         query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(400u128), Uint128::from(1200u128));
 
-        //USER 1 tries to Re-BOND another user's NFT_ID 1. Not the owner. Not allowed.
-        let msg = ExecuteMsg::Bond { nft_id: Some("1".to_string()) };
+        //USER 1 tries to Re-BOND NFT_ID 1 setting a new owner. Not allowed, a NFT is never transferred on bond.
+        let msg = ExecuteMsg::Bond { nft_id: Some("1".to_string()), owner: Some(USER1.to_string()), lock: None, reward_route: None };
         let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap_err();
-        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OwnerOnRebond {  });
 
         //USER 1 tries to Unbond another user's NFT with id 1 (not the owner) 
         let msg = ExecuteMsg::Unbond { nft_id: "1".to_string() };
@@ -326,20 +370,1092 @@ mod tests {
         let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "1".to_string());
         assert_eq!(all_nft_info.access.owner, String::from(USER2));
         assert_eq!(all_nft_info.info.extension.native, vec![coin(400u128, NATIVE_DENOM)]);
-        assert_eq!(all_nft_info.info.extension.status, Status::Unbonding);
+        assert_eq!(all_nft_info.info.extension.status, unbonding_status(&app));
         // staking contract will choose the two validator's with most amount of bonded tokens and will unbound half of the 400 from each.
         // VALIDATOR2: 600 - 200 = 400. VALIDATOR3: 1200 - 200 =1000. 
         query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(400u128), Uint128::from(400u128), Uint128::from(1000u128));      
   
         // USER 1 can not claim a NFT owned by USER2
-        let msg = ExecuteMsg::Claim { nft_id: "1".to_string() };
+        let msg = ExecuteMsg::Claim { nft_id: "1".to_string(), recipient: None };
         let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap_err();  
         assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
 
         // USER 2 can claim his NFT
         // Getting error because There is nothing to claim yet as on Integration tests we still do not get unbonding tokens sent to the delegator by the validator.
-        let msg = ExecuteMsg::Claim { nft_id: "1".to_string() };
+        let msg = ExecuteMsg::Claim { nft_id: "1".to_string(), recipient: None };
         app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap_err();  
     }
 
-}
\ No newline at end of file
+
+    #[test]
+    fn batch_unbond_claim() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // USER1 bonds NFT_ID 0, 1 and 2. USER2 bonds NFT_ID 3
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM)]).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM)]).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(200, NATIVE_DENOM)]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(300, NATIVE_DENOM)]).unwrap();
+
+        let msg = ExecuteMsg::BatchUnbond { nft_ids: vec![] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::EmptyNFTBatch {  });
+
+        let msg = ExecuteMsg::BatchUnbond { nft_ids: vec!["0".to_string(), "0".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::DuplicateNFT { nft_id: "0".to_string() });
+
+        // NFT_ID 3 is owned by USER2. Nothing is unbonded
+        let msg = ExecuteMsg::BatchUnbond { nft_ids: vec!["0".to_string(), "3".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.info.extension.status, Status::Bonded);
+
+        let msg = ExecuteMsg::BatchUnbond { nft_ids: vec!["0".to_string(), "1".to_string()] };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let unbond_events : Vec<_> = res.events.iter().filter(|event| event.ty == "wasm-unbond_nft").collect();
+        assert_eq!(unbond_events.len(), 2);
+        assert_eq!(unbond_events[1].attributes[1], ("nft_id", "1"));
+        assert_eq!(unbond_events[1].attributes[2], ("amount", "400ujunox"));
+
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.info.extension.status, unbonding_status(&app));
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "1".to_string());
+        assert_eq!(all_nft_info.info.extension.status, unbonding_status(&app));
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "2".to_string());
+        assert_eq!(all_nft_info.info.extension.status, Status::Bonded);
+
+        // Claims have not matured yet
+        let msg = ExecuteMsg::BatchClaim { nft_ids: vec!["0".to_string(), "1".to_string()], recipient: Some(USER3.to_string()) };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NFTNotClaimable { nft_id: "0".to_string() });
+
+        // After the unbonding period the staking module releases the undelegated tokens
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+        let balance_before = app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount;
+        let recipient_before = app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount;
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let claim_events : Vec<_> = res.events.iter().filter(|event| event.ty == "wasm-claim_nft").collect();
+        assert_eq!(claim_events.len(), 2);
+        assert_eq!(claim_events[0].attributes[3], ("recipient", USER3));
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, balance_before);
+        let recipient_after = app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount;
+        assert_eq!(recipient_after - recipient_before, Uint128::from(1000u128));
+
+        // Claimed NFTs are burnt
+        let res : cw721::NumTokensResponse = app.wrap()
+            .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::NumTokens {  })
+            .unwrap();
+        assert_eq!(res.count, 2);
+    }
+
+    #[test]
+    fn failed_nft_step_reverts_staking() {
+        let (mut app, code_id_agent, code_id_staking, _) = store_code();
+        let code_id_nft = app.store_code(contract_failing_nft());
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        app.wasm_sudo(Addr::unchecked(&nft_contract_addr), &true).unwrap();
+
+        // Mint fails: nothing is delegated and no NFT is minted
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnableMintNFT {  });
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::zero(), Uint128::zero());
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3400u128));
+        let res : cw721::NumTokensResponse = app.wrap()
+            .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::NumTokens {  })
+            .unwrap();
+        assert_eq!(res.count, 1);
+
+        // Metadata update fails: the top-up is not delegated
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnableUpdateNFTMetadata {  });
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::zero(), Uint128::zero());
+        let bonded : Uint128 = app.wrap().query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(bonded, Uint128::from(600u128));
+
+        // Unbond fails: tokens stay delegated and no claim is created
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnableToUnbondNFT {  });
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::zero(), Uint128::zero());
+        let claims : staking::msg::NftClaimsResponse = app.wrap()
+            .query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::Claims { nft_id: "0".to_string(), start_after: None, limit: None })
+            .unwrap();
+        assert!(claims.claims.is_empty());
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.info.extension.status, Status::Bonded);
+
+        app.wasm_sudo(Addr::unchecked(&nft_contract_addr), &false).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        app.wasm_sudo(Addr::unchecked(&nft_contract_addr), &true).unwrap();
+
+        // Burn fails: tokens are not paid out and the claim is kept
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnableToBurnNFT {  });
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3400u128));
+        let claims : staking::msg::NftClaimsResponse = app.wrap()
+            .query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::Claims { nft_id: "0".to_string(), start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(claims.claims.len(), 1);
+
+        app.wasm_sudo(Addr::unchecked(&nft_contract_addr), &false).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(4000u128));
+    }
+
+    #[test]
+    fn position_queries() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        let next_nft_id : Uint128 = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::NextNftId {  }).unwrap();
+        assert_eq!(next_nft_id, Uint128::zero());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(200, NATIVE_DENOM.to_string())]).unwrap();
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+
+        let next_nft_id : Uint128 = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::NextNftId {  }).unwrap();
+        assert_eq!(next_nft_id, Uint128::from(3u128));
+
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.owner, USER1);
+        assert_eq!(position.status, unbonding_status(&app));
+        assert_eq!(position.metadata.native, vec![coin(600, NATIVE_DENOM)]);
+        assert_eq!(position.metadata.total_bonded, Uint128::from(600u128));
+        assert_eq!(position.bonded, Uint128::zero());
+        // Unbonded from the two validators with the most bonded tokens
+        assert_eq!(position.claims.len(), 2);
+        assert_eq!(position.claims.iter().map(|claim| claim.amount).sum::<Uint128>(), Uint128::from(600u128));
+        // Metadata follows the staking contract
+        assert!(position.metadata.allocations.is_empty());
+        let unbonding : Vec<_> = position.metadata.unbonding.iter().map(|unbonding| (unbonding.amount, unbonding.release_at)).collect();
+        let claims : Vec<_> = position.claims.iter().map(|claim| (claim.amount, claim.release_at)).collect();
+        assert_eq!(unbonding, claims);
+        let block = app.block_info();
+        assert_eq!((position.metadata.updated_height, position.metadata.updated_at), (block.height, block.time));
+
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "2".to_string() }).unwrap();
+        assert_eq!(position.status, Status::Bonded);
+        assert_eq!(position.bonded, Uint128::from(200u128));
+        assert!(position.claims.is_empty());
+        assert!(position.metadata.unbonding.is_empty());
+        assert_eq!(position.metadata.allocations.iter().map(|allocation| allocation.amount).sum::<Uint128>(), Uint128::from(200u128));
+
+        app.wrap().query_wasm_smart::<PositionResponse>(agent_contract.addr(), &QueryMsg::Position { nft_id: "3".to_string() }).unwrap_err();
+
+        let msg = QueryMsg::PositionsByOwner { owner: USER1.to_string(), start_after: None, limit: None };
+        let res : PositionsResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &msg).unwrap();
+        let nft_ids : Vec<_> = res.positions.iter().map(|position| position.nft_id.as_str()).collect();
+        assert_eq!(nft_ids, vec!["0", "2"]);
+
+        let msg = QueryMsg::PositionsByOwner { owner: USER1.to_string(), start_after: Some("0".to_string()), limit: Some(1) };
+        let res : PositionsResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &msg).unwrap();
+        assert_eq!(res.positions.len(), 1);
+        assert_eq!(res.positions[0].nft_id, "2");
+        assert_eq!(res.positions[0].bonded, Uint128::from(200u128));
+
+        let msg = QueryMsg::PositionsByOwner { owner: USER3.to_string(), start_after: None, limit: None };
+        let res : PositionsResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &msg).unwrap();
+        assert!(res.positions.is_empty());
+    }
+
+    #[test]
+    fn claim_to_recipient() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+        // Recipient must be a valid address
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: Some("Invalid".to_string()) };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: Some(USER3.to_string()) };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let claim_event = res.events.iter().find(|event| event.ty == "wasm-claim_nft").unwrap();
+        assert_eq!(claim_event.attributes[1], ("nft_id", "0"));
+        assert_eq!(claim_event.attributes[2], ("amount", "600ujunox"));
+        assert_eq!(claim_event.attributes[3], ("recipient", USER3));
+
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3400u128));
+        assert_eq!(app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount, Uint128::from(4600u128));
+    }
+
+    #[test]
+    fn send_nft_to_agent() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+
+        // Only the NFT contract can call the hook
+        let msg = ExecuteMsg::ReceiveNft(cw721::Cw721ReceiveMsg { sender: USER1.to_string(), token_id: "0".to_string(), msg: to_binary(&ReceiveNftMsg::Unbond {  }).unwrap() });
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+
+        // Unbonded NFT is sent back to its owner
+        let msg = nft::msg::ExecuteMsg::SendNft { contract: agent_contract.addr().into(), token_id: "0".to_string(), msg: to_binary(&ReceiveNftMsg::Unbond {  }).unwrap() };
+        app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER1));
+        assert_eq!(all_nft_info.info.extension.status, unbonding_status(&app));
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::zero(), Uint128::zero(), Uint128::zero());
+
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+        // Claimed NFT is burnt and the tokens go to the recipient
+        let msg = nft::msg::ExecuteMsg::SendNft { contract: agent_contract.addr().into(), token_id: "0".to_string(), msg: to_binary(&ReceiveNftMsg::Claim { recipient: Some(USER2.to_string()) }).unwrap() };
+        app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3400u128));
+        assert_eq!(app.wrap().query_balance(USER2, NATIVE_DENOM).unwrap().amount, Uint128::from(4600u128));
+        let res : cw721::NumTokensResponse = app.wrap()
+            .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::NumTokens {  })
+            .unwrap();
+        assert_eq!(res.count, 0);
+    }
+
+    #[test]
+    fn claim_receipts() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let msg = InstantiateMsg { claim_receipts: Some(true), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let tokens_by_status = |app: &App, status: nft::msg::StatusFilter| -> Vec<String> {
+            let res : cw721::TokensResponse = app.wrap()
+                .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::TokensByStatus { status, start_after: None, limit: None })
+                .unwrap();
+            res.tokens
+        };
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(DAY), reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Bonded), vec!["0"]);
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Locked), vec!["1"]);
+
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string()).info.extension.status, unbonding_status(&app));
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Unbonding), vec!["0"]);
+
+        // Unbonding NFTs can not be rebonded nor unbonded again
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::UnbondingNFT {  });
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NFTNotBonded { nft_id: "0".to_string() });
+
+        // Released tokens are claimable and the lock has expired
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.status, Status::Claimable);
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Claimable), vec!["0"]);
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Bonded), vec!["1"]);
+
+        // Claimed NFT is kept as a receipt
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.access.owner, USER1);
+        assert_eq!(all_nft_info.info.extension.status, Status::Claimed);
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(600, NATIVE_DENOM)]);
+        assert_eq!(all_nft_info.info.extension.unbonding, vec![]);
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Claimed), vec!["0"]);
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ClaimedNFT { nft_id: "0".to_string() });
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ClaimedNFT { nft_id: "0".to_string() });
+
+        // A receipt claimed through the NFT contract goes back to its owner
+        let msg = ExecuteMsg::Unbond { nft_id: "1".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(week));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        let msg = nft::msg::ExecuteMsg::SendNft { contract: agent_contract.addr().into(), token_id: "1".to_string(), msg: to_binary(&ReceiveNftMsg::Claim { recipient: None }).unwrap() };
+        app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "1".to_string());
+        assert_eq!(all_nft_info.access.owner, USER1);
+        assert_eq!(all_nft_info.info.extension.status, Status::Claimed);
+        assert_eq!(tokens_by_status(&app, nft::msg::StatusFilter::Claimed), vec!["0", "1"]);
+        // All bonded tokens are back
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(4000u128));
+    }
+
+    #[test]
+    fn transfer_policy() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+
+        // Only the manager sets the policy
+        let policy = TransferPolicy { block_unbonding: true, block_locked: true, soulbound: true, allowed_recipients: vec![] };
+        let msg = ExecuteMsg::UpdateTransferPolicy { policy: policy.clone() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let res : TransferPolicy = app.wrap().query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::TransferPolicy {}).unwrap();
+        assert_eq!(res, policy);
+
+        let msg = nft::msg::ExecuteMsg::TransferNft { recipient: USER2.to_string(), token_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<nft::contract::ContractError>().unwrap(), nft::contract::ContractError::Soulbound {});
+
+        // Soulbound NFTs are still sent to the agent and back
+        let send_msg = nft::msg::ExecuteMsg::SendNft { contract: agent_contract.addr().into(), token_id: "0".to_string(), msg: to_binary(&ReceiveNftMsg::Unbond {  }).unwrap() };
+        app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &send_msg, &[]).unwrap();
+        assert_eq!(get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string()).access.owner, USER1);
+
+        let msg = ExecuteMsg::UpdateTransferPolicy { policy: TransferPolicy { soulbound: false, ..policy } };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let msg = nft::msg::ExecuteMsg::TransferNft { recipient: USER2.to_string(), token_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<nft::contract::ContractError>().unwrap(), nft::contract::ContractError::UnbondingTransfer { token_id: "0".to_string() });
+    }
+
+    #[test]
+    fn royalty() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let royalty = Royalty { payment_address: TREASURY1.to_string(), percentage: 5 };
+        let msg = InstantiateMsg { royalty: Some(royalty.clone()), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+
+        let royalty_info = |app: &App| -> nft::msg::RoyaltiesInfoResponse {
+            app.wrap()
+                .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::RoyaltyInfo { token_id: "0".to_string(), sale_price: Uint128::from(1000u128) })
+                .unwrap()
+        };
+        assert_eq!(royalty_info(&app), nft::msg::RoyaltiesInfoResponse { address: TREASURY1.to_string(), royalty_amount: Uint128::from(50u128) });
+
+        // Only the manager updates the royalty
+        let msg = ExecuteMsg::UpdateRoyalty { royalty: Some(Royalty { percentage: 3, ..royalty }) };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(royalty_info(&app).royalty_amount, Uint128::from(30u128));
+        let msg = ExecuteMsg::UpdateRoyalty { royalty: None };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(royalty_info(&app).royalty_amount, Uint128::zero());
+    }
+
+    #[test]
+    fn collection_info() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+
+        let collection_info = |app: &App| -> nft::msg::CollectionInfoResponse {
+            app.wrap().query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::CollectionInfo {}).unwrap()
+        };
+        let res = collection_info(&app);
+        assert_eq!((res.name, res.symbol), ("angel_staking_nft".to_string(), "ASM".to_string()));
+        assert_eq!((res.total_bonded, res.active_positions), (vec![coin(1000, NATIVE_DENOM)], 2));
+
+        // Unbonding positions leave the totals
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let res = collection_info(&app);
+        assert_eq!((res.total_bonded, res.active_positions), (vec![coin(400, NATIVE_DENOM)], 1));
+
+        // Only the manager updates the collection info
+        let info = CollectionInfo { description: "Angel positions".to_string(), image: Some("ipfs://angel".to_string()), external_link: None };
+        let msg = ExecuteMsg::UpdateCollectionInfo { collection_info: info.clone() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let res = collection_info(&app);
+        assert_eq!((res.description, res.image), (info.description, info.image));
+    }
+
+    #[test]
+    fn branded_instance() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let collection_info = CollectionInfo { description: "Positions of the Greek charities".to_string(), image: None, external_link: Some("https://greeks.org".to_string()) };
+        let msg = InstantiateMsg { nft_name: Some("Greeks".to_string()), nft_symbol: Some("GRK".to_string()), nft_label: Some("greeks_nft".to_string()), staking_label: Some("greeks_staking".to_string()), collection_info: Some(collection_info.clone()), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "greeks-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+
+        let res: nft::msg::CollectionInfoResponse = app.wrap().query_wasm_smart(&nft_contract_addr, &nft::msg::QueryMsg::CollectionInfo {}).unwrap();
+        assert_eq!((res.name, res.symbol), ("Greeks".to_string(), "GRK".to_string()));
+        assert_eq!((res.description, res.external_link), (collection_info.description, collection_info.external_link));
+        assert_eq!(app.contract_data(&Addr::unchecked(&nft_contract_addr)).unwrap().label, "greeks_nft");
+        assert_eq!(app.contract_data(&Addr::unchecked(&staking_contract_addr)).unwrap().label, "greeks_staking");
+    }
+
+    #[test]
+    fn adopt_contracts() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        // The NFT and staking contracts are instantiated first, for the agent to be contract3
+        let agent_addr = "contract3";
+        let nft_msg = nft::contract::InstantiateMsg { name: "angel_staking_nft".to_string(), symbol: "ASM".to_string(), minter: agent_addr.to_string(), royalty: None, collection_info: None };
+        let nft_contract_addr = app.instantiate_contract(code_id_nft, Addr::unchecked(ADMIN), &nft_msg, &[], "angel_staking_nft", Some(ADMIN.to_string())).unwrap();
+        let staking_msg = staking::msg::InstantiateMsg { agent: agent_addr.to_string(), manager: MANAGER1.to_string(), treasury: TREASURY1.to_string() };
+        let staking_contract_addr = app.instantiate_contract(code_id_staking, Addr::unchecked(ADMIN), &staking_msg, &[], "angel_staking", Some(ADMIN.to_string())).unwrap();
+        let other_staking_msg = staking::msg::InstantiateMsg { agent: USER1.to_string(), ..staking_msg };
+        let other_staking_addr = app.instantiate_contract(code_id_staking, Addr::unchecked(ADMIN), &other_staking_msg, &[], "angel_staking", Some(ADMIN.to_string())).unwrap();
+
+        let msg = InstantiateMsg { nft_contract: Some(nft_contract_addr.to_string()), staking_contract: Some(staking_contract_addr.to_string()), first_nft_id: Some(Uint128::from(5u128)), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        assert_eq!(agent_contract.addr(), Addr::unchecked(agent_addr));
+        assert_eq!(get_nft_contract_address(&app, &agent_contract), nft_contract_addr.to_string());
+        assert_eq!(get_staking_contract_address(&app, &agent_contract), staking_contract_addr.to_string());
+
+        // Bonds go through the adopted contracts, from the first NFT id
+        add_3_validators(&mut app, staking_contract_addr.as_str(), Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.to_string(), "5".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER1));
+        let bonded : Uint128 = app.wrap().query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id: "5".to_string() }).unwrap();
+        assert_eq!(bonded, Uint128::from(600u128));
+
+        // A new agent adopts the contracts of the previous one, past its NFT ids, and the previous manager hands them over
+        let msg = InstantiateMsg { nft_contract: Some(nft_contract_addr.to_string()), staking_contract: Some(staking_contract_addr.to_string()), first_nft_id: Some(Uint128::from(3u128)), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let err = app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::FirstNftIdInUse { min: Uint128::from(6u128) });
+        let msg = InstantiateMsg { first_nft_id: None, ..msg };
+        let new_agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let next_nft_id : Uint128 = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::NextNftId {  }).unwrap();
+        assert_eq!(next_nft_id, Uint128::from(6u128));
+
+        // The state of the previous agent is imported past the hand over
+        let import_msg = ExecuteMsg::ImportState { limit: None };
+        let err = app.execute_contract(Addr::unchecked(MANAGER1), new_agent_contract.addr(), &import_msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::HandOverPending { agent: agent_contract.addr().to_string() });
+        let msg = ExecuteMsg::HandOver { agent: new_agent_contract.addr().to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let minter : nft::msg::MinterResponse = app.wrap().query_wasm_smart(&nft_contract_addr, &nft::msg::QueryMsg::Minter {  }).unwrap();
+        assert_eq!(minter.minter, new_agent_contract.addr().to_string());
+        let agent : String = app.wrap().query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::Agent {  }).unwrap();
+        assert_eq!(agent, new_agent_contract.addr().to_string());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap_err();
+        let err = app.execute_contract(Addr::unchecked(USER1), new_agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ImportPending {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), new_agent_contract.addr(), &import_msg, &[]).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), new_agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.to_string(), "6".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER1));
+
+        // Contracts managed by another agent are rejected
+        let msg = InstantiateMsg { staking_contract: Some(other_staking_addr.to_string()), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let err = app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ForeignContract { contract: other_staking_addr.to_string() });
+    }
+
+    #[test]
+    fn hand_over_state() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let fees = Fees { deposit_fee_bps: 0, reward_commission_bps: 1000, early_exit_fee_bps: 1000 };
+        let msg = InstantiateMsg { fees: Some(fees.clone()), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // Two NFTs locked for two years route their rewards to a beneficiary, which is donated 36 of the rewards of NFT_ID 0
+        let msg = ExecuteMsg::AddBeneficiary { address: USER3.to_string(), name: "Charity".to_string(), description_url: "https://charity.org".to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let route = RewardRoute { owner_bps: 5000, treasury_bps: 1000, beneficiaries: vec![BeneficiaryShare { address: USER3.to_string(), bps: 4000 }] };
+        let lock = Duration::Time(60 * 60 * 24 * 365 * 2);
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(lock), reward_route: Some(route.clone()) };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        let collect_msg = staking::msg::ExecuteMsg::CollectAngelRewards {  };
+        app.execute_contract(Addr::unchecked(MANAGER1), Addr::unchecked(&staking_contract_addr), &collect_msg, &[]).unwrap();
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount, Uint128::from(4036u128));
+
+        // NFT_ID 0 leaves early and owes a 10% fee on its claim
+        let msg = ExecuteMsg::EarlyUnbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let res : EarlyExitFeesResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::EarlyExitFees { start_after: None, limit: None }).unwrap();
+        assert_eq!(res.fees, vec![EarlyExitFee { nft_id: "0".to_string(), fee: vec![coin(100, NATIVE_DENOM)] }]);
+
+        // A new agent adopts the contracts, which are handed over before the state is imported
+        let msg = InstantiateMsg { fees: Some(fees), nft_contract: Some(nft_contract_addr.clone()), staking_contract: Some(staking_contract_addr.clone()), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let new_agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let msg = ExecuteMsg::HandOver { agent: new_agent_contract.addr().to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "1".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER2), new_agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::ImportPending {  });
+
+        // Only the manager, page by page
+        let msg = ExecuteMsg::ImportState { limit: Some(1) };
+        let err = app.execute_contract(Addr::unchecked(USER1), new_agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        let res = app.execute_contract(Addr::unchecked(MANAGER1), new_agent_contract.addr(), &msg, &[]).unwrap();
+        let import_event = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+        assert!(import_event.attributes.iter().any(|attr| attr.key == "done" && attr.value == "false"));
+        let msg = ExecuteMsg::ImportState { limit: None };
+        let res = app.execute_contract(Addr::unchecked(MANAGER1), new_agent_contract.addr(), &msg, &[]).unwrap();
+        let import_event = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+        assert!(import_event.attributes.iter().any(|attr| attr.key == "imported" && attr.value == "3"));
+        assert!(import_event.attributes.iter().any(|attr| attr.key == "done" && attr.value == "true"));
+        app.execute_contract(Addr::unchecked(MANAGER1), new_agent_contract.addr(), &msg, &[]).unwrap_err();
+
+        let res : EarlyExitFeesResponse = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::EarlyExitFees { start_after: None, limit: None }).unwrap();
+        assert_eq!(res.fees, vec![EarlyExitFee { nft_id: "0".to_string(), fee: vec![coin(100, NATIVE_DENOM)] }]);
+        let res : RewardRoutesResponse = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::RewardRoutes { start_after: None, limit: None }).unwrap();
+        assert_eq!(res.routes, vec![NftRewardRoute { nft_id: "0".to_string(), route: route.clone() }, NftRewardRoute { nft_id: "1".to_string(), route }]);
+        let res : BeneficiaryResponse = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::Beneficiary { address: USER3.to_string() }).unwrap();
+        assert_eq!(res.total_received, Uint128::from(36u128));
+        assert!(res.active);
+
+        // Rewards of NFT_ID 1 keep their route through the new agent: 45 to the owner, 9 to the treasury and 36 to the beneficiary
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "1".to_string() };
+        app.execute_contract(Addr::unchecked(USER2), new_agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER2, NATIVE_DENOM).unwrap().amount, Uint128::from(3045u128));
+        assert_eq!(app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount, Uint128::from(4072u128));
+        let res : BeneficiaryResponse = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::Beneficiary { address: USER3.to_string() }).unwrap();
+        assert_eq!(res.total_received, Uint128::from(72u128));
+
+        // And NFT_ID 0 pays its early exit fee on the claim
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: None };
+        let res = app.execute_contract(Addr::unchecked(USER1), new_agent_contract.addr(), &msg, &[]).unwrap();
+        let claim_event = res.events.iter().find(|event| event.ty == "wasm-claim_nft").unwrap();
+        assert_eq!(claim_event.attributes[4], ("early_exit_fee", "100ujunox"));
+        let res : EarlyExitFeesResponse = app.wrap().query_wasm_smart(new_agent_contract.addr(), &QueryMsg::EarlyExitFees { start_after: None, limit: None }).unwrap();
+        assert!(res.fees.is_empty());
+    }
+
+    #[test]
+    fn bond_for_third_party() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // Owner must be a valid address
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: Some("Invalid".to_string()), lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap_err();
+
+        //USER 1 BONDS NFT_ID 0 for USER 2
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: Some(USER2.to_string()), lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER2));
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(600u128, NATIVE_DENOM)]);
+
+        //USER 3 tops up NFT_ID 0, which stays owned by USER 2
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER3), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER2));
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(1000u128, NATIVE_DENOM)]);
+        let bonded : Uint128 = app.wrap().query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(bonded, Uint128::from(1000u128));
+
+        // Only the owner can unbond it
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+    }
+
+    #[test]
+    fn merge_split() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let bonded_by_nft = |app: &App, nft_id: &str| -> Uint128 {
+            app.wrap().query_wasm_smart(&staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id: nft_id.to_string() }).unwrap()
+        };
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(200, NATIVE_DENOM.to_string())]).unwrap();
+
+        let msg = ExecuteMsg::Merge { nft_ids: vec!["0".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::MergeTooFewNFTs {  });
+        let msg = ExecuteMsg::Merge { nft_ids: vec!["0".to_string(), "2".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+
+        // NFT_ID 1 is merged into NFT_ID 0 and burnt
+        let msg = ExecuteMsg::Merge { nft_ids: vec!["0".to_string(), "1".to_string()] };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(1000u128, NATIVE_DENOM)]);
+        assert_eq!(bonded_by_nft(&app, "0"), Uint128::from(1000u128));
+        assert_eq!(bonded_by_nft(&app, "1"), Uint128::zero());
+        let res : cw721::NumTokensResponse = app.wrap()
+            .query_wasm_smart(Addr::unchecked(&nft_contract_addr), &nft::msg::QueryMsg::NumTokens {  })
+            .unwrap();
+        assert_eq!(res.count, 2);
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(400u128), Uint128::from(200u128));
+
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::from(400u128), Uint128::from(600u128)] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSplitAmounts {  });
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::zero()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSplitAmounts {  });
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::MAX, Uint128::from(2u128)] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidSplitAmounts {  });
+
+        // NFT_ID 0 has a royalty override and a reward history
+        let mut extension = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string()).info.extension;
+        extension.lifetime_rewards = Uint128::from(1000u128);
+        extension.royalty_percentage = Some(5);
+        extension.royalty_payment_address = Some(USER3.to_string());
+        let msg = nft::msg::ExecuteMsg::UpdateMetadata { token_id: "0".to_string(), token_uri: None, extension };
+        app.execute_contract(agent_contract.addr(), Addr::unchecked(&nft_contract_addr), &msg, &[]).unwrap();
+
+        // NFT_ID 3 and 4 are minted from NFT_ID 0, with its royalty and their share of its lifetime rewards
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::from(100u128), Uint128::from(300u128)] };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "0".to_string());
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(600u128, NATIVE_DENOM)]);
+        assert_eq!(all_nft_info.info.extension.total_bonded, Uint128::from(600u128));
+        assert_eq!(all_nft_info.info.extension.lifetime_rewards, Uint128::from(600u128));
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr.clone(), "4".to_string());
+        assert_eq!(all_nft_info.access.owner, String::from(USER1));
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(300u128, NATIVE_DENOM)]);
+        assert_eq!(all_nft_info.info.extension.total_bonded, Uint128::from(300u128));
+        assert_eq!(all_nft_info.info.extension.status, Status::Bonded);
+        assert_eq!(all_nft_info.info.extension.lifetime_rewards, Uint128::from(300u128));
+        assert_eq!(all_nft_info.info.extension.royalty_percentage, Some(5));
+        assert_eq!(all_nft_info.info.extension.royalty_payment_address, Some(USER3.to_string()));
+        assert_eq!(bonded_by_nft(&app, "0"), Uint128::from(600u128));
+        assert_eq!(bonded_by_nft(&app, "3"), Uint128::from(100u128));
+        assert_eq!(bonded_by_nft(&app, "4"), Uint128::from(300u128));
+        let next_nft_id : Uint128 = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::NextNftId {  }).unwrap();
+        assert_eq!(next_nft_id, Uint128::from(5u128));
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(600u128), Uint128::from(400u128), Uint128::from(200u128));
+
+        // Split NFTs can be unbonded. Unbonding NFTs can not be merged
+        let msg = ExecuteMsg::Unbond { nft_id: "3".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let msg = ExecuteMsg::Merge { nft_ids: vec!["0".to_string(), "3".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NFTNotBonded { nft_id: "3".to_string() });
+    }
+
+    #[test]
+    fn registered_denoms() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let fees = Fees { deposit_fee_bps: 0, reward_commission_bps: 0, early_exit_fee_bps: 1000 };
+        let msg = InstantiateMsg { fees: Some(fees), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        let nft_contract_addr = get_nft_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // Unregistered denoms are rejected by the staking contract
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(300, LSD_DENOM)]).unwrap_err();
+        let msg = staking::msg::ExecuteMsg::RegisterDenom { denom: LSD_DENOM.to_string(), adapter: staking::state::DenomAdapter::Custody { unbonding_period: DAY } };
+        app.execute_contract(Addr::unchecked(MANAGER1), Addr::unchecked(&staking_contract_addr), &msg, &[]).unwrap();
+
+        // NFT_ID 0 holds both denoms, NFT_ID 1 only the registered one
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(WEEK), reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM)]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(300, LSD_DENOM)]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(200, LSD_DENOM)]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.metadata.native, vec![coin(1000, NATIVE_DENOM), coin(300, LSD_DENOM)]);
+        assert_eq!(position.metadata.total_bonded, Uint128::from(1000u128));
+        assert_eq!(position.bonded, Uint128::from(1000u128));
+        assert_eq!(position.bonded_coins, vec![coin(1000, NATIVE_DENOM), coin(300, LSD_DENOM)]);
+        assert_eq!(app.wrap().query_balance(&staking_contract_addr, LSD_DENOM).unwrap().amount, Uint128::from(500u128));
+        query_delegation_on_three_validators(&app, &staking_contract_addr, Uint128::from(1000u128), Uint128::zero(), Uint128::zero());
+
+        // Only NFTs of the bond denom alone are split. Merged NFTs keep one coin per denom
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::from(100u128)] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::OnlyOneNativeCoinPerNFT {  });
+        let msg = ExecuteMsg::Merge { nft_ids: vec!["0".to_string(), "1".to_string()] };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let all_nft_info = get_nft_all_info(&app, nft_contract_addr, "0".to_string());
+        assert_eq!(all_nft_info.info.extension.native, vec![coin(1000, NATIVE_DENOM), coin(500, LSD_DENOM)]);
+
+        // Every coin is unbonded, paying the early exit fee of its denom
+        let msg = ExecuteMsg::EarlyUnbond { nft_id: "0".to_string() };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert!(res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "early_exit_fee" && attr.value == "100ujunox,50stujunox")));
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.bonded_coins, vec![]);
+        assert_eq!(position.status, unbonding_status(&app));
+        assert_eq!(position.metadata.unbonding.iter().map(|unbonding| unbonding.denom.clone()).collect::<Vec<_>>(), vec![None, Some(LSD_DENOM.to_string())]);
+        assert_eq!(position.claims.len(), 1);
+        assert_eq!(position.denom_claims.iter().map(|claim| claim.denom.clone()).collect::<Vec<_>>(), vec![LSD_DENOM.to_string()]);
+
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+        let msg = ExecuteMsg::Claim { nft_id: "0".to_string(), recipient: None };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let claim_event = res.events.iter().find(|event| event.ty == "wasm-claim_nft").unwrap();
+        assert_eq!(claim_event.attributes[2], ("amount", "1000ujunox,500stujunox"));
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3900u128));
+        assert_eq!(app.wrap().query_balance(USER1, LSD_DENOM).unwrap().amount, Uint128::from(950u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(100u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, LSD_DENOM).unwrap().amount, Uint128::from(50u128));
+        assert_eq!(app.wrap().query_balance(&staking_contract_addr, LSD_DENOM).unwrap().amount, Uint128::zero());
+    }
+
+    #[test]
+    fn locked_bond() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let now = app.block_info().time;
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(Duration::Height(100)), reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidLock {  });
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(Duration::Time(u64::MAX)), reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::LockTooLong {  });
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(DAY), reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(600, NATIVE_DENOM.to_string())]).unwrap();
+        let day_lock = now.plus_seconds(24 * 60 * 60);
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.metadata.lock_until, Some(day_lock));
+
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::LockedNFT { nft_id: "0".to_string(), until: day_lock });
+        let msg = ExecuteMsg::BatchUnbond { nft_ids: vec!["0".to_string()] };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::LockedNFT { nft_id: "0".to_string(), until: day_lock });
+
+        // Others can top-up but not lock the NFT
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: Some(WEEK), reward_route: None };
+        let err = app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(400, NATIVE_DENOM.to_string())]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.metadata.lock_until, Some(day_lock));
+
+        // The owner extends the lock for a week
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: Some(WEEK), reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(100, NATIVE_DENOM.to_string())]).unwrap();
+        let week_lock = now.plus_seconds(7 * 24 * 60 * 60);
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.metadata.lock_until, Some(week_lock));
+        assert_eq!(position.status, Status::Locked);
+
+        app.update_block(|block| block.time = block.time.plus_seconds(24 * 60 * 60));
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::LockedNFT { nft_id: "0".to_string(), until: week_lock });
+
+        // Expired lock
+        app.update_block(|block| block.time = week_lock);
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.status, Status::Bonded);
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.status, unbonding_status(&app));
+        assert_eq!(position.claims.iter().map(|claim| claim.amount).sum::<Uint128>(), Uint128::from(1100u128));
+    }
+
+    #[test]
+    fn fees() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let fees = Fees { deposit_fee_bps: 100, reward_commission_bps: 1000, early_exit_fee_bps: 1000 };
+        let msg = InstantiateMsg { fees: Some(Fees { deposit_fee_bps: 501, ..fees.clone() }), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let err = app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::FeeAboveCap { fee: "deposit_fee".to_string(), bps: 501, max: 500 });
+        let msg = InstantiateMsg { fees: Some(fees.clone()), ..msg };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // Only the manager, within the caps
+        let msg = ExecuteMsg::UpdateFees { fees: Fees { early_exit_fee_bps: 2001, ..fees.clone() } };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        let err = app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::FeeAboveCap { fee: "early_exit_fee".to_string(), bps: 2001, max: 2000 });
+        let res : Fees = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Fees {  }).unwrap();
+        assert_eq!(res, fees);
+
+        // 1% deposit fee goes to the treasury
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: Some(WEEK), reward_route: None };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        let bond_event = res.events.iter().find(|event| event.ty == "wasm" && event.attributes.iter().any(|attr| attr.value == "execute_bond")).unwrap();
+        assert!(bond_event.attributes.iter().any(|attr| attr.key == "deposit_fee" && attr.value == "10"));
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.metadata.native, vec![coin(990, NATIVE_DENOM)]);
+        assert_eq!(position.bonded, Uint128::from(990u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(20u128));
+
+        // Locked NFT can only leave early, paying a 10% fee on claim
+        let msg = ExecuteMsg::Unbond { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        let msg = ExecuteMsg::EarlyUnbond { nft_id: "0".to_string() };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert!(res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "early_exit_fee" && attr.value == "99ujunox")));
+        // Unlocked NFT pays no early exit fee
+        let msg = ExecuteMsg::EarlyUnbond { nft_id: "1".to_string() };
+        let res = app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+        assert!(!res.events.iter().any(|event| event.attributes.iter().any(|attr| attr.key == "early_exit_fee")));
+
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+
+        let msg = ExecuteMsg::BatchClaim { nft_ids: vec!["0".to_string()], recipient: None };
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let claim_event = res.events.iter().find(|event| event.ty == "wasm-claim_nft").unwrap();
+        assert_eq!(claim_event.attributes[4], ("early_exit_fee", "99ujunox"));
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3891u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(119u128));
+
+        let msg = ExecuteMsg::Claim { nft_id: "1".to_string(), recipient: None };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER2, NATIVE_DENOM).unwrap().amount, Uint128::from(3990u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(119u128));
+        assert_eq!(app.wrap().query_balance(agent_contract.addr(), NATIVE_DENOM).unwrap().amount, Uint128::zero());
+    }
+
+    #[test]
+    fn claim_rewards() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let fees = Fees { deposit_fee_bps: 0, reward_commission_bps: 1000, early_exit_fee_bps: 0 };
+        let msg = InstantiateMsg { fees: Some(fees), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+
+        // 10% apr. Rewards are accounted once collected
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "0".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NoRewards { nft_id: "0".to_string() });
+        let collect_msg = staking::msg::ExecuteMsg::CollectAngelRewards {  };
+        app.execute_contract(Addr::unchecked(MANAGER1), Addr::unchecked(&staking_contract_addr), &collect_msg, &[]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.rewards, Uint128::from(100u128));
+
+        // Only the owner, who keeps the NFT bonded. The 10% commission goes to the treasury
+        let err = app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+        let res = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let rewards_event = res.events.iter().find(|event| event.ty == "wasm-claim_rewards").unwrap();
+        assert_eq!(rewards_event.attributes[4], ("reward_commission", "10"));
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3090u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(10u128));
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.rewards, Uint128::zero());
+        assert_eq!(position.metadata.lifetime_rewards, Uint128::from(100u128));
+        assert_eq!(position.status, Status::Bonded);
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+
+        // Pending rewards are paid out with the claim
+        let msg = ExecuteMsg::Unbond { nft_id: "1".to_string() };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+        let week = match WEEK { Duration::Time(seconds) => seconds, _ => unreachable!() };
+        app.update_block(|block| block.time = block.time.plus_seconds(week + 1));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        let msg = ExecuteMsg::Claim { nft_id: "1".to_string(), recipient: None };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER2, NATIVE_DENOM).unwrap().amount, Uint128::from(4090u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(20u128));
+        assert_eq!(app.wrap().query_balance(agent_contract.addr(), NATIVE_DENOM).unwrap().amount, Uint128::zero());
+    }
+
+    #[test]
+    fn reward_route() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let fees = Fees { deposit_fee_bps: 0, reward_commission_bps: 1000, early_exit_fee_bps: 0 };
+        let msg = InstantiateMsg { fees: Some(fees), ..default_instantiate_msg(code_id_nft, code_id_staking) };
+        let agent_contract = AgentContract(app.instantiate_contract(code_id_agent, Addr::unchecked(MANAGER1), &msg, &[], "angel-agent", Some(ADMIN.to_string())).unwrap());
+        let staking_contract_addr = get_staking_contract_address(&app, &agent_contract);
+        add_3_validators(&mut app, &staking_contract_addr, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+
+        // Only to active beneficiaries, with shares adding up to 10000 bps
+        let route = RewardRoute { owner_bps: 5000, treasury_bps: 1000, beneficiaries: vec![BeneficiaryShare { address: USER3.to_string(), bps: 3000 }] };
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: Some(route.clone()) };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::BeneficiaryNotActive { address: USER3.to_string() });
+        let msg = ExecuteMsg::AddBeneficiary { address: USER3.to_string(), name: "Charity".to_string(), description_url: "https://charity.org".to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: Some(route.clone()) };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::InvalidRewardRoute {  });
+        let route = RewardRoute { beneficiaries: vec![BeneficiaryShare { address: USER3.to_string(), bps: 4000 }], ..route };
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: Some(route.clone()) };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: None, owner: None, lock: None, reward_route: None };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM.to_string())]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "0".to_string() }).unwrap();
+        assert_eq!(position.reward_route, Some(route.clone()));
+
+        // Only the owner changes the route on a rebond
+        let msg = ExecuteMsg::Bond { nft_id: Some("0".to_string()), owner: None, lock: None, reward_route: Some(route.clone()) };
+        let err = app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[coin(10, NATIVE_DENOM.to_string())]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::NotOwnerNFT {  });
+
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        let collect_msg = staking::msg::ExecuteMsg::CollectAngelRewards {  };
+        app.execute_contract(Addr::unchecked(MANAGER1), Addr::unchecked(&staking_contract_addr), &collect_msg, &[]).unwrap();
+
+        // 100 rewards, 10 of commission. The other 90 are routed: 45 to the owner, 9 to the treasury and 36 to the beneficiary
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3045u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(19u128));
+        assert_eq!(app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount, Uint128::from(4036u128));
+        assert_eq!(app.wrap().query_balance(agent_contract.addr(), NATIVE_DENOM).unwrap().amount, Uint128::zero());
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "1".to_string() };
+        app.execute_contract(Addr::unchecked(USER2), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER2, NATIVE_DENOM).unwrap().amount, Uint128::from(3090u128));
+
+        let res : DonationsResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Donations { start_after: None, limit: None }).unwrap();
+        assert_eq!(res.donations, vec![Donation { beneficiary: USER3.to_string(), amount: Uint128::from(36u128) }]);
+        let res : BeneficiaryResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Beneficiary { address: USER3.to_string() }).unwrap();
+        assert_eq!(res.total_received, Uint128::from(36u128));
+
+        // Split NFTs keep the route
+        let msg = ExecuteMsg::Split { nft_id: "0".to_string(), amounts: vec![Uint128::from(400u128)] };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let position : PositionResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Position { nft_id: "2".to_string() }).unwrap();
+        assert_eq!(position.reward_route, Some(route));
+
+        // Share of a deactivated beneficiary goes to the treasury. Of the 54 routed after the split, 27 go to the owner and 27 to the treasury
+        let msg = ExecuteMsg::DeactivateBeneficiary { address: USER3.to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        app.execute_contract(Addr::unchecked(MANAGER1), Addr::unchecked(&staking_contract_addr), &collect_msg, &[]).unwrap();
+        let msg = ExecuteMsg::ClaimRewards { nft_id: "0".to_string() };
+        app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap();
+        assert_eq!(app.wrap().query_balance(USER1, NATIVE_DENOM).unwrap().amount, Uint128::from(3072u128));
+        assert_eq!(app.wrap().query_balance(TREASURY1, NATIVE_DENOM).unwrap().amount, Uint128::from(62u128));
+        assert_eq!(app.wrap().query_balance(USER3, NATIVE_DENOM).unwrap().amount, Uint128::from(4036u128));
+    }
+
+    #[test]
+    fn beneficiary_registry() {
+        let (mut app, code_id_agent, code_id_staking, code_id_nft) = store_code();
+        let agent_contract = agent_angel_instantiate(&mut app, code_id_agent, code_id_nft, code_id_staking, MANAGER1.to_string(), TREASURY1.to_string());
+
+        // Only the manager curates the registry
+        let msg = ExecuteMsg::AddBeneficiary { address: USER3.to_string(), name: "Charity".to_string(), description_url: "https://charity.org".to_string() };
+        let err = app.execute_contract(Addr::unchecked(USER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::Unauthorized {  });
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let err = app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::BeneficiaryAlreadyRegistered { address: USER3.to_string() });
+        let msg = ExecuteMsg::AddBeneficiary { address: USER2.to_string(), name: "Shelter".to_string(), description_url: "https://shelter.org".to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+
+        let msg = ExecuteMsg::UpdateBeneficiary { address: USER1.to_string(), name: None, description_url: None };
+        let err = app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::BeneficiaryNotFound { address: USER1.to_string() });
+        let msg = ExecuteMsg::UpdateBeneficiary { address: USER3.to_string(), name: None, description_url: Some("https://charity.org/about".to_string()) };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+
+        let msg = ExecuteMsg::DeactivateBeneficiary { address: USER2.to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let err = app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.downcast::<ContractError>().unwrap(), ContractError::BeneficiaryNotActive { address: USER2.to_string() });
+
+        let res : BeneficiaryResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Beneficiary { address: USER3.to_string() }).unwrap();
+        assert_eq!(res, BeneficiaryResponse { address: USER3.to_string(), name: "Charity".to_string(), description_url: "https://charity.org/about".to_string(), active: true, total_received: Uint128::zero() });
+        let res : BeneficiariesResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Beneficiaries { start_after: None, limit: Some(1) }).unwrap();
+        assert_eq!(res.beneficiaries.len(), 1);
+        assert_eq!(res.beneficiaries[0].address, USER2.to_string());
+        assert!(!res.beneficiaries[0].active);
+        let res : BeneficiariesResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Beneficiaries { start_after: Some(USER2.to_string()), limit: None }).unwrap();
+        assert_eq!(res.beneficiaries.len(), 1);
+        assert_eq!(res.beneficiaries[0].address, USER3.to_string());
+
+        // Deactivated beneficiaries can be added again
+        let msg = ExecuteMsg::AddBeneficiary { address: USER2.to_string(), name: "Shelter".to_string(), description_url: "https://shelter.org".to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), agent_contract.addr(), &msg, &[]).unwrap();
+        let res : BeneficiaryResponse = app.wrap().query_wasm_smart(agent_contract.addr(), &QueryMsg::Beneficiary { address: USER2.to_string() }).unwrap();
+        assert!(res.active);
+    }
+}
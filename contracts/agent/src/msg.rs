@@ -1,4 +1,11 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cosmwasm_std::{Coin, Uint128};
+use cw_utils::Duration;
+use cw721::Cw721ReceiveMsg;
+use nft::contract::{Metadata, Status};
+use nft::state::{CollectionInfo, Royalty, TransferPolicy};
+use staking::state::{NftClaim, DenomClaim};
+use crate::state::{Fees, RewardRoute};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -7,22 +14,137 @@ pub struct InstantiateMsg {
    pub admin: String,
    pub manager: String,
    pub treasury: String,
+   /// No fees if not set
+   pub fees: Option<Fees>,
+   /// Claimed NFTs are kept as receipts with status Claimed instead of being burnt. false if not set
+   pub claim_receipts: Option<bool>,
+   /// Royalty on secondary sales of the NFTs. No royalty if not set
+   pub royalty: Option<Royalty>,
+   /// Name of the NFT collection. angel_staking_nft if not set
+   pub nft_name: Option<String>,
+   /// Symbol of the NFT collection. ASM if not set
+   pub nft_symbol: Option<String>,
+   /// Label of the NFT contract. angel_staking_nft if not set
+   pub nft_label: Option<String>,
+   /// Label of the staking contract. angel_staking if not set
+   pub staking_label: Option<String>,
+   /// Description, image and link of the NFT collection
+   pub collection_info: Option<CollectionInfo>,
+   /// Existing NFT contract to use instead of instantiating one. Its minter must be this contract,
+   /// or the agent managing it, which then hands it over with HandOver
+   pub nft_contract: Option<String>,
+   /// Existing staking contract to use instead of instantiating one. Its agent must be this contract,
+   /// or the agent managing it, which then hands it over with HandOver
+   pub staking_contract: Option<String>,
+   /// Id of the first NFT minted, past the ids already used in an existing NFT contract.
+   /// Defaults to the first unused id: the next id of the agent handing it over, and at least its number of tokens
+   pub first_nft_id: Option<Uint128>,
 }
 
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Bond will bond all staking tokens sent with the message
+    /// Bond will bond all staking tokens sent with the message.
+    /// Without nft_id a new NFT is minted to owner, or to the sender if not set.
+    /// With nft_id the tokens are added to that NFT, whoever owns it.
+    /// lock is a time duration the NFT can not be unbonded for. On a rebond it can only be set by the owner to extend the lock
+    /// reward_route splits the NFT rewards between owner, treasury and beneficiaries. On a rebond it can only be set by the owner
+    /// Tokens of denoms registered on the staking contract earn no staking rewards
     Bond {
-       nft_id:Option<String>      
+       nft_id:Option<String>,
+       owner:Option<String>,
+       lock:Option<Duration>,
+       reward_route:Option<RewardRoute>
      },
     /// Unbond staking tokens set by amount
     Unbond { 
         nft_id:String        
     },
-    /// Claim is used to claim native tokens previously "unbonded" after the chain-defined unbonding period
+    /// Unbond a NFT before its lock expires. The early exit fee is taken from its claim
+    EarlyUnbond { 
+        nft_id:String        
+    },
+    /// Claim is used to claim native tokens previously "unbonded" after the chain-defined unbonding period.
+    /// Tokens are sent to recipient, or to the sender if not set
     Claim { 
+        nft_id:String,
+        recipient:Option<String>
+    },
+    /// Unbond every NFT in the list. All of them must be owned by the sender
+    BatchUnbond { 
+        nft_ids:Vec<String>
+    },
+    /// Claim every NFT in the list. All of them must be owned by the sender. Tokens go to recipient, or the sender by default
+    BatchClaim { 
+        nft_ids:Vec<String>,
+        recipient: Option<String>,
+    },
+    /// Withdraw the rewards accrued by the NFT without unbonding it. The reward commission goes to the treasury
+    ClaimRewards { 
         nft_id:String
     },
+    /// Merge the bonded NFTs into the first one of the list, burning the rest. All of them must be owned by the sender
+    Merge { 
+        nft_ids:Vec<String>
+    },
+    /// Mint a new NFT for each amount, taken from the bonded tokens of the NFT. Tokens stay delegated.
+    /// Only NFTs holding the bond denom alone can be split. New NFTs keep the lock and royalty of the NFT and take their share of its lifetime rewards
+    Split { 
+        nft_id:String,
+        amounts:Vec<Uint128>
+    },
+    /// Manager sets the fees, within the caps
+    UpdateFees { 
+        fees:Fees
+    },
+    /// Manager sets the policy of NFT transfers between holders
+    UpdateTransferPolicy {
+        policy: TransferPolicy
+    },
+    /// Manager sets the royalty on secondary sales of the NFTs. None removes it
+    UpdateRoyalty {
+        royalty: Option<Royalty>
+    },
+    /// Manager sets the description, image and link of the NFT collection
+    UpdateCollectionInfo {
+        collection_info: CollectionInfo
+    },
+    /// Manager registers a beneficiary, or reactivates a deactivated one
+    AddBeneficiary {
+        address:String,
+        name:String,
+        description_url:String
+    },
+    /// Manager updates the fields that are set
+    UpdateBeneficiary {
+        address:String,
+        name:Option<String>,
+        description_url:Option<String>
+    },
+    /// Manager deactivates a beneficiary. It can not be routed to and its share of the rewards goes to the treasury
+    DeactivateBeneficiary {
+        address:String
+    },
+    /// Manager hands the NFT and staking contracts over to agent, which adopted them on instantiation.
+    /// agent then imports the early exit fees, reward routes and beneficiaries of this contract with ImportState
+    HandOver {
+        agent:String
+    },
+    /// Manager imports a page of limit entries of the state of the agent that handed the adopted contracts over.
+    /// Other messages are refused until the whole state is imported
+    ImportState {
+        limit:Option<u32>
+    },
+    /// Hook for a NFT sent with SendNft. The embedded msg is a ReceiveNftMsg
+    ReceiveNft(Cw721ReceiveMsg),
+}
+
+/// Actions on a NFT sent to the agent. They are done on behalf of the NFT sender
+#[cw_serde]
+pub enum ReceiveNftMsg {
+    /// Unbond the NFT and send it back to its sender
+    Unbond {},
+    /// Claim the NFT, which is burnt. Tokens are sent to recipient, or to the NFT sender if not set
+    Claim { recipient: Option<String> },
 }
 
 #[cw_serde]
@@ -31,5 +153,104 @@ pub enum QueryMsg {
     #[returns(String)]
      GetNFTAdress{},
      #[returns(String)]
-     GetStakingAdress{}
+     GetStakingAdress{},
+    /// Owner, metadata, bonded tokens and claims of a NFT
+    #[returns(PositionResponse)]
+    Position { nft_id: String },
+    /// Positions of the NFTs held by owner. start_after is a NFT id
+    #[returns(PositionsResponse)]
+    PositionsByOwner { owner: String, start_after: Option<String>, limit: Option<u32> },
+    /// NFT id to be used on the next new bond
+    #[returns(Uint128)]
+    NextNftId {},
+    #[returns(Fees)]
+    Fees {},
+    #[returns(BeneficiaryResponse)]
+    Beneficiary { address: String },
+    /// Registered beneficiaries, active or not. start_after is a beneficiary address
+    #[returns(BeneficiariesResponse)]
+    Beneficiaries { start_after: Option<String>, limit: Option<u32> },
+    /// Rewards ever donated to each beneficiary. start_after is a beneficiary address
+    #[returns(DonationsResponse)]
+    Donations { start_after: Option<String>, limit: Option<u32> },
+    /// Early exit fees to be paid on the claim of NFTs. start_after is a NFT id
+    #[returns(EarlyExitFeesResponse)]
+    EarlyExitFees { start_after: Option<String>, limit: Option<u32> },
+    /// Reward routes of the NFTs. start_after is a NFT id
+    #[returns(RewardRoutesResponse)]
+    RewardRoutes { start_after: Option<String>, limit: Option<u32> },
+}
+
+#[cw_serde]
+pub struct PositionResponse {
+    pub nft_id: String,
+    pub owner: String,
+    pub metadata: Metadata,
+    /// Status at the query block time
+    pub status: Status,
+    /// Bond denom tokens bonded by the NFT on the staking contract
+    pub bonded: Uint128,
+    /// Tokens of every denom bonded by the NFT on the staking contract
+    pub bonded_coins: Vec<Coin>,
+    /// Pending claims of the NFT on the staking contract, one per validator
+    pub claims: Vec<NftClaim>,
+    /// Pending claims of the NFT on registered denoms, one per denom
+    pub denom_claims: Vec<DenomClaim>,
+    /// Rewards accrued by the NFT and not claimed yet, before the reward commission
+    pub rewards: Uint128,
+    /// All rewards go to the owner if not set
+    pub reward_route: Option<RewardRoute>,
+}
+
+#[cw_serde]
+pub struct PositionsResponse {
+    pub positions: Vec<PositionResponse>,
+}
+
+#[cw_serde]
+pub struct BeneficiaryResponse {
+    pub address: String,
+    pub name: String,
+    pub description_url: String,
+    pub active: bool,
+    /// Rewards ever donated to the beneficiary
+    pub total_received: Uint128,
+}
+
+#[cw_serde]
+pub struct BeneficiariesResponse {
+    pub beneficiaries: Vec<BeneficiaryResponse>,
+}
+
+#[cw_serde]
+pub struct Donation {
+    pub beneficiary: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct DonationsResponse {
+    pub donations: Vec<Donation>,
+}
+
+#[cw_serde]
+pub struct EarlyExitFee {
+    pub nft_id: String,
+    pub fee: Vec<Coin>,
+}
+
+#[cw_serde]
+pub struct EarlyExitFeesResponse {
+    pub fees: Vec<EarlyExitFee>,
+}
+
+#[cw_serde]
+pub struct NftRewardRoute {
+    pub nft_id: String,
+    pub route: RewardRoute,
+}
+
+#[cw_serde]
+pub struct RewardRoutesResponse {
+    pub routes: Vec<NftRewardRoute>,
 }
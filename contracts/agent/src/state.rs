@@ -1,20 +1,97 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Uint128,Addr};
-use cw_storage_plus::{Item};
+use cosmwasm_std::{Uint128,Addr,Coin};
+use cw_storage_plus::{Item, Map};
 use nft::contract::Metadata;
 
+// Context of a staking submessage, needed on its reply to update the NFT
 #[cw_serde]
-pub struct CacheNFT {
+pub struct PendingOp {
+    // EXECUTE_*_STAKING_REPLY_ID step the reply has to handle
+    pub step: u64,
+    // Owner of the NFT to be minted, otherwise the sender of the operation
     pub sender: Addr,
     pub nft_id: String,
     pub extension: Metadata,
 }
 
+// Fees in basis points, paid to the treasury
+#[cw_serde]
+#[derive(Default)]
+pub struct Fees {
+    // Taken from the tokens sent on Bond
+    pub deposit_fee_bps: u16,
+    // Taken from the rewards of the positions before they are distributed
+    pub reward_commission_bps: u16,
+    // Taken from the claim of a NFT unbonded before its lock expired
+    pub early_exit_fee_bps: u16,
+}
+
+// Share of the rewards of a NFT donated to a beneficiary, in basis points
+#[cw_serde]
+pub struct BeneficiaryShare {
+    pub address: String,
+    pub bps: u16,
+}
+
+// Routing of the rewards of a NFT, after the reward commission. Shares are in basis points and add up to 10000.
+// Rounding dust and the shares of deactivated beneficiaries go to the treasury
+#[cw_serde]
+pub struct RewardRoute {
+    pub owner_bps: u16,
+    pub treasury_bps: u16,
+    pub beneficiaries: Vec<BeneficiaryShare>,
+}
+
+// Beneficiary vetted by the manager. Only active beneficiaries receive donations
+#[cw_serde]
+pub struct Beneficiary {
+    pub name: String,
+    pub description_url: String,
+    pub active: bool,
+}
+
+// State of the previous agent still to be imported, one map after the other
+#[cw_serde]
+pub enum ImportStep {
+    EarlyExitFees,
+    RewardRoutes,
+    Beneficiaries,
+}
+
+// Import of the state of the agent the adopted contracts are handed over by
+#[cw_serde]
+pub struct StateImport {
+    pub agent: Addr,
+    pub step: ImportStep,
+    // Last key imported on the current step
+    pub start_after: Option<String>,
+}
+
 // Addresses
 pub const STAKING: Item<String> = Item::new("staking");
 pub const NFT: Item<String> = Item::new("nft");
+pub const MANAGER: Item<String> = Item::new("manager");
+pub const TREASURY: Item<String> = Item::new("treasury");
+
+pub const FEES: Item<Fees> = Item::new("fees");
+pub const CLAIM_RECEIPTS: Item<bool> = Item::new("claim_receipts");
+// key: nft_id. Early exit fee to be paid out of the NFT claim, one coin per denom of the NFT
+pub const EARLY_EXIT_FEES: Map<&str, Vec<Coin>> = Map::new("early_exit_fees");
+
+// key: nft_id. NFTs without a route pay all their rewards to the owner
+pub const REWARD_ROUTES: Map<&str, RewardRoute> = Map::new("reward_routes");
+// key: beneficiary address
+pub const BENEFICIARIES: Map<&Addr, Beneficiary> = Map::new("beneficiaries");
+// key: beneficiary address. Rewards ever donated to the beneficiary
+pub const DONATED: Map<&Addr, Uint128> = Map::new("donated");
+
+// Set while the state of the previous agent is not fully imported
+pub const STATE_IMPORT: Item<StateImport> = Item::new("state_import");
 
 // Next NFT_ID to be used to issue an NFT
 pub const NFT_ID: Item<Uint128> = Item::new("nft_id");
 
-pub const CACHE_NFT: Item<CacheNFT> = Item::new("cache_nft");
\ No newline at end of file
+// key: reply id of the staking submessage
+pub const PENDING_OPS: Map<u64, PendingOp> = Map::new("pending_ops");
+// Next reply id to be used for a pending operation
+pub const NEXT_REPLY_ID: Item<u64> = Item::new("next_reply_id");
\ No newline at end of file
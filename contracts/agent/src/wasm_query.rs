@@ -1,6 +1,8 @@
-use cosmwasm_std::{to_binary, Addr, Deps, StdResult, Uint128,WasmMsg};
+use cosmwasm_std::{to_binary, Addr, Coin, Deps, StdResult, Uint128,WasmMsg};
 use cw721_base::MintMsg;
-use nft::contract::Metadata;
+use nft::contract::{Metadata, ValidatorAllocation};
+use nft::state::{CollectionInfo, Royalty, TransferPolicy};
+use staking::state::{NftClaim, DenomClaim};
 
 pub fn get_cw721_mint_msg(
     owner: &Addr,
@@ -34,6 +36,58 @@ pub fn get_cw721_mint_msg(
     Ok(update_wasm_msg)
  }
 
+ pub fn get_cw721_update_transfer_policy_msg(
+    policy: TransferPolicy,
+    nft_contract_address: &Addr
+ ) -> StdResult<WasmMsg> {
+    let update_msg = nft::msg::ExecuteMsg::UpdateTransferPolicy { policy };
+    let update_wasm_msg = WasmMsg::Execute {
+        contract_addr: nft_contract_address.into(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    };
+    Ok(update_wasm_msg)
+ }
+
+ pub fn get_cw721_update_royalty_msg(
+    royalty: Option<Royalty>,
+    nft_contract_address: &Addr
+ ) -> StdResult<WasmMsg> {
+    let update_msg = nft::msg::ExecuteMsg::UpdateDefaultRoyalty { royalty };
+    let update_wasm_msg = WasmMsg::Execute {
+        contract_addr: nft_contract_address.into(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    };
+    Ok(update_wasm_msg)
+ }
+
+ pub fn get_cw721_update_minter_msg(
+    minter: &Addr,
+    nft_contract_address: &Addr
+ ) -> StdResult<WasmMsg> {
+    let update_msg = nft::msg::ExecuteMsg::UpdateMinter { minter: minter.into() };
+    let update_wasm_msg = WasmMsg::Execute {
+        contract_addr: nft_contract_address.into(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    };
+    Ok(update_wasm_msg)
+ }
+
+ pub fn get_cw721_update_collection_info_msg(
+    collection_info: CollectionInfo,
+    nft_contract_address: &Addr
+ ) -> StdResult<WasmMsg> {
+    let update_msg = nft::msg::ExecuteMsg::UpdateCollectionInfo { collection_info };
+    let update_wasm_msg = WasmMsg::Execute {
+        contract_addr: nft_contract_address.into(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    };
+    Ok(update_wasm_msg)
+ }
+
  pub fn get_cw721_burn_msg(
     token_id: String,
     nft_contract_address: &Addr
@@ -47,6 +101,20 @@ pub fn get_cw721_mint_msg(
     Ok(burn_wasm_msg)
  }
 
+ pub fn get_cw721_transfer_msg(
+    token_id: String,
+    recipient: &Addr,
+    nft_contract_address: &Addr
+ ) -> StdResult<WasmMsg> {
+    let transfer_msg = nft::msg::ExecuteMsg::TransferNft { recipient: recipient.into(), token_id }; 
+    let transfer_wasm_msg = WasmMsg::Execute {
+        contract_addr: nft_contract_address.into(),
+        msg: to_binary(&transfer_msg)?,
+        funds: vec![],
+    };
+    Ok(transfer_wasm_msg)
+ }
+
  pub fn get_staking_bond_msg(
     nft_id:Uint128,
     staking_contract_address: &Addr
@@ -73,6 +141,21 @@ pub fn get_cw721_mint_msg(
     Ok(unbond_wasm_msg)
  }
  
+ pub fn get_staking_unbond_denom_msg(
+    nft_id: Uint128,
+    denom: String,
+    amount: Uint128,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let unbond_msg = staking::msg::ExecuteMsg::UnbondDenom { nft_id, denom, amount };
+    let unbond_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&unbond_msg)?,
+        funds: vec![],
+    };
+    Ok(unbond_wasm_msg)
+ }
+
  pub fn get_staking_claim_msg(
     nft_id: Uint128,
     sender: &Addr,
@@ -87,6 +170,78 @@ pub fn get_cw721_mint_msg(
     };
     Ok(claim_wasm_msg)
  }
+
+ pub fn get_staking_claim_denom_msg(
+    nft_id: Uint128,
+    sender: &Addr,
+    denom: String,
+    amount: Uint128,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let claim_msg = staking::msg::ExecuteMsg::ClaimDenom { nft_id, sender: sender.into(), denom, amount };
+    let claim_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&claim_msg)?,
+        funds: vec![],
+    };
+    Ok(claim_wasm_msg)
+ }
+
+ pub fn get_staking_claim_rewards_msg(
+    nft_id: Uint128,
+    recipient: &Addr,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let claim_rewards_msg = staking::msg::ExecuteMsg::ClaimRewards { nft_id, recipient: recipient.into() }; 
+    let claim_rewards_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&claim_rewards_msg)?,
+        funds: vec![],
+    };
+    Ok(claim_rewards_wasm_msg)
+ }
+
+ pub fn get_staking_merge_msg(
+    nft_id: Uint128,
+    nft_ids: Vec<Uint128>,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let merge_msg = staking::msg::ExecuteMsg::Merge { nft_id, nft_ids }; 
+    let merge_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&merge_msg)?,
+        funds: vec![],
+    };
+    Ok(merge_wasm_msg)
+ }
+
+ pub fn get_staking_update_agent_msg(
+    agent: &Addr,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let update_msg = staking::msg::ExecuteMsg::UpdateAgent { agent: agent.into() };
+    let update_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&update_msg)?,
+        funds: vec![],
+    };
+    Ok(update_wasm_msg)
+ }
+
+ pub fn get_staking_split_msg(
+    nft_id: Uint128,
+    new_nft_ids: Vec<Uint128>,
+    amounts: Vec<Uint128>,
+    staking_contract_address: &Addr,
+ ) -> StdResult<WasmMsg> {
+    let split_msg = staking::msg::ExecuteMsg::Split { nft_id, new_nft_ids, amounts }; 
+    let split_wasm_msg = WasmMsg::Execute {
+        contract_addr: staking_contract_address.into(),
+        msg: to_binary(&split_msg)?,
+        funds: vec![],
+    };
+    Ok(split_wasm_msg)
+ }
  pub fn get_nft_owner(deps: Deps, nft_id: String, nft_contract_addr: &String) -> StdResult<String> {
     let resp: cw721::OwnerOfResponse = deps
         .querier
@@ -103,6 +258,74 @@ pub fn get_cw721_mint_msg(
  pub fn get_staking_bonded (deps: Deps, nft_id: String, staking_contract_addr: &String) -> StdResult<Uint128> {
     let resp: Uint128 = deps
         .querier
-        .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id : nft_id })?;
+        .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::BondedByNFT { nft_id })?;
+    Ok(resp)
+ }
+
+ pub fn get_staking_bonded_coins (deps: Deps, nft_id: String, staking_contract_addr: &String) -> StdResult<Vec<Coin>> {
+    let resp: Vec<Coin> = deps
+        .querier
+        .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::BondedCoinsByNFT { nft_id })?;
+    Ok(resp)
+ }
+
+ pub fn get_staking_rewards (deps: Deps, nft_id: String, staking_contract_addr: &String) -> StdResult<Uint128> {
+    let resp: Uint128 = deps
+        .querier
+        .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::RewardsByNFT { nft_id })?;
+    Ok(resp)
+ }
+
+ pub fn get_staking_allocation (deps: Deps, amount: Uint128, staking_contract_addr: &String) -> StdResult<Vec<ValidatorAllocation>> {
+    let resp: staking::msg::AllocationResponse = deps
+        .querier
+        .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::Allocation { amount })?;
+    Ok(resp.allocations
+        .into_iter()
+        .map(|allocation| ValidatorAllocation { validator: allocation.validator, amount: allocation.amount })
+        .collect())
+ }
+
+ pub fn get_nft_all_info(deps: Deps, nft_id: String, nft_contract_addr: &String) -> StdResult<cw721::AllNftInfoResponse<Metadata>> {
+    let resp: cw721::AllNftInfoResponse<Metadata> = deps
+        .querier
+        .query_wasm_smart(nft_contract_addr, &nft::msg::QueryMsg::AllNftInfo { token_id: nft_id, include_expired: None })?;
     Ok(resp)
- }
\ No newline at end of file
+ }
+
+ // Every claim of the NFT on registered denoms, going through all the pages of the staking query
+ pub fn get_staking_denom_claims(deps: Deps, nft_id: String, staking_contract_addr: &String) -> StdResult<Vec<DenomClaim>> {
+    let mut claims: Vec<DenomClaim> = vec![];
+    loop {
+        let start_after = claims.last().map(|claim| claim.denom.clone());
+        let resp: staking::msg::DenomClaimsResponse = deps
+            .querier
+            .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::DenomClaims { nft_id: nft_id.clone(), start_after, limit: None })?;
+        if resp.claims.is_empty() {
+            return Ok(claims)
+        }
+        claims.extend(resp.claims);
+    }
+ }
+
+ pub fn get_nft_tokens(deps: Deps, owner: String, start_after: Option<String>, limit: Option<u32>, nft_contract_addr: &String) -> StdResult<Vec<String>> {
+    let resp: cw721::TokensResponse = deps
+        .querier
+        .query_wasm_smart(nft_contract_addr, &nft::msg::QueryMsg::Tokens { owner, start_after, limit })?;
+    Ok(resp.tokens)
+ }
+
+ // Every claim of the NFT, going through all the pages of the staking query
+ pub fn get_staking_claims(deps: Deps, nft_id: String, staking_contract_addr: &String) -> StdResult<Vec<NftClaim>> {
+    let mut claims: Vec<NftClaim> = vec![];
+    loop {
+        let start_after = claims.last().map(|claim| claim.validator.clone());
+        let resp: staking::msg::NftClaimsResponse = deps
+            .querier
+            .query_wasm_smart(staking_contract_addr, &staking::msg::QueryMsg::Claims { nft_id: nft_id.clone(), start_after, limit: None })?;
+        if resp.claims.is_empty() {
+            return Ok(claims)
+        }
+        claims.extend(resp.claims);
+    }
+ }
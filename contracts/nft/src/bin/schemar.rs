@@ -1,13 +1,12 @@
 use cosmwasm_schema::write_api;
-use::nft::msg::{ExecuteMsg,QueryMsg};
-
-use cw721_base::InstantiateMsg;
+use::nft::msg::{InstantiateMsg,ExecuteMsg,QueryMsg,MigrateMsg};
 
 fn main() {
     write_api! {                                   
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
      }
 
 }
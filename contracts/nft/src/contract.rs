@@ -3,29 +3,170 @@ use serde::{Deserialize, Serialize};
 
 #[cfg(not(feature="library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{Empty, Coin, Binary, Deps, DepsMut,Env, MessageInfo, Response, StdResult};
-use cw2::set_contract_version;
-pub use cw721_base::{Cw721Contract, ContractError, InstantiateMsg, MintMsg, MinterResponse};
+use cosmwasm_std::{Addr, Empty, Coin, Binary, Deps, DepsMut,Env, MessageInfo, Order, Response, StdError, StdResult, Storage, Timestamp, Decimal, Uint128, to_binary, from_slice};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Bound, Map};
+use cw721_base::state::TokenInfo;
+
+use crate::state::{CollectionInfo, CollectionTotals, Royalty, TransferPolicy, COLLECTION_INFO, COLLECTION_TOTALS, DEFAULT_ROYALTY, TRANSFER_POLICY};
+use crate::token_uri::token_uri;
+pub use cw721_base::{Cw721Contract, MintMsg, MinterResponse};
+pub use crate::msg::InstantiateMsg;
+pub use crate::error::ContractError;
 
 // Version info for migration
 const CONTRACT_NAME: &str = "crates.io:cw721-angel";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
-#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, )]
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub enum Status {
-    Bonded, Unbonding
+    #[default]
+    Bonded,
+    /// Bonded and can not be unbonded before lock_until
+    Locked,
+    /// Tokens are released at release_at
+    Unbonding { release_at: Timestamp },
+    /// Tokens are released and can be claimed
+    Claimable,
+    /// Tokens were claimed. The NFT is kept as a receipt
+    Claimed,
 }
 
+impl Status {
+    /// Status of bonded tokens, Locked until lock_until
+    pub fn bonded(lock_until: Option<Timestamp>, time: Timestamp) -> Status {
+        match lock_until {
+            Some(until) if until > time => Status::Locked,
+            _ => Status::Bonded,
+        }
+    }
+
+    pub fn is_bonded(&self) -> bool {
+        matches!(self, Status::Bonded | Status::Locked)
+    }
+
+    pub fn is_unbonding(&self) -> bool {
+        matches!(self, Status::Unbonding { .. } | Status::Claimable)
+    }
+}
+
+/// Tokens of the position delegated to a validator
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, )]
+pub struct ValidatorAllocation {
+    pub validator: String,
+    pub amount: Uint128,
+}
+
+/// Tokens of the position being unbonded, released at release_at
 #[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, )]
+pub struct PendingUnbonding {
+    pub amount: Uint128,
+    pub release_at: Timestamp,
+    /// Registered denom of the tokens, None for the bond denom
+    #[serde(default)]
+    pub denom: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, JsonSchema, Debug, Default)]
 pub struct Metadata {
     pub native: Vec<Coin>,
     pub status: Status,
+    /// Bonded tokens can not be unbonded before this time
+    #[serde(default)]
+    pub lock_until: Option<Timestamp>,
+    /// Set by the contract on mint
+    #[serde(default)]
+    pub created_height: u64,
+    #[serde(default)]
+    pub created_at: Timestamp,
+    /// Set by the contract on mint and on every metadata update
+    #[serde(default)]
+    pub updated_height: u64,
+    #[serde(default)]
+    pub updated_at: Timestamp,
+    /// Bond denom tokens ever bonded by the position
+    #[serde(default)]
+    pub total_bonded: Uint128,
+    /// Share of the pooled delegations held by the position on its last update
+    #[serde(default)]
+    pub allocations: Vec<ValidatorAllocation>,
+    #[serde(default)]
+    pub unbonding: Vec<PendingUnbonding>,
+    /// Rewards ever claimed by the position
+    #[serde(default)]
+    pub lifetime_rewards: Uint128,
+    /// CW2981 royalty of the token, instead of the default one
+    #[serde(default)]
+    pub royalty_percentage: Option<u64>,
+    #[serde(default)]
+    pub royalty_payment_address: Option<String>,
+}
+
+impl Metadata {
+    /// Status at time. Expired locks are Bonded and released unbondings are Claimable
+    pub fn status_at(&self, time: Timestamp) -> Status {
+        match &self.status {
+            Status::Locked => Status::bonded(self.lock_until, time),
+            Status::Unbonding { release_at } if *release_at <= time => Status::Claimable,
+            status => status.clone(),
+        }
+    }
 }
 
 pub type Extension = Metadata;    
 
+// Status before Unbonding carried its release time
+#[derive(Deserialize)]
+enum LegacyStatus {
+    Bonded, Unbonding
+}
+
+// Metadata of the first version, before the position history
+#[derive(Deserialize)]
+struct LegacyMetadata {
+    native: Vec<Coin>,
+    status: LegacyStatus,
+}
+
+impl LegacyMetadata {
+    // Unbonding tokens are released with their last pending unbonding, read from the staking claims
+    fn into_metadata(self, unbonding: Vec<PendingUnbonding>) -> Metadata {
+        let status = match self.status {
+            LegacyStatus::Bonded => Status::Bonded,
+            LegacyStatus::Unbonding => Status::Unbonding {
+                release_at: unbonding.iter().map(|unbonding| unbonding.release_at).max().unwrap_or_default(),
+            },
+        };
+        Metadata { native: self.native, status, unbonding, ..Default::default() }
+    }
+}
+
+// Pending unbondings of a first version token from the claims of its nft_id on the staking contract
+fn legacy_unbonding(deps: Deps, staking: Option<&Addr>, token_id: &str) -> Result<Vec<PendingUnbonding>, ContractError> {
+    let unknown_release = || ContractError::UnknownRelease { token_id: token_id.to_string() };
+    let staking = staking.ok_or_else(unknown_release)?;
+    let mut unbonding = vec![];
+    let mut start_after = None;
+    loop {
+        let msg = staking::msg::QueryMsg::Claims { nft_id: token_id.to_string(), start_after, limit: None };
+        let res: staking::msg::NftClaimsResponse = deps.querier.query_wasm_smart(staking, &msg)?;
+        match res.claims.last() {
+            Some(last) => start_after = Some(last.validator.clone()),
+            None => break,
+        }
+        unbonding.extend(res.claims.into_iter().map(|claim| PendingUnbonding { amount: claim.amount, release_at: claim.release_at, denom: None }));
+    }
+    if unbonding.is_empty() {
+        return Err(unknown_release());
+    }
+    Ok(unbonding)
+}
+
 pub mod entry {
-    use crate::msg::{ExecuteMsg, QueryMsg};
+    use crate::msg::{ExecuteMsg, QueryMsg, MigrateMsg, StatusFilter, RoyaltiesInfoResponse, CheckRoyaltiesResponse, CollectionInfoResponse};
+
+    const DEFAULT_LIMIT: u32 = 10;
+    const MAX_LIMIT: u32 = 100;
 
     use super::*;
 
@@ -37,7 +178,14 @@ pub mod entry {
         msg: InstantiateMsg,
     ) -> Result<Response, ContractError> {
         let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
-        let res = cw721_base::Cw721Contract::instantiate(&contract, deps.branch(), env, info, msg)?;
+        let base_msg = cw721_base::InstantiateMsg { name: msg.name, symbol: msg.symbol, minter: msg.minter };
+        let res = cw721_base::Cw721Contract::instantiate(&contract, deps.branch(), env, info, base_msg)?;
+        if let Some(royalty) = msg.royalty {
+            let royalty = check_royalty(deps.as_ref(), royalty)?;
+            DEFAULT_ROYALTY.save(deps.storage, &royalty)?;
+        }
+        COLLECTION_INFO.save(deps.storage, &msg.collection_info.unwrap_or_default())?;
+        COLLECTION_TOTALS.save(deps.storage, &CollectionTotals::default())?;
 
         // Explicitly set contract name and version, otherwise set to cw721-base info
         set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)
@@ -49,20 +197,203 @@ pub mod entry {
 
     #[cfg_attr(not(feature = "library"), entry_point)]
     pub fn execute(
-        deps: DepsMut,
+        mut deps: DepsMut,
         env: Env,
         info: MessageInfo,
         msg: ExecuteMsg,
     ) -> Result<Response, ContractError> {
        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
        match msg {
+            ExecuteMsg::Mint(mut mint_msg) => {
+                check_token_royalty(&mint_msg.extension)?;
+                mint_msg.extension.created_height = env.block.height;
+                mint_msg.extension.created_at = env.block.time;
+                mint_msg.extension.updated_height = env.block.height;
+                mint_msg.extension.updated_at = env.block.time;
+                if mint_msg.token_uri.is_none() {
+                    let collection = contract.contract_info.load(deps.storage)?.name;
+                    mint_msg.token_uri = Some(token_uri(&collection, &mint_msg.token_id, &mint_msg.extension)?);
+                }
+                let extension = mint_msg.extension.clone();
+                let res = cw721_base::Cw721Contract::execute(&contract, deps.branch(), env, info, cw721_base::ExecuteMsg::Mint(mint_msg))?;
+                update_totals(deps.storage, None, Some(&extension))?;
+                Ok(res)
+            },
             ExecuteMsg::UpdateMetadata {
                 token_id,
                 token_uri,
                 extension,
-            } => execute_update_metadata(deps, env, info, token_id, token_uri, extension),
-            _ => cw721_base::Cw721Contract::execute(&contract, deps, env, info, msg.into()),
+            } => {
+                check_token_royalty(&extension)?;
+                execute_update_metadata(deps, env, info, token_id, token_uri, extension)
+            },
+            ExecuteMsg::Burn { token_id } => execute_burn(deps, env, info, token_id),
+            ExecuteMsg::TransferNft { ref recipient, ref token_id } | ExecuteMsg::SendNft { contract: ref recipient, ref token_id, .. } => {
+                check_transfer(deps.as_ref(), &env, &info, recipient, token_id)?;
+                Ok(cw721_base::Cw721Contract::execute(&contract, deps, env, info, msg.into())?)
+            },
+            ExecuteMsg::UpdateTransferPolicy { policy } => execute_update_transfer_policy(deps, info, policy),
+            ExecuteMsg::UpdateDefaultRoyalty { royalty } => execute_update_default_royalty(deps, info, royalty),
+            ExecuteMsg::UpdateCollectionInfo { collection_info } => execute_update_collection_info(deps, info, collection_info),
+            ExecuteMsg::UpdateMinter { minter } => execute_update_minter(deps, info, minter),
+            _ => Ok(cw721_base::Cw721Contract::execute(&contract, deps, env, info, msg.into())?),
+        }
+    }
+
+    // Transfers between holders follow the transfer policy. The minter sends and receives NFTs on unbond and claim
+    fn check_transfer(deps: Deps, env: &Env, info: &MessageInfo, recipient: &str, token_id: &str) -> Result<(), ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let minter = contract.minter.load(deps.storage)?;
+        if info.sender == minter || recipient == minter.as_str() {
+            return Ok(())
+        }
+        let policy = TRANSFER_POLICY.may_load(deps.storage)?.unwrap_or_default();
+        if policy.soulbound {
+            return Err(ContractError::Soulbound {})
+        }
+        let extension = contract.tokens.load(deps.storage, token_id)?.extension;
+        match extension.status_at(env.block.time) {
+            Status::Unbonding { .. } | Status::Claimable if policy.block_unbonding => {
+                return Err(ContractError::UnbondingTransfer { token_id: token_id.to_string() })
+            },
+            Status::Locked if policy.block_locked => {
+                return Err(ContractError::LockedTransfer { token_id: token_id.to_string(), until: extension.lock_until.unwrap_or_default() })
+            },
+            _ => {},
+        }
+        if !policy.allowed_recipients.is_empty() && !policy.allowed_recipients.iter().any(|allowed| allowed == recipient) {
+            return Err(ContractError::RecipientNotAllowed { recipient: recipient.to_string() })
+        }
+        Ok(())
+    }
+
+    fn execute_update_collection_info(deps: DepsMut, info: MessageInfo, collection_info: CollectionInfo) -> Result<Response, ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        if info.sender != contract.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {})
+        }
+        COLLECTION_INFO.save(deps.storage, &collection_info)?;
+        Ok(Response::new().add_attribute("action", "update_collection_info"))
+    }
+
+    fn execute_update_minter(deps: DepsMut, info: MessageInfo, minter: String) -> Result<Response, ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        if info.sender != contract.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {})
+        }
+        let minter = deps.api.addr_validate(&minter)?;
+        contract.minter.save(deps.storage, &minter)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_minter")
+            .add_attribute("minter", minter))
+    }
+
+    // Bonded and locked positions are active. Totals are never negative, even for tokens minted before they were kept
+    fn update_totals(storage: &mut dyn Storage, old: Option<&Metadata>, new: Option<&Metadata>) -> StdResult<()> {
+        let mut totals = COLLECTION_TOTALS.may_load(storage)?.unwrap_or_default();
+        if let Some(old) = old.filter(|old| old.status.is_bonded()) {
+            totals.active_positions = totals.active_positions.saturating_sub(1);
+            for coin in old.native.iter() {
+                if let Some(total) = totals.bonded.iter_mut().find(|total| total.denom == coin.denom) {
+                    total.amount = total.amount.saturating_sub(coin.amount);
+                }
+            }
+        }
+        if let Some(new) = new.filter(|new| new.status.is_bonded()) {
+            totals.active_positions += 1;
+            for coin in new.native.iter() {
+                match totals.bonded.iter_mut().find(|total| total.denom == coin.denom) {
+                    Some(total) => total.amount += coin.amount,
+                    None => totals.bonded.push(coin.clone()),
+                }
+            }
+        }
+        totals.bonded.retain(|total| !total.amount.is_zero());
+        COLLECTION_TOTALS.save(storage, &totals)
+    }
+
+    fn query_collection_info(deps: Deps) -> StdResult<CollectionInfoResponse> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let contract_info = contract.contract_info.load(deps.storage)?;
+        let collection_info = COLLECTION_INFO.may_load(deps.storage)?.unwrap_or_default();
+        let totals = COLLECTION_TOTALS.may_load(deps.storage)?.unwrap_or_default();
+        Ok(CollectionInfoResponse {
+            name: contract_info.name,
+            symbol: contract_info.symbol,
+            description: collection_info.description,
+            image: collection_info.image,
+            external_link: collection_info.external_link,
+            total_bonded: totals.bonded,
+            active_positions: totals.active_positions,
+        })
+    }
+
+    fn execute_update_default_royalty(deps: DepsMut, info: MessageInfo, royalty: Option<Royalty>) -> Result<Response, ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        if info.sender != contract.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {})
+        }
+        let res = Response::new().add_attribute("action", "update_default_royalty");
+        match royalty {
+            Some(royalty) => {
+                let royalty = check_royalty(deps.as_ref(), royalty)?;
+                DEFAULT_ROYALTY.save(deps.storage, &royalty)?;
+                Ok(res
+                    .add_attribute("payment_address", royalty.payment_address)
+                    .add_attribute("percentage", royalty.percentage.to_string()))
+            },
+            None => {
+                DEFAULT_ROYALTY.remove(deps.storage);
+                Ok(res)
+            },
+        }
+    }
+
+    fn check_royalty(deps: Deps, royalty: Royalty) -> Result<Royalty, ContractError> {
+        if royalty.percentage > 100 {
+            return Err(ContractError::InvalidRoyaltyPercentage {})
+        }
+        deps.api.addr_validate(&royalty.payment_address)?;
+        Ok(royalty)
+    }
+
+    fn check_token_royalty(metadata: &Metadata) -> Result<(), ContractError> {
+        match metadata.royalty_percentage {
+            Some(percentage) if percentage > 100 => Err(ContractError::InvalidRoyaltyPercentage {}),
+            _ => Ok(()),
+        }
+    }
+
+    // The token royalty, if set, or the default one
+    fn query_royalty_info(deps: Deps, token_id: String, sale_price: Uint128) -> StdResult<RoyaltiesInfoResponse> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let extension = contract.tokens.load(deps.storage, &token_id)?.extension;
+        let royalty = match (extension.royalty_payment_address, extension.royalty_percentage) {
+            (Some(payment_address), Some(percentage)) => Some(Royalty { payment_address, percentage }),
+            _ => DEFAULT_ROYALTY.may_load(deps.storage)?,
+        };
+        Ok(match royalty {
+            Some(royalty) => RoyaltiesInfoResponse {
+                address: royalty.payment_address,
+                royalty_amount: sale_price * Decimal::percent(royalty.percentage),
+            },
+            None => RoyaltiesInfoResponse { address: String::new(), royalty_amount: Uint128::zero() },
+        })
+    }
+
+    fn execute_update_transfer_policy(deps: DepsMut, info: MessageInfo, policy: TransferPolicy) -> Result<Response, ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        if info.sender != contract.minter.load(deps.storage)? {
+            return Err(ContractError::Unauthorized {})
+        }
+        for recipient in policy.allowed_recipients.iter() {
+            deps.api.addr_validate(recipient)?;
         }
+        TRANSFER_POLICY.save(deps.storage, &policy)?;
+        Ok(Response::new()
+            .add_attribute("action", "update_transfer_policy")
+            .add_attribute("soulbound", policy.soulbound.to_string())
+        )
     }
 
     #[cfg_attr(not(feature = "library"), entry_point)]
@@ -72,27 +403,69 @@ pub mod entry {
         msg: QueryMsg               
     ) -> StdResult<Binary> {
         let tract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
-        cw721_base::Cw721Contract::query(&tract, deps, env, msg.into())
+        match msg {
+            QueryMsg::TokensByStatus { status, start_after, limit } => to_binary(&query_tokens_by_status(deps, env, status, start_after, limit)?),
+            QueryMsg::TransferPolicy {} => to_binary(&TRANSFER_POLICY.may_load(deps.storage)?.unwrap_or_default()),
+            QueryMsg::RoyaltyInfo { token_id, sale_price } => to_binary(&query_royalty_info(deps, token_id, sale_price)?),
+            QueryMsg::CheckRoyalties {} => to_binary(&CheckRoyaltiesResponse { royalty_payments: true }),
+            QueryMsg::CollectionInfo {} => to_binary(&query_collection_info(deps)?),
+            _ => cw721_base::Cw721Contract::query(&tract, deps, env, msg.into()),
+        }
+    }
+
+    // Tokens are filtered on their status at the current block time
+    fn query_tokens_by_status(
+        deps: Deps,
+        env: Env,
+        status: StatusFilter,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    ) -> StdResult<cw721::TokensResponse> {
+        let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+        // cw721_base tokens are indexed with its own cw_storage_plus version. Their primary map is read to bound the range
+        let tokens: Map<&str, TokenInfo<Extension>> = Map::new("tokens");
+        let start = start_after.as_deref().map(Bound::exclusive);
+        let tokens = tokens
+            .range(deps.storage, start, None, Order::Ascending)
+            .filter(|item| match item {
+                Ok((_, token_info)) => status.matches(&token_info.extension.status_at(env.block.time)),
+                Err(_) => true,
+            })
+            .take(limit)
+            .map(|item| item.map(|(token_id, _)| token_id))
+            .collect::<StdResult<Vec<_>>>()?;
+        Ok(cw721::TokensResponse { tokens })
     }
 
+    // Creation is kept from the stored metadata. Without token_uri, it is generated from the metadata
     fn execute_update_metadata(
         deps: DepsMut,
-        _env: Env,
+        env: Env,
         info: MessageInfo,
         token_id: String,
         token_uri: Option<String>,
-        metadata: Metadata
+        mut metadata: Metadata
     ) -> Result<Response, ContractError> {
         let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
         let minter = contract.minter.load(deps.storage)?;
         if info.sender != minter {
             Err(ContractError::Unauthorized {})
         } else {
+            let collection = contract.contract_info.load(deps.storage)?.name;
+            let old = contract.tokens.may_load(deps.storage, &token_id)?.map(|token_info| token_info.extension);
+            update_totals(deps.storage, old.as_ref(), Some(&metadata))?;
             contract
                 .tokens
                 .update(deps.storage, &token_id, |token| match token {
                     Some(mut token_info) => {
-                        token_info.token_uri = token_uri.clone();
+                        metadata.created_height = token_info.extension.created_height;
+                        metadata.created_at = token_info.extension.created_at;
+                        metadata.updated_height = env.block.height;
+                        metadata.updated_at = env.block.time;
+                        token_info.token_uri = match token_uri.clone() {
+                            Some(token_uri) => Some(token_uri),
+                            None => Some(crate::token_uri::token_uri(&collection, &token_id, &metadata)?),
+                        };
                         token_info.extension = metadata;
                         Ok(token_info)
                     },
@@ -101,13 +474,128 @@ pub mod entry {
             Ok(Response::new())
         }
     }
+
+    // The minter burns the NFT once its position is claimable or claimed. Anyone else is subject to cw721_base rules
+    fn execute_burn(
+        mut deps: DepsMut,
+        env: Env,
+        info: MessageInfo,
+        token_id: String,
+    ) -> Result<Response, ContractError> {
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let minter = contract.minter.load(deps.storage)?;
+        let token_info = contract.tokens.load(deps.storage, &token_id)?;
+        let res = if info.sender != minter {
+            cw721_base::Cw721Contract::execute(&contract, deps.branch(), env, info, cw721_base::ExecuteMsg::Burn { token_id })?
+        } else {
+            if !matches!(token_info.extension.status_at(env.block.time), Status::Claimable | Status::Claimed) {
+                return Err(ContractError::NotClaimable { token_id });
+            }
+            contract.tokens.remove(deps.storage, &token_id)?;
+            contract.decrement_tokens(deps.storage)?;
+            Response::new()
+                .add_attribute("action", "burn")
+                .add_attribute("sender", info.sender)
+                .add_attribute("token_id", token_id)
+        };
+        update_totals(deps.storage, Some(&token_info.extension), None)?;
+        Ok(res)
+    }
+
+    // Tokens minted before the history fields are stamped on the migration block. Their total bonded is their current bond denom amount.
+    // Tokens stored with the legacy status get their release time or lock
+    #[cfg_attr(not(feature = "library"), entry_point)]
+    pub fn migrate(deps: DepsMut, env: Env, msg: MigrateMsg) -> Result<Response, ContractError> {
+        // Only older versions of this contract are migrated
+        let version = get_contract_version(deps.storage)?;
+        let invalid = || ContractError::InvalidMigration { contract: version.contract.clone(), version: version.version.clone() };
+        let stored: semver::Version = version.version.parse().map_err(|_| invalid())?;
+        let current: semver::Version = CONTRACT_VERSION.parse().map_err(|_| invalid())?;
+        if version.contract != CONTRACT_NAME || stored >= current {
+            return Err(invalid());
+        }
+        set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let staking = match msg.staking {
+            Some(staking) => {
+                let staking = deps.api.addr_validate(&staking)?;
+                let agent: String = deps.querier.query_wasm_smart(&staking, &staking::msg::QueryMsg::Agent {})?;
+                if agent != contract.minter.load(deps.storage)? {
+                    return Err(ContractError::ForeignStaking { staking: staking.to_string() });
+                }
+                Some(staking)
+            },
+            None => None,
+        };
+        let raw_tokens: Map<&str, Empty> = Map::new("tokens");
+        // Listed from the raw map, as listing the indexed tokens parses them
+        let token_ids = raw_tokens
+            .keys_raw(deps.storage, None, None, Order::Ascending)
+            .map(String::from_utf8)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(StdError::from)?;
+        let bond_denom = deps.querier.query_bonded_denom()?;
+        let mut migrated = 0u64;
+        COLLECTION_TOTALS.save(deps.storage, &CollectionTotals::default())?;
+        for token_id in token_ids {
+            let key = raw_tokens.key(&token_id);
+            let raw = deps.storage.get(&key).unwrap_or_default();
+            let (mut token_info, mut changed) = match from_slice::<TokenInfo<Metadata>>(&raw) {
+                Ok(token_info) => (token_info, false),
+                Err(_) => {
+                    let legacy: TokenInfo<LegacyMetadata> = from_slice(&raw)?;
+                    let unbonding = match legacy.extension.status {
+                        LegacyStatus::Bonded => vec![],
+                        LegacyStatus::Unbonding => legacy_unbonding(deps.as_ref(), staking.as_ref(), &token_id)?,
+                    };
+                    // Saving the indexed token reads the stored one, which can not be parsed. Its owner index is kept
+                    deps.storage.remove(&key);
+                    let token_info = TokenInfo {
+                        owner: legacy.owner,
+                        approvals: legacy.approvals,
+                        token_uri: legacy.token_uri,
+                        extension: legacy.extension.into_metadata(unbonding),
+                    };
+                    (token_info, true)
+                },
+            };
+            let extension = &mut token_info.extension;
+            if extension.created_height == 0 {
+                extension.created_height = env.block.height;
+                extension.created_at = env.block.time;
+                extension.updated_height = env.block.height;
+                extension.updated_at = env.block.time;
+                if extension.total_bonded.is_zero() {
+                    extension.total_bonded = extension.native.iter()
+                        .filter(|coin| coin.denom == bond_denom)
+                        .map(|coin| coin.amount)
+                        .sum();
+                }
+                changed = true;
+            }
+            if changed {
+                contract.tokens.save(deps.storage, &token_id, &token_info)?;
+                migrated += 1;
+            }
+            update_totals(deps.storage, None, Some(&token_info.extension))?;
+        }
+
+        Ok(Response::new()
+            .add_attribute("action", "migrate")
+            .add_attribute("migrated_tokens", migrated.to_string())
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use cosmwasm_std::{testing::{mock_dependencies, mock_env, mock_info}, coins, from_binary};
+    use crate::msg::StatusFilter;
+    use crate::msg::{CheckRoyaltiesResponse, CollectionInfoResponse, RoyaltiesInfoResponse};
+    use crate::state::TransferPolicy;
+    use cosmwasm_std::{testing::{mock_dependencies, mock_env, mock_info}, coin, coins, from_binary, Storage};
     use cw721::NftInfoResponse;
     const CREATOR: &str = "creator";
 
@@ -121,6 +609,8 @@ mod tests {
             name: "Greeks".to_string(),
             symbol: "drachma".to_string(),
             minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -133,16 +623,27 @@ mod tests {
             extension: Metadata {
                 native: coins(1000, "earth"),
                 status: Status::Bonded,
+                lock_until: None,
+                ..Default::default()
             },
         };
 
         let exec_msg = crate::msg::ExecuteMsg::Mint(mint_msg.clone());
-        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg.into()).unwrap();
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
         let query_msg = crate::msg::QueryMsg::NftInfo { token_id: token_id.to_string() };
         let res : NftInfoResponse<Metadata> = from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
-        assert_eq!(res.token_uri, mint_msg.token_uri);
-        assert_eq!(res.extension, mint_msg.extension);
+        // Token uri is generated from the stamped metadata
+        assert_eq!(res.token_uri, Some(token_uri("Greeks", token_id, &res.extension).unwrap()));
+        // Creation and update are stamped by the contract
+        let env = mock_env();
+        assert_eq!(res.extension, Metadata {
+            created_height: env.block.height,
+            created_at: env.block.time,
+            updated_height: env.block.height,
+            updated_at: env.block.time,
+            ..mint_msg.extension
+        });
 
     }
 
@@ -156,6 +657,8 @@ mod tests {
             name: "Greeks".to_string(),
             symbol: "drachma".to_string(),
             minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
         };
         entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
 
@@ -168,21 +671,27 @@ mod tests {
             extension: Metadata {
                 native: coins(1000, "earth"),
                 status: Status::Bonded,
+                lock_until: None,
+                ..Default::default()
             },
         };
 
         let exec_msg = crate::msg::ExecuteMsg::Mint(mint_msg.clone());
-        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg.into()).unwrap();
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
 
 
         let _old_metadata = Metadata {
             native: coins(1000, "earth"),
             status: Status::Bonded,
+            lock_until: None,
+            ..Default::default()
         };
 
         let new_metadata = Metadata {
             native: coins(2000, "earth"),
             status: Status::Bonded,
+            lock_until: None,
+            ..Default::default()
         };
 
         let exec_msg = crate::msg::ExecuteMsg::UpdateMetadata { 
@@ -191,13 +700,493 @@ mod tests {
             extension: new_metadata.clone() 
         };
 
-        entry::execute(deps.as_mut(), mock_env(), info, exec_msg.into()).unwrap();
+        let mut env = mock_env();
+        env.block.height += 10;
+        env.block.time = env.block.time.plus_seconds(60);
+        entry::execute(deps.as_mut(), env.clone(), info, exec_msg).unwrap();
 
+        // Creation is kept, update is stamped
         let query_msg = crate::msg::QueryMsg::NftInfo { token_id: token_id.to_string() };
         let res : NftInfoResponse<Metadata> = from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
         assert_eq!(res.token_uri, Some(token_uri.to_string()));
-        assert_eq!(res.extension, new_metadata);
+        assert_eq!(res.extension, Metadata {
+            created_height: mock_env().block.height,
+            created_at: mock_env().block.time,
+            updated_height: env.block.height,
+            updated_at: env.block.time,
+            ..new_metadata
+        });
+
+    }
+
+    #[test]
+    fn generated_token_uri() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let lock_until = Timestamp::from_seconds(1_700_000_000);
+        let mint_msg = MintMsg {
+            token_id: "1".to_string(),
+            owner: "bob".to_string(),
+            token_uri: None,
+            extension: Metadata { native: coins(1000, "earth"), status: Status::Locked, lock_until: Some(lock_until), ..Default::default() },
+        };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+
+        let decode = |data_uri: &str, media_type: &str| -> String {
+            let base64 = data_uri.strip_prefix(&format!("data:{};base64,", media_type)).unwrap();
+            String::from_utf8(Binary::from_base64(base64).unwrap().to_vec()).unwrap()
+        };
+        let nft_info = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> NftInfoResponse<Metadata> {
+            let query_msg = crate::msg::QueryMsg::NftInfo { token_id: "1".to_string() };
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap()
+        };
+        let json = decode(&nft_info(&deps).token_uri.unwrap(), "application/json");
+        assert!(json.starts_with(r#"{"name":"Greeks #1","#));
+        assert!(json.contains(r#"{"trait_type":"earth","value":"1000"}"#));
+        assert!(json.contains(r#"{"trait_type":"Status","value":"Locked"}"#));
+        assert!(json.contains(r#"{"trait_type":"Unlock","value":"2023-11-14 22:13 UTC"}"#));
+        let image = json.split(r#""image":""#).nth(1).unwrap().split('"').next().unwrap();
+        let svg = decode(image, "image/svg+xml");
+        assert!(svg.contains(">1000 earth</text>"));
+        assert!(svg.contains(">Status: Locked</text>"));
+        assert!(svg.contains(">Unlock: 2023-11-14 22:13 UTC</text>"));
+
+        // Recomputed on update
+        let extension = Metadata { native: coins(1000, "earth"), status: Status::Unbonding { release_at: Timestamp::from_seconds(951_782_400) }, ..Default::default() };
+        let exec_msg = crate::msg::ExecuteMsg::UpdateMetadata { token_id: "1".to_string(), token_uri: None, extension };
+        entry::execute(deps.as_mut(), mock_env(), info, exec_msg).unwrap();
+        let json = decode(&nft_info(&deps).token_uri.unwrap(), "application/json");
+        assert!(json.contains(r#"{"trait_type":"Status","value":"Unbonding"}"#));
+        assert!(json.contains(r#"{"trait_type":"Unlock","value":"2000-02-29 00:00 UTC"}"#));
+    }
+
+    #[test]
+    fn migrate_history() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_staking("earth", &[], &[]);
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let mint_msg = MintMsg {
+            token_id: "1".to_string(),
+            owner: "bob".to_string(),
+            token_uri: None,
+            extension: Metadata {
+                native: vec![coin(1000, "earth"), coin(500, "moon")],
+                status: Status::Bonded,
+                lock_until: None,
+                ..Default::default()
+            },
+        };
+        entry::execute(deps.as_mut(), mock_env(), info, crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+
+        // Token minted before the history fields. Only the bond denom counts in its total bonded
+        let mut token_info = contract.tokens.load(deps.as_ref().storage, "1").unwrap();
+        token_info.extension.created_height = 0;
+        token_info.extension.created_at = Timestamp::default();
+        contract.tokens.save(deps.as_mut().storage, "1", &token_info).unwrap();
+
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+
+        let mut env = mock_env();
+        env.block.height += 10;
+        let res = entry::migrate(deps.as_mut(), env.clone(), crate::msg::MigrateMsg { staking: None }).unwrap();
+        assert_eq!(res.attributes[1], ("migrated_tokens", "1"));
+        let token_info = contract.tokens.load(deps.as_ref().storage, "1").unwrap();
+        assert_eq!(token_info.extension.created_height, env.block.height);
+        assert_eq!(token_info.extension.total_bonded, Uint128::from(1000u128));
+
+        // Already migrated
+        let err = entry::migrate(deps.as_mut(), env, crate::msg::MigrateMsg { staking: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMigration { contract: CONTRACT_NAME.to_string(), version: CONTRACT_VERSION.to_string() });
+    }
+
+    #[test]
+    fn tokens_by_status() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let now = mock_env().block.time;
+        let statuses = vec![
+            (Status::Bonded, None),
+            (Status::Locked, Some(now.plus_seconds(100))),
+            (Status::Unbonding { release_at: now.plus_seconds(100) }, None),
+            (Status::Unbonding { release_at: now }, None),
+            (Status::Claimed, None),
+        ];
+        for (token_id, (status, lock_until)) in statuses.into_iter().enumerate() {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "bob".to_string(),
+                token_uri: None,
+                extension: Metadata { native: coins(1000, "earth"), status, lock_until, ..Default::default() },
+            };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+        }
+
+        let tokens_by_status = |env: Env, status: StatusFilter, start_after: Option<String>| -> Vec<String> {
+            let query_msg = crate::msg::QueryMsg::TokensByStatus { status, start_after, limit: None };
+            let res : cw721::TokensResponse = from_binary(&entry::query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+            res.tokens
+        };
+        assert_eq!(tokens_by_status(mock_env(), StatusFilter::Bonded, None), vec!["0"]);
+        assert_eq!(tokens_by_status(mock_env(), StatusFilter::Locked, None), vec!["1"]);
+        assert_eq!(tokens_by_status(mock_env(), StatusFilter::Unbonding, None), vec!["2"]);
+        assert_eq!(tokens_by_status(mock_env(), StatusFilter::Claimable, None), vec!["3"]);
+        assert_eq!(tokens_by_status(mock_env(), StatusFilter::Claimed, None), vec!["4"]);
+
+        // The lock expires and the unbonding is released
+        let mut env = mock_env();
+        env.block.time = now.plus_seconds(100);
+        assert_eq!(tokens_by_status(env.clone(), StatusFilter::Bonded, None), vec!["0", "1"]);
+        assert_eq!(tokens_by_status(env.clone(), StatusFilter::Bonded, Some("0".to_string())), vec!["1"]);
+        assert_eq!(tokens_by_status(env.clone(), StatusFilter::Locked, None), Vec::<String>::new());
+        assert_eq!(tokens_by_status(env, StatusFilter::Claimable, None), vec!["2", "3"]);
+    }
+
+    #[test]
+    fn migrate_legacy_status() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_staking("earth", &[], &[]);
+        let contract: Cw721Contract<Extension, Empty, Empty, Empty> = cw721_base::Cw721Contract::default();
+        let raw_tokens: Map<&str, Empty> = Map::new("tokens");
+        let env = mock_env();
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: "agent".to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), env.clone(), mock_info(CREATOR, &[]), init_msg).unwrap();
+
+        // Tokens of the first version, before Unbonding carried its release time
+        let legacy_tokens = [
+            ("1", r#"{"owner":"bob","approvals":[],"token_uri":null,"extension":{"native":[{"denom":"earth","amount":"1000"}],"status":"Bonded"}}"#),
+            ("2", r#"{"owner":"bob","approvals":[],"token_uri":null,"extension":{"native":[{"denom":"earth","amount":"1000"}],"status":"Unbonding"}}"#),
+        ];
+        // A failed migration is reverted, which the mock storage does not do
+        let store_legacy = |storage: &mut dyn Storage| {
+            for (token_id, raw) in legacy_tokens.iter() {
+                storage.set(&raw_tokens.key(token_id), raw.as_bytes());
+            }
+            set_contract_version(storage, CONTRACT_NAME, "0.1.0").unwrap();
+        };
+
+        // The staking contract of the agent holds the claims of token 2 on two validators
+        let (first_release, last_release) = (env.block.time.plus_seconds(50), env.block.time.plus_seconds(100));
+        deps.querier.update_wasm(move |query| {
+            let (contract_addr, msg) = match query {
+                cosmwasm_std::WasmQuery::Smart { contract_addr, msg } => (contract_addr, msg),
+                _ => panic!("unexpected query"),
+            };
+            let res = match from_slice(msg).unwrap() {
+                staking::msg::QueryMsg::Agent {} if contract_addr == "staking" => to_binary("agent"),
+                staking::msg::QueryMsg::Agent {} => to_binary("other_agent"),
+                staking::msg::QueryMsg::Claims { nft_id, start_after: None, .. } if nft_id == "2" => {
+                    let claim = |validator: &str, release_at| staking::state::NftClaim { nft_id: nft_id.clone(), validator: validator.to_string(), amount: Uint128::from(500u128), release_at };
+                    to_binary(&staking::msg::NftClaimsResponse { claims: vec![claim("val1", last_release), claim("val2", first_release)] })
+                },
+                staking::msg::QueryMsg::Claims { .. } => to_binary(&staking::msg::NftClaimsResponse { claims: vec![] }),
+                _ => panic!("unexpected query"),
+            };
+            cosmwasm_std::SystemResult::Ok(cosmwasm_std::ContractResult::Ok(res.unwrap()))
+        });
+
+        // The release time of the unbonding token is read from the staking claims
+        store_legacy(&mut deps.storage);
+        let err = entry::migrate(deps.as_mut(), env.clone(), crate::msg::MigrateMsg { staking: None }).unwrap_err();
+        assert_eq!(err, ContractError::UnknownRelease { token_id: "2".to_string() });
+        store_legacy(&mut deps.storage);
+        let err = entry::migrate(deps.as_mut(), env.clone(), crate::msg::MigrateMsg { staking: Some("foreign".to_string()) }).unwrap_err();
+        assert_eq!(err, ContractError::ForeignStaking { staking: "foreign".to_string() });
+        store_legacy(&mut deps.storage);
+        let res = entry::migrate(deps.as_mut(), env.clone(), crate::msg::MigrateMsg { staking: Some("staking".to_string()) }).unwrap();
+        assert_eq!(res.attributes[1], ("migrated_tokens", "2"));
+        let extension = |token_id: &str| contract.tokens.load(deps.as_ref().storage, token_id).unwrap().extension;
+        assert_eq!(extension("1").status, Status::Bonded);
+        assert_eq!(extension("2").status, Status::Unbonding { release_at: last_release });
+        assert_eq!(extension("2").unbonding, vec![
+            PendingUnbonding { amount: Uint128::from(500u128), release_at: last_release, denom: None },
+            PendingUnbonding { amount: Uint128::from(500u128), release_at: first_release, denom: None },
+        ]);
+        assert_eq!(extension("2").created_height, env.block.height);
+        assert_eq!(extension("2").total_bonded, Uint128::from(1000u128));
+
+        // Still unbonding, not claimable on migration
+        let query_msg = crate::msg::QueryMsg::TokensByStatus { status: StatusFilter::Unbonding, start_after: None, limit: None };
+        let res : cw721::TokensResponse = from_binary(&entry::query(deps.as_ref(), env, query_msg).unwrap()).unwrap();
+        assert_eq!(res.tokens, vec!["2"]);
+    }
+
+    #[test]
+    fn migrate_checks_version() {
+        let mut deps = mock_dependencies();
+        deps.querier.update_staking("earth", &[], &[]);
+
+        // Foreign contract
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        let err = entry::migrate(deps.as_mut(), mock_env(), crate::msg::MigrateMsg { staking: None }).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMigration { contract: "crates.io:other".to_string(), version: "0.1.0".to_string() });
+
+        // Same or newer version
+        for version in [CONTRACT_VERSION, "9.0.0"] {
+            set_contract_version(deps.as_mut().storage, CONTRACT_NAME, version).unwrap();
+            let err = entry::migrate(deps.as_mut(), mock_env(), crate::msg::MigrateMsg { staking: None }).unwrap_err();
+            assert_eq!(err, ContractError::InvalidMigration { contract: CONTRACT_NAME.to_string(), version: version.to_string() });
+        }
+    }
+
+    #[test]
+    fn transfer_policy() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let now = mock_env().block.time;
+        let statuses = vec![
+            (Status::Bonded, None),
+            (Status::Locked, Some(now.plus_seconds(100))),
+            (Status::Unbonding { release_at: now.plus_seconds(100) }, None),
+        ];
+        for (token_id, (status, lock_until)) in statuses.into_iter().enumerate() {
+            let mint_msg = MintMsg {
+                token_id: token_id.to_string(),
+                owner: "bob".to_string(),
+                token_uri: None,
+                extension: Metadata { native: coins(1000, "earth"), status, lock_until, ..Default::default() },
+            };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+        }
+        let transfer = |token_id: &str, recipient: &str| crate::msg::ExecuteMsg::TransferNft { recipient: recipient.to_string(), token_id: token_id.to_string() };
+
+        // Only the minter sets the policy
+        let policy = TransferPolicy { block_unbonding: true, block_locked: true, soulbound: false, allowed_recipients: vec![] };
+        let exec_msg = crate::msg::ExecuteMsg::UpdateTransferPolicy { policy: policy.clone() };
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let res : TransferPolicy = from_binary(&entry::query(deps.as_ref(), mock_env(), crate::msg::QueryMsg::TransferPolicy {}).unwrap()).unwrap();
+        assert_eq!(res, policy);
+
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer("1", "alice")).unwrap_err();
+        assert_eq!(err, ContractError::LockedTransfer { token_id: "1".to_string(), until: now.plus_seconds(100) });
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer("2", "alice")).unwrap_err();
+        assert_eq!(err, ContractError::UnbondingTransfer { token_id: "2".to_string() });
+        // Sent to the minter to be unbonded or claimed
+        let exec_msg = crate::msg::ExecuteMsg::SendNft { contract: CREATOR.to_string(), token_id: "2".to_string(), msg: to_binary("claim").unwrap() };
+        entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), exec_msg).unwrap();
+        // Expired lock
+        let mut env = mock_env();
+        env.block.time = now.plus_seconds(100);
+        entry::execute(deps.as_mut(), env, mock_info("bob", &[]), transfer("1", "alice")).unwrap();
+
+        // Allowed recipients only
+        let policy = TransferPolicy { allowed_recipients: vec!["market".to_string()], ..policy };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::UpdateTransferPolicy { policy: policy.clone() }).unwrap();
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer("0", "carol")).unwrap_err();
+        assert_eq!(err, ContractError::RecipientNotAllowed { recipient: "carol".to_string() });
+        entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), transfer("0", "market")).unwrap();
+
+        // Soulbound
+        let policy = TransferPolicy { soulbound: true, ..policy };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::UpdateTransferPolicy { policy }).unwrap();
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("market", &[]), transfer("0", "market")).unwrap_err();
+        assert_eq!(err, ContractError::Soulbound {});
+        // The minter still moves NFTs
+        entry::execute(deps.as_mut(), mock_env(), info, transfer("2", "bob")).unwrap();
+    }
+
+    #[test]
+    fn royalties() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let royalty = Royalty { payment_address: "treasury".to_string(), percentage: 101 };
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: Some(royalty.clone()),
+            collection_info: None,
+        };
+        let err = entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage {});
+        let init_msg = InstantiateMsg { royalty: Some(Royalty { percentage: 5, ..royalty }), ..init_msg };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let extension = Metadata { native: coins(1000, "earth"), ..Default::default() };
+        let mint_msg = MintMsg { token_id: "1".to_string(), owner: "bob".to_string(), token_uri: None, extension: extension.clone() };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+        // Token with its own royalty
+        let extension = Metadata { royalty_percentage: Some(10), royalty_payment_address: Some("artist".to_string()), ..extension };
+        let mint_msg = MintMsg { token_id: "2".to_string(), owner: "bob".to_string(), token_uri: None, extension: extension.clone() };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+        let mint_msg = MintMsg { token_id: "3".to_string(), owner: "bob".to_string(), token_uri: None, extension: Metadata { royalty_percentage: Some(101), ..extension } };
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap_err();
+        assert_eq!(err, ContractError::InvalidRoyaltyPercentage {});
+
+        let royalty_info = |deps: &cosmwasm_std::OwnedDeps<_, _, _>, token_id: &str| -> RoyaltiesInfoResponse {
+            let query_msg = crate::msg::QueryMsg::RoyaltyInfo { token_id: token_id.to_string(), sale_price: Uint128::from(1000u128) };
+            from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap()
+        };
+        assert_eq!(royalty_info(&deps, "1"), RoyaltiesInfoResponse { address: "treasury".to_string(), royalty_amount: Uint128::from(50u128) });
+        assert_eq!(royalty_info(&deps, "2"), RoyaltiesInfoResponse { address: "artist".to_string(), royalty_amount: Uint128::from(100u128) });
+        let res : CheckRoyaltiesResponse = from_binary(&entry::query(deps.as_ref(), mock_env(), crate::msg::QueryMsg::CheckRoyalties {}).unwrap()).unwrap();
+        assert!(res.royalty_payments);
+
+        // Only the minter updates the default royalty
+        let exec_msg = crate::msg::ExecuteMsg::UpdateDefaultRoyalty { royalty: Some(Royalty { payment_address: "treasury".to_string(), percentage: 2 }) };
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        assert_eq!(royalty_info(&deps, "1").royalty_amount, Uint128::from(20u128));
+        entry::execute(deps.as_mut(), mock_env(), info, crate::msg::ExecuteMsg::UpdateDefaultRoyalty { royalty: None }).unwrap();
+        assert_eq!(royalty_info(&deps, "1"), RoyaltiesInfoResponse { address: String::new(), royalty_amount: Uint128::zero() });
+        assert_eq!(royalty_info(&deps, "2").royalty_amount, Uint128::from(100u128));
+    }
+
+    #[test]
+    fn collection_info() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(CREATOR, &[]);
+        let collection_info = CollectionInfo { description: "Staking positions".to_string(), image: Some("ipfs://image".to_string()), external_link: None };
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: Some(collection_info.clone()),
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+        let tokens = vec![
+            (coins(1000, "earth"), Status::Bonded),
+            (coins(500, "earth"), Status::Locked),
+            (coins(200, "earth"), Status::Unbonding { release_at: Timestamp::default() }),
+        ];
+        for (token_id, (native, status)) in tokens.into_iter().enumerate() {
+            let mint_msg = MintMsg { token_id: token_id.to_string(), owner: "bob".to_string(), token_uri: None, extension: Metadata { native, status, ..Default::default() } };
+            entry::execute(deps.as_mut(), mock_env(), info.clone(), crate::msg::ExecuteMsg::Mint(mint_msg)).unwrap();
+        }
+
+        let query_collection_info = |deps: &cosmwasm_std::OwnedDeps<_, _, _>| -> CollectionInfoResponse {
+            from_binary(&entry::query(deps.as_ref(), mock_env(), crate::msg::QueryMsg::CollectionInfo {}).unwrap()).unwrap()
+        };
+        assert_eq!(query_collection_info(&deps), CollectionInfoResponse {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            description: collection_info.description.clone(),
+            image: collection_info.image.clone(),
+            external_link: None,
+            total_bonded: coins(1500, "earth"),
+            active_positions: 2,
+        });
+
+        // Unbonded and burnt positions are no longer active
+        let extension = Metadata { native: coins(1000, "earth"), status: Status::Unbonding { release_at: Timestamp::default() }, ..Default::default() };
+        let exec_msg = crate::msg::ExecuteMsg::UpdateMetadata { token_id: "0".to_string(), token_uri: None, extension };
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let res = query_collection_info(&deps);
+        assert_eq!((res.total_bonded, res.active_positions), (coins(500, "earth"), 1));
+        entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), crate::msg::ExecuteMsg::Burn { token_id: "1".to_string() }).unwrap();
+        let res = query_collection_info(&deps);
+        assert_eq!((res.total_bonded, res.active_positions), (vec![], 0));
+
+        // Only the minter sets the collection info
+        let collection_info = CollectionInfo { external_link: Some("https://angel.protocol".to_string()), ..collection_info };
+        let exec_msg = crate::msg::ExecuteMsg::UpdateCollectionInfo { collection_info: collection_info.clone() };
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        assert_eq!(query_collection_info(&deps).external_link, collection_info.external_link);
+
+        // Only the minter hands the collection over
+        let exec_msg = crate::msg::ExecuteMsg::UpdateMinter { minter: "bob".to_string() };
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("bob", &[]), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+        let res : crate::msg::MinterResponse = from_binary(&entry::query(deps.as_ref(), mock_env(), crate::msg::QueryMsg::Minter {}).unwrap()).unwrap();
+        assert_eq!(res.minter, "bob");
+        let err = entry::execute(deps.as_mut(), mock_env(), info, crate::msg::ExecuteMsg::UpdateCollectionInfo { collection_info }).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+    }
+
+    #[test]
+    fn minter_burn() {
+        let mut deps = mock_dependencies();
+
+        let info = mock_info(CREATOR, &[]);
+        let init_msg = InstantiateMsg {
+            name: "Greeks".to_string(),
+            symbol: "drachma".to_string(),
+            minter: CREATOR.to_string(),
+            royalty: None,
+            collection_info: None,
+        };
+        entry::instantiate(deps.as_mut(), mock_env(), info.clone(), init_msg).unwrap();
+
+        let mint_msg = MintMsg {
+            token_id: "1".to_string(),
+            owner: "bob".to_string(),
+            token_uri: None,
+            extension: Metadata {
+                native: coins(1000, "earth"),
+                status: Status::Unbonding { release_at: mock_env().block.time.plus_seconds(100) },
+                lock_until: None,
+                ..Default::default()
+            },
+        };
+        let exec_msg = crate::msg::ExecuteMsg::Mint(mint_msg);
+        entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg).unwrap();
+
+        // Neither the owner nor the minter
+        let exec_msg = crate::msg::ExecuteMsg::Burn { token_id: "1".to_string() };
+        let err = entry::execute(deps.as_mut(), mock_env(), mock_info("alice", &[]), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {});
+
+        // Minter can not burn a token it does not own before it is claimable
+        let err = entry::execute(deps.as_mut(), mock_env(), info.clone(), exec_msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NotClaimable { token_id: "1".to_string() });
+
+        // Minter burns a claimable token it does not own
+        let mut env = mock_env();
+        env.block.time = env.block.time.plus_seconds(100);
+        entry::execute(deps.as_mut(), env.clone(), info.clone(), exec_msg.clone()).unwrap();
+        let query_msg = crate::msg::QueryMsg::NumTokens {};
+        let res : cw721::NumTokensResponse = from_binary(&entry::query(deps.as_ref(), mock_env(), query_msg).unwrap()).unwrap();
+        assert_eq!(res.count, 0);
 
+        // Already burnt
+        entry::execute(deps.as_mut(), env, info, exec_msg).unwrap_err();
     }
 
 
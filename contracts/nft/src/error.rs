@@ -0,0 +1,59 @@
+use cosmwasm_std::{StdError, Timestamp};
+use thiserror::Error;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ContractError {
+    #[error("{0}")]
+    Std(#[from] StdError),
+
+    #[error("Unauthorized")]
+    Unauthorized {},
+
+    #[error("token_id already claimed")]
+    Claimed {},
+
+    #[error("Cannot set approval that is already expired")]
+    Expired {},
+
+    #[error("Approval not found for: {spender}")]
+    ApprovalNotFound { spender: String },
+
+    #[error("NFT {token_id} can not be transferred while unbonding")]
+    UnbondingTransfer { token_id: String },
+
+    #[error("NFT {token_id} can not be transferred until {until}")]
+    LockedTransfer { token_id: String, until: Timestamp },
+
+    #[error("NFTs are soulbound and can not be transferred")]
+    Soulbound {},
+
+    #[error("Recipient {recipient} is not allowed to receive NFTs")]
+    RecipientNotAllowed { recipient: String },
+
+    #[error("NFT {token_id} can not be burnt by the minter before it is claimable")]
+    NotClaimable { token_id: String },
+
+    #[error("Royalty percentage can not exceed 100")]
+    InvalidRoyaltyPercentage {},
+
+    #[error("Release time of NFT {token_id} is unknown, migrate with the staking contract holding its claims")]
+    UnknownRelease { token_id: String },
+
+    #[error("Staking contract {staking} is not run by the minter")]
+    ForeignStaking { staking: String },
+
+    #[error("Can not migrate from {contract} version {version}")]
+    InvalidMigration { contract: String, version: String },
+}
+
+impl From<cw721_base::ContractError> for ContractError {
+    fn from(err: cw721_base::ContractError) -> Self {
+        match err {
+            cw721_base::ContractError::Std(err) => ContractError::Std(err),
+            cw721_base::ContractError::Unauthorized {} => ContractError::Unauthorized {},
+            cw721_base::ContractError::Claimed {} => ContractError::Claimed {},
+            cw721_base::ContractError::Expired {} => ContractError::Expired {},
+            cw721_base::ContractError::ApprovalNotFound { spender } => ContractError::ApprovalNotFound { spender },
+        }
+    }
+}
@@ -3,7 +3,7 @@ mod tests {
     use crate::{msg::{ExecuteMsg}, contract::{Metadata, Status}};
     use crate::helpers::{NftContract};
     use cosmwasm_std::{coins, Addr, Coin, Empty, Uint128};
-    use cw721::{ NftInfoResponse};
+    use cw721::{ NftInfoResponse, ApprovalResponse, ApprovalsResponse, OperatorsResponse};
     use cw_multi_test::{App, AppBuilder, Contract, ContractWrapper, Executor};
 
 
@@ -62,7 +62,7 @@ mod tests {
             .instantiate_contract(
                 code_id,
                 Addr::unchecked(ADMIN),
-                &crate::contract::InstantiateMsg{name, symbol, minter},
+                &crate::contract::InstantiateMsg{name, symbol, minter, royalty: None, collection_info: None},
                 &[],
                 "nft",
                 None,
@@ -73,7 +73,7 @@ mod tests {
 
     fn get_nft_info(app: &App, nft_contract:&NftContract, token_id:String) -> NftInfoResponse<Metadata> {
         app.wrap()
-            .query_wasm_smart(nft_contract.addr(), &crate::msg::QueryMsg::NftInfo { token_id: token_id } )
+            .query_wasm_smart(nft_contract.addr(), &crate::msg::QueryMsg::NftInfo { token_id } )
             .unwrap()
     }
 
@@ -133,7 +133,9 @@ mod tests {
 
         let metadata = Metadata{ 
             native: coins(1000, NATIVE_DENOM), 
-            status: Status::Bonded };
+            status: Status::Bonded,
+            lock_until: None,
+            ..Default::default() };
 
         //mint NFT to User
         let mint_msg = crate::contract::MintMsg{
@@ -148,7 +150,8 @@ mod tests {
         app.execute(Addr::unchecked(MINTER), cosmos_msg).unwrap();
 
         let res = get_nft_info(&app, &cw721_contract, "0".to_string());
-        assert_eq!(res.extension, metadata);
+        let block = app.block_info();
+        assert_eq!(res.extension, Metadata { created_height: block.height, created_at: block.time, updated_height: block.height, updated_at: block.time, ..metadata });
         //println!("{:?}", res);
          //check to see if User is the owner.
         //  let owner = get_owner(&app, &cw721_contract, "0".to_string());
@@ -163,7 +166,9 @@ mod tests {
 
         let new_metadata = Metadata{ 
             native: coins(2000, NATIVE_DENOM), 
-            status: Status::Bonded };
+            status: Status::Bonded,
+            lock_until: None,
+            ..Default::default() };
             
         let msg:ExecuteMsg = crate::msg::ExecuteMsg::UpdateMetadata { 
             token_id: TOKEN_ID.to_string(), 
@@ -175,7 +180,52 @@ mod tests {
         app.execute(Addr::unchecked(MINTER), cosmos_msg).unwrap();        
 
         let res = get_nft_info(&app, &cw721_contract, "0".to_string());
-        assert_eq!(res.extension, new_metadata);
+        assert_eq!(res.extension, Metadata { created_height: block.height, created_at: block.time, updated_height: block.height, updated_at: block.time, ..new_metadata });
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn approvals() {
+        let (mut app, code_id_cw721) = store_code();
+        let cw721_contract = cw721_instantiate(&mut app, code_id_cw721, "NFT_name".to_string(), "NFT_symbol".to_string(), MINTER.to_string());
+        let metadata = Metadata { native: coins(1000, NATIVE_DENOM), status: Status::Bonded, ..Default::default() };
+        for token_id in ["0", "1"] {
+            let mint_msg = crate::contract::MintMsg { token_id: token_id.to_string(), owner: USER1.to_string(), token_uri: None, extension: metadata.clone() };
+            app.execute(Addr::unchecked(MINTER), cw721_contract.call(ExecuteMsg::Mint(mint_msg)).unwrap()).unwrap();
+        }
+
+        // Spender approved on one token
+        let msg = ExecuteMsg::Approve { spender: USER2.to_string(), token_id: TOKEN_ID.to_string(), expires: None };
+        app.execute(Addr::unchecked(USER1), cw721_contract.call(msg).unwrap()).unwrap();
+        let res : ApprovalResponse = app.wrap()
+            .query_wasm_smart(cw721_contract.addr(), &crate::msg::QueryMsg::Approval { token_id: TOKEN_ID.to_string(), spender: USER2.to_string(), include_expired: None })
+            .unwrap();
+        assert_eq!(res.approval.spender, USER2);
+        let res : ApprovalsResponse = app.wrap()
+            .query_wasm_smart(cw721_contract.addr(), &crate::msg::QueryMsg::Approvals { token_id: TOKEN_ID.to_string(), include_expired: None })
+            .unwrap();
+        assert_eq!(res.approvals.len(), 1);
+        let msg = ExecuteMsg::Revoke { spender: USER2.to_string(), token_id: TOKEN_ID.to_string() };
+        app.execute(Addr::unchecked(USER1), cw721_contract.call(msg).unwrap()).unwrap();
+        let res : ApprovalsResponse = app.wrap()
+            .query_wasm_smart(cw721_contract.addr(), &crate::msg::QueryMsg::Approvals { token_id: TOKEN_ID.to_string(), include_expired: None })
+            .unwrap();
+        assert_eq!(res.approvals, vec![]);
+        let msg = ExecuteMsg::TransferNft { recipient: USER2.to_string(), token_id: TOKEN_ID.to_string() };
+        app.execute(Addr::unchecked(USER2), cw721_contract.call(msg).unwrap()).unwrap_err();
+
+        // Operator on all tokens of the owner
+        let msg = ExecuteMsg::ApproveAll { operator: USER2.to_string(), expires: None };
+        app.execute(Addr::unchecked(USER1), cw721_contract.call(msg).unwrap()).unwrap();
+        let res : OperatorsResponse = app.wrap()
+            .query_wasm_smart(cw721_contract.addr(), &crate::msg::QueryMsg::AllOperators { owner: USER1.to_string(), include_expired: None, start_after: None, limit: None })
+            .unwrap();
+        assert_eq!(res.operators.len(), 1);
+        assert_eq!(res.operators[0].spender, USER2);
+        let msg = ExecuteMsg::TransferNft { recipient: USER2.to_string(), token_id: "1".to_string() };
+        app.execute(Addr::unchecked(USER2), cw721_contract.call(msg).unwrap()).unwrap();
+        let msg = ExecuteMsg::RevokeAll { operator: USER2.to_string() };
+        app.execute(Addr::unchecked(USER1), cw721_contract.call(msg).unwrap()).unwrap();
+        let msg = ExecuteMsg::TransferNft { recipient: USER2.to_string(), token_id: TOKEN_ID.to_string() };
+        app.execute(Addr::unchecked(USER2), cw721_contract.call(msg).unwrap()).unwrap_err();
+    }
+}
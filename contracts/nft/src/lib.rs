@@ -1,4 +1,7 @@
 pub mod contract;
+pub mod error;
 pub mod helpers;
 pub mod msg;
+pub mod state;
+pub mod token_uri;
 pub mod integration_tests;
\ No newline at end of file
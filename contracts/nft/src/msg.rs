@@ -1,9 +1,32 @@
-use cosmwasm_std::{Binary, Empty};
+use cosmwasm_std::{Binary, Coin, Empty, Uint128};
 use cosmwasm_schema::{cw_serde, QueryResponses};
+use cw721::Expiration;
 use cw721_base::MintMsg; 
 use cw721_base::msg::QueryMsg as Cw721QueryMsg;
 use cw721_base::ExecuteMsg as Cw721ExecuteMsg; 
-use crate::contract::Metadata; 
+use crate::contract::{Metadata, Status}; 
+use crate::state::{CollectionInfo, Royalty, TransferPolicy};
+
+#[cw_serde]
+pub struct InstantiateMsg {
+    /// Name of the NFT contract
+    pub name: String,
+    /// Symbol of the NFT contract
+    pub symbol: String,
+    /// The minter is the only one who can create new NFTs
+    pub minter: String,
+    /// Royalty of tokens without their own. No royalty if not set
+    pub royalty: Option<Royalty>,
+    /// Empty description if not set
+    pub collection_info: Option<CollectionInfo>,
+}
+
+#[cw_serde]
+pub struct MigrateMsg {
+    /// Staking contract holding the claims of the tokens unbonding in the first version, whose release time was not stored.
+    /// Its agent must be the minter
+    pub staking: Option<String>,
+}
 
 #[cw_serde]
 pub enum ExecuteMsg {
@@ -11,13 +34,31 @@ pub enum ExecuteMsg {
     Mint(MintMsg<Metadata>),
     /// Updates metadata of the NFT
     UpdateMetadata { token_id: String, token_uri: Option<String>, extension: Metadata },
-    /// Burn an NFT the sender has access to
+    /// Burn an NFT the sender has access to. The minter burns any claimable or claimed NFT
     Burn { token_id: String },
     /// Transfer is a base message to move a token to another account without triggering actions
     TransferNft { recipient: String, token_id: String },
     /// Send is a base message to transfer a token to a contract and trigger an action
     /// on the receiving contract.
     SendNft {contract: String,token_id: String,msg: Binary,},    
+    /// Allows spender to transfer / send the token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    Approve { spender: String, token_id: String, expires: Option<Expiration> },
+    /// Remove previously granted Approval
+    Revoke { spender: String, token_id: String },
+    /// Allows operator to transfer / send any token from the owner's account.
+    /// If expiration is set, then this allowance has a time/height limit
+    ApproveAll { operator: String, expires: Option<Expiration> },
+    /// Remove previously granted ApproveAll permission
+    RevokeAll { operator: String },
+    /// Sets the policy of transfers between holders, can only be called by the contract minter
+    UpdateTransferPolicy { policy: TransferPolicy },
+    /// Sets the royalty of tokens without their own, can only be called by the contract minter. None removes it
+    UpdateDefaultRoyalty { royalty: Option<Royalty> },
+    /// Sets the collection metadata, can only be called by the contract minter
+    UpdateCollectionInfo { collection_info: CollectionInfo },
+    /// Hands the collection over to a new minter, can only be called by the contract minter
+    UpdateMinter { minter: String },
 }
 
 #[cw_serde]
@@ -31,6 +72,28 @@ pub enum QueryMsg {
         token_id: String,
         include_expired: Option<bool>,
     },
+    /// Return the approval of spender on the given token, error if there is none
+   #[returns(cw721::ApprovalResponse)]
+    Approval {
+        token_id: String,
+        spender: String,
+        include_expired: Option<bool>,
+    },
+    /// Return approvals that a token has
+   #[returns(cw721::ApprovalsResponse)]
+    Approvals {
+        token_id: String,
+        include_expired: Option<bool>,
+    },
+    /// List all operators that can access all of the owner's tokens
+   #[returns(cw721::OperatorsResponse)]
+    AllOperators {
+        owner: String,
+        /// unset or false will filter out expired items, you must set to true to see them
+        include_expired: Option<bool>,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
     /// Total number of tokens issued
    #[returns(cw721::NumTokensResponse)]
     NumTokens {},
@@ -71,9 +134,73 @@ pub enum QueryMsg {
     /// Return the minter
    #[returns(MinterResponse)]
     Minter {},
-    /// Extension query
-   #[returns(())]
+    /// Collection metadata and the tokens of its active positions
+   #[returns(CollectionInfoResponse)]
     CollectionInfo {},
+    /// Tokens whose position has the given status at the current block time
+   #[returns(cw721::TokensResponse)]
+    TokensByStatus {
+        status: StatusFilter,
+        start_after: Option<String>,
+        limit: Option<u32>,
+    },
+    /// Policy of transfers between holders
+   #[returns(TransferPolicy)]
+    TransferPolicy {},
+    /// CW2981 royalty to pay on a sale of the token at sale_price
+   #[returns(RoyaltiesInfoResponse)]
+    RoyaltyInfo {
+        token_id: String,
+        sale_price: Uint128,
+    },
+    /// CW2981 check that the contract implements royalties
+   #[returns(CheckRoyaltiesResponse)]
+    CheckRoyalties {},
+}
+
+/// Status of a position, whatever its lock or release time
+#[cw_serde]
+pub enum StatusFilter {
+    Bonded,
+    Locked,
+    Unbonding,
+    Claimable,
+    Claimed,
+}
+
+impl StatusFilter {
+    pub fn matches(&self, status: &Status) -> bool {
+        matches!((self, status),
+            (StatusFilter::Bonded, Status::Bonded)
+            | (StatusFilter::Locked, Status::Locked)
+            | (StatusFilter::Unbonding, Status::Unbonding { .. })
+            | (StatusFilter::Claimable, Status::Claimable)
+            | (StatusFilter::Claimed, Status::Claimed)
+        )
+    }
+}
+
+#[cw_serde]
+pub struct CollectionInfoResponse {
+    pub name: String,
+    pub symbol: String,
+    pub description: String,
+    pub image: Option<String>,
+    pub external_link: Option<String>,
+    /// Tokens bonded by the bonded and locked positions
+    pub total_bonded: Vec<Coin>,
+    pub active_positions: u64,
+}
+
+#[cw_serde]
+pub struct RoyaltiesInfoResponse {
+    pub address: String,
+    pub royalty_amount: Uint128,
+}
+
+#[cw_serde]
+pub struct CheckRoyaltiesResponse {
+    pub royalty_payments: bool,
 }
 
 /// Shows who can mint these tokens
@@ -100,6 +227,10 @@ impl From<ExecuteMsg> for Cw721ExecuteMsg<Metadata,Empty>
             ExecuteMsg::Burn { token_id } => Cw721ExecuteMsg::Burn { token_id },
             ExecuteMsg::TransferNft {recipient, token_id,} => Cw721ExecuteMsg::TransferNft {recipient, token_id,},
             ExecuteMsg::SendNft {contract, token_id, msg,} => Cw721ExecuteMsg::SendNft {contract,token_id, msg,},
+            ExecuteMsg::Approve { spender, token_id, expires } => Cw721ExecuteMsg::Approve { spender, token_id, expires },
+            ExecuteMsg::Revoke { spender, token_id } => Cw721ExecuteMsg::Revoke { spender, token_id },
+            ExecuteMsg::ApproveAll { operator, expires } => Cw721ExecuteMsg::ApproveAll { operator, expires },
+            ExecuteMsg::RevokeAll { operator } => Cw721ExecuteMsg::RevokeAll { operator },
             _ => unreachable!("Invalid ExecuteMsg"),
         }
     }
@@ -116,6 +247,9 @@ impl From<QueryMsg> for Cw721QueryMsg<Empty>                         //<<<<<<<<<
                 token_id,
                 include_expired,
             },
+            QueryMsg::Approval { token_id, spender, include_expired } => Cw721QueryMsg::Approval { token_id, spender, include_expired },
+            QueryMsg::Approvals { token_id, include_expired } => Cw721QueryMsg::Approvals { token_id, include_expired },
+            QueryMsg::AllOperators { owner, include_expired, start_after, limit } => Cw721QueryMsg::AllOperators { owner, include_expired, start_after, limit },
             QueryMsg::NumTokens {} => Cw721QueryMsg::NumTokens {},
             QueryMsg::ContractInfo {} => Cw721QueryMsg::ContractInfo {},
             QueryMsg::NftInfo { token_id } => Cw721QueryMsg::NftInfo { token_id },
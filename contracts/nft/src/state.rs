@@ -0,0 +1,51 @@
+use cosmwasm_schema::cw_serde;
+use cosmwasm_std::Coin;
+use cw_storage_plus::Item;
+
+/// Transfers of NFTs between holders. Transfers from or to the minter are always allowed
+#[cw_serde]
+#[derive(Default)]
+pub struct TransferPolicy {
+    /// Unbonding and claimable NFTs can not be transferred
+    pub block_unbonding: bool,
+    /// Locked NFTs can not be transferred before their lock expires
+    pub block_locked: bool,
+    /// NFTs can not be transferred at all
+    pub soulbound: bool,
+    /// Only these recipients can receive NFTs. Anyone if empty
+    pub allowed_recipients: Vec<String>,
+}
+
+pub const TRANSFER_POLICY: Item<TransferPolicy> = Item::new("transfer_policy");
+
+/// CW2981 royalty, in percent of the sale price
+#[cw_serde]
+pub struct Royalty {
+    pub payment_address: String,
+    pub percentage: u64,
+}
+
+// Royalty of tokens without their own
+pub const DEFAULT_ROYALTY: Item<Royalty> = Item::new("default_royalty");
+
+/// Collection level metadata, set by the minter
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionInfo {
+    pub description: String,
+    pub image: Option<String>,
+    pub external_link: Option<String>,
+}
+
+pub const COLLECTION_INFO: Item<CollectionInfo> = Item::new("collection_info");
+
+/// Tokens held by the active, bonded or locked, positions of the collection
+#[cw_serde]
+#[derive(Default)]
+pub struct CollectionTotals {
+    pub bonded: Vec<Coin>,
+    pub active_positions: u64,
+}
+
+// Kept on every mint, metadata update and burn
+pub const COLLECTION_TOTALS: Item<CollectionTotals> = Item::new("collection_totals");
@@ -0,0 +1,101 @@
+use cosmwasm_std::{to_vec, Binary, StdResult, Timestamp};
+use serde::Serialize;
+
+use crate::contract::{Metadata, Status};
+
+/// ERC721 metadata JSON, rendered by wallets and marketplaces
+#[derive(Serialize)]
+struct TokenJson {
+    name: String,
+    description: String,
+    image: String,
+    attributes: Vec<TokenAttribute>,
+}
+
+#[derive(Serialize)]
+struct TokenAttribute {
+    trait_type: String,
+    value: String,
+}
+
+// Data URI of the token JSON, with an SVG image of the position amounts, status and unlock date
+pub fn token_uri(collection: &str, token_id: &str, metadata: &Metadata) -> StdResult<String> {
+    let status = status_label(&metadata.status);
+    let unlock = unlock_time(metadata).map_or("-".to_string(), format_date);
+
+    let mut attributes: Vec<TokenAttribute> = metadata
+        .native
+        .iter()
+        .map(|coin| TokenAttribute { trait_type: coin.denom.clone(), value: coin.amount.to_string() })
+        .collect();
+    attributes.push(TokenAttribute { trait_type: "Status".to_string(), value: status.to_string() });
+    attributes.push(TokenAttribute { trait_type: "Unlock".to_string(), value: unlock.clone() });
+
+    let name = format!("{} #{}", collection, token_id);
+    let json = TokenJson {
+        image: format!("data:image/svg+xml;base64,{}", Binary::from(svg(&name, metadata, status, &unlock).into_bytes()).to_base64()),
+        description: format!("Staking position {} of {}", token_id, collection),
+        name,
+        attributes,
+    };
+    Ok(format!("data:application/json;base64,{}", Binary::from(to_vec(&json)?).to_base64()))
+}
+
+fn svg(name: &str, metadata: &Metadata, status: &str, unlock: &str) -> String {
+    let mut lines = vec![name.to_string()];
+    lines.extend(metadata.native.iter().map(|coin| format!("{} {}", coin.amount, coin.denom)));
+    lines.push(format!("Status: {}", status));
+    lines.push(format!("Unlock: {}", unlock));
+
+    let text: String = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| format!(r#"<text x="20" y="{}">{}</text>"#, 40 + 30 * i, escape_xml(line)))
+        .collect();
+    format!(
+        r##"<svg xmlns="http://www.w3.org/2000/svg" width="350" height="350" viewBox="0 0 350 350"><rect width="100%" height="100%" fill="#1b1f3b"/><g font-family="monospace" font-size="16" fill="#ffffff">{}</g></svg>"##,
+        text
+    )
+}
+
+fn status_label(status: &Status) -> &'static str {
+    match status {
+        Status::Bonded => "Bonded",
+        Status::Locked => "Locked",
+        Status::Unbonding { .. } => "Unbonding",
+        Status::Claimable => "Claimable",
+        Status::Claimed => "Claimed",
+    }
+}
+
+// Locked positions unlock with their lock and unbonding ones on release
+fn unlock_time(metadata: &Metadata) -> Option<Timestamp> {
+    match metadata.status {
+        Status::Locked => metadata.lock_until,
+        Status::Unbonding { release_at } => Some(release_at),
+        _ => None,
+    }
+}
+
+// UTC date as YYYY-MM-DD HH:MM, from the days since 1970-01-01 civil calendar conversion
+fn format_date(time: Timestamp) -> String {
+    let seconds = time.seconds();
+    let days = (seconds / 86_400) as i64;
+    let minutes = (seconds % 86_400) / 60;
+
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z.rem_euclid(146_097);
+    let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02} UTC", year, month, day, minutes / 60, minutes % 60)
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -1,12 +1,13 @@
 use cosmwasm_schema::write_api;
 
-use::staking::msg::{ExecuteMsg,QueryMsg,InstantiateMsg};
+use::staking::msg::{ExecuteMsg,QueryMsg,InstantiateMsg,MigrateMsg};
 
 fn main(){
     write_api!{
         instantiate: InstantiateMsg,
         execute: ExecuteMsg,
         query: QueryMsg,
+        migrate: MigrateMsg,
     }
     
 }
\ No newline at end of file
@@ -1,20 +1,31 @@
 use std::{vec};
+use std::collections::BTreeMap;
 
 #[cfg(not(feature="library"))]
 use cosmwasm_std::entry_point;
-use cosmwasm_std::{coin, to_binary, Addr, BankMsg,Binary, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response, StakingMsg, StdResult, Uint128,Uint64,Order,Coin, DistributionMsg, CosmosMsg};
+use cosmwasm_std::{coin, to_binary, Addr, BankMsg,Binary, Decimal, Deps, DepsMut, Env, MessageInfo, QuerierWrapper, Response, StakingMsg, StdError, StdResult, Storage, Uint128,Uint64,Order,Coin, DistributionMsg, CosmosMsg, Timestamp, WasmMsg};
 
-use cw2::set_contract_version;
-use cw_utils::{one_coin, PaymentError, Duration};
+use cw2::{get_contract_version, set_contract_version};
+use cw_storage_plus::{Bound, PrefixBound};
+use cw_utils::{one_coin, PaymentError, Duration, Expiration};
 use crate::error::ContractError;
-use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg};
-use crate::state::{BONDED, UNBONDING, TOTAL_BONDED, TOTAL_CLAIMED, NFT_BONDED, AGENT, MANAGER, CLAIMS, State, NUMBER_VALIDATORS, ValidatorInfo, TREASURY, NFT_VAL_UNBONDING};
+use crate::msg::{ExecuteMsg, InstantiateMsg, QueryMsg, MigrateMsg, NftClaimsResponse, Release, UpcomingReleasesResponse, AllocationResponse, ValidatorAllocation, AdapterExecuteMsg, DenomInfo, DenomsResponse, ClaimCursor, DenomClaimsResponse, LiquidClaimCursor, LiquidClaimsResponse, LiquidPoolResponse};
+use cw20::{AllAccountsResponse, AllAllowancesResponse, AllowanceInfo, AllowanceResponse, BalanceResponse, Cw20ReceiveMsg, TokenInfoResponse};
+use crate::state::{BONDED, UNBONDING, TOTAL_BONDED, TOTAL_CLAIMED, NFT_BONDED, AGENT, MANAGER, State, NUMBER_VALIDATORS, ValidatorInfo, TREASURY, NFT_VAL_UNBONDING, LEGACY_CLAIMS, NftClaim, REWARD_INDEX, NFT_REWARDS, NftRewards, DENOMS, DENOM_BONDED, NFT_DENOM_BONDED, DENOM_CLAIMS, DenomClaim, DenomAdapter, LIQUID_POOL, LIQUID_SUPPLY, LIQUID_BALANCES, LIQUID_ALLOWANCES, LiquidClaim};
 
 
 // version info for migration info
 const CONTRACT_NAME: &str = "crates.io:cw-staking-angel";
 const CONTRACT_VERSION: &str = env!("CARGO_PKG_VERSION");
 
+// Pagination
+const DEFAULT_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 30;
+
+// Liquid staking share token
+const LIQUID_NAME: &str = "Angel liquid staking";
+const LIQUID_SYMBOL: &str = "ALS";
+const LIQUID_DECIMALS: u8 = 6;
 
 #[cfg_attr(not(feature = "library"), entry_point)]
 pub fn instantiate(
@@ -54,102 +65,150 @@ pub fn execute(
         ExecuteMsg::Bond {nft_id} => execute_bond(deps, env, info, nft_id),
         ExecuteMsg::Unbond { nft_id, amount } => execute_unbond(deps, env, info, nft_id, amount),
         ExecuteMsg::Claim {nft_id, sender, amount} => execute_claim(deps, env, info, nft_id, sender,amount),
+        ExecuteMsg::UnbondDenom { nft_id, denom, amount } => execute_unbond_denom(deps, env, info, nft_id, denom, amount),
+        ExecuteMsg::ClaimDenom { nft_id, sender, denom, amount } => execute_claim_denom(deps, env, info, nft_id, sender, denom, amount),
+        ExecuteMsg::Merge { nft_id, nft_ids } => execute_merge(deps, env, info, nft_id, nft_ids),
+        ExecuteMsg::Split { nft_id, new_nft_ids, amounts } => execute_split(deps, env, info, nft_id, new_nft_ids, amounts),
+        ExecuteMsg::ClaimRewards { nft_id, recipient } => execute_claim_rewards(deps, env, info, nft_id, recipient),
         ExecuteMsg::AddValidator { address, bond_denom, unbonding_period } => execute_add_validator (deps, env, info, address, bond_denom, unbonding_period),
         ExecuteMsg::RemoveValidator { address } => execute_remove_validator (deps, env, info, address, ),
+        ExecuteMsg::RegisterDenom { denom, adapter } => execute_register_denom(deps, env, info, denom, adapter),
+        ExecuteMsg::RemoveDenom { denom } => execute_remove_denom(deps, env, info, denom),
         ExecuteMsg::BondCheck {} => execute_bond_check(deps.as_ref(), env, info),
         ExecuteMsg::CollectAngelRewards {  } => execute_collect_rewards(deps, env, info),
+        ExecuteMsg::BondLiquid {  } => execute_bond_liquid(deps, env, info),
+        ExecuteMsg::UnbondLiquid { amount } => execute_unbond_liquid(deps, env, info, amount),
+        ExecuteMsg::ClaimLiquid {  } => execute_claim_liquid(deps, env, info),
+        ExecuteMsg::Transfer { recipient, amount } => execute_transfer(deps, env, info, recipient, amount),
+        ExecuteMsg::Send { contract, amount, msg } => execute_send(deps, env, info, contract, amount, msg),
+        ExecuteMsg::Burn { amount } => execute_burn(deps, env, info, amount),
+        ExecuteMsg::IncreaseAllowance { spender, amount, expires } => execute_increase_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::DecreaseAllowance { spender, amount, expires } => execute_decrease_allowance(deps, env, info, spender, amount, expires),
+        ExecuteMsg::TransferFrom { owner, recipient, amount } => execute_transfer_from(deps, env, info, owner, recipient, amount),
+        ExecuteMsg::SendFrom { owner, contract, amount, msg } => execute_send_from(deps, env, info, owner, contract, amount, msg),
+        ExecuteMsg::BurnFrom { owner, amount } => execute_burn_from(deps, env, info, owner, amount),
+        ExecuteMsg::UpdateAgent { agent } => execute_update_agent(deps, info, agent),
     }
 }
 
-pub fn execute_bond(deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Uint128) -> Result<Response, ContractError> {
+pub fn execute_bond(mut deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128) -> Result<Response, ContractError> {
     let agent = AGENT.load(deps.storage)?;
     if info.sender != agent {
         return Err(ContractError::Unauthorized {});
     }
     // Making sure there is only one coin and handling the possible errors.
-    let d_coins = match one_coin(&info) {
-        Ok(coin) => coin,
-        Err(err) => {
-            match err {
-                PaymentError::NoFunds{} => {return Err(ContractError::NoFunds {  });}
-                PaymentError::MultipleDenoms{} => {return Err(ContractError::MultipleDenoms {  });}
-                _ => {return Err(ContractError::InvalidCoin {  });}
-            }
-        },
-    };
+    let d_coins = one_coin(&info).map_err(payment_error)?;
 
     let can_be_bonded_denom = deps.querier.query_bonded_denom()?;
     if d_coins.denom != can_be_bonded_denom {
-        return Err(ContractError::InvalidCoin {  });       
+        return bond_registered_denom(deps, nft_id, d_coins);
     }
     let amount = d_coins.amount;
 
 
     let validator_address = chosen_validator(deps.as_ref(), None)?;
 
+    // Rewards so far belong to the tokens bonded before this bond
+    let (reward_msgs, rewards) = accrue_rewards(deps.branch(), &env)?;
+
+    add_bonded(deps.storage, &validator_address, &nft_id.to_string(), amount)?;
 
+    let res = Response::new()
+        .add_messages(reward_msgs)
+        .add_message(StakingMsg::Delegate {
+            validator: validator_address.to_string(),
+            amount: d_coins,
+        })
+        .add_attribute("action", "bond")
+        .add_attribute("rewards", rewards)
+        .add_attribute("from", nft_id)
+        .add_attribute("bonded", amount)
+        .add_attribute("validator", validator_address);
+    Ok(res)
+}
+
+
+// Making sure there is only one coin and handling the possible errors.
+fn payment_error(err: PaymentError) -> ContractError {
+    match err {
+        PaymentError::NoFunds{} => ContractError::NoFunds {  },
+        PaymentError::MultipleDenoms{} => ContractError::MultipleDenoms {  },
+        _ => ContractError::InvalidCoin {  },
+    }
+}
+
+// Adds amount to the tokens bonded on the validator and by key, a nft_id or LIQUID_POOL. Rewards must be accrued before
+fn add_bonded(storage: &mut dyn Storage, validator_address: &str, key: &str, amount: Uint128) -> Result<(), ContractError> {
     //Update bonded tokens to validator
     let state = State::new();
-    let mut validator_info = state.validator.load(deps.storage, &validator_address)?;
+    let mut validator_info = state.validator.load(storage, validator_address)?;
     validator_info.bonded = validator_info.bonded.checked_add(amount.u128()).unwrap();  
-    state.validator.save(deps.storage, &validator_address, &validator_info)?;
+    state.validator.save(storage, validator_address, &validator_info)?;
 
-    let key = nft_id.to_string();
-    let mut nft_amount_bonded = NFT_BONDED.may_load(deps.storage, &key)?.unwrap_or_default();
+    settle_nft_rewards(storage, key)?;
+    let mut nft_amount_bonded = NFT_BONDED.may_load(storage, key)?.unwrap_or_default();
     nft_amount_bonded = nft_amount_bonded.checked_add(amount).unwrap();  
-    NFT_BONDED.save(deps.storage, &key, &nft_amount_bonded)?;
+    NFT_BONDED.save(storage, key, &nft_amount_bonded)?;
 
-    BONDED.update(deps.storage, |total| -> StdResult<_> {
+    BONDED.update(storage, |total| -> StdResult<_> {
             Ok(total.checked_add(amount)?)
     })?;
 
-    TOTAL_BONDED.update(deps.storage, |total| -> StdResult<_> {
+    TOTAL_BONDED.update(storage, |total| -> StdResult<_> {
         Ok(total.checked_add(amount)?)
     })?;
+    Ok(())
+}
 
-    let res = Response::new()
-        .add_message(StakingMsg::Delegate {
-            validator: validator_address.to_string(),
-            amount: d_coins,
-        })
+// Tokens of a registered denom are bonded by the NFT apart from the pooled delegations. They earn no rewards here
+fn bond_registered_denom(deps: DepsMut, nft_id: Uint128, d_coin: Coin) -> Result<Response, ContractError> {
+    let adapter = DENOMS.may_load(deps.storage, &d_coin.denom)?.ok_or(ContractError::InvalidCoin {  })?;
+    let key = nft_id.to_string();
+    NFT_DENOM_BONDED.update(deps.storage, (&key, &d_coin.denom), |bonded| -> StdResult<_> {
+        Ok(bonded.unwrap_or_default().checked_add(d_coin.amount)?)
+    })?;
+    DENOM_BONDED.update(deps.storage, &d_coin.denom, |bonded| -> StdResult<_> {
+        Ok(bonded.unwrap_or_default().checked_add(d_coin.amount)?)
+    })?;
+
+    let mut res = Response::new();
+    if let DenomAdapter::Contract { address, .. } = adapter {
+        res = res.add_message(WasmMsg::Execute { contract_addr: address, msg: to_binary(&AdapterExecuteMsg::Deposit {  })?, funds: vec![d_coin.clone()] });
+    }
+    Ok(res
         .add_attribute("action", "bond")
         .add_attribute("from", nft_id)
-        .add_attribute("bonded", amount)
-        .add_attribute("validator", validator_address);
-    Ok(res)
+        .add_attribute("bonded", d_coin.amount)
+        .add_attribute("denom", d_coin.denom))
 }
 
-
 // Returns validator with the least amount of tokens bonded
 // excluded address can not be returned 
 pub fn chosen_validator (deps: Deps, excluded_address: Option<String>) -> Result<String, ContractError>  {
     let state = State::new();
     // let validator_result : StdResult<Vec<_>>;
-    let validator_result: (String, ValidatorInfo) ;
-    if excluded_address.is_none() {
+    let validator_result: (String, ValidatorInfo) = match excluded_address {
         // validator_result = state.validator.idx.bonded
         // .range(deps.storage,None,None,Order::Ascending)
         // .take(1)
         // .collect();
-         validator_result = state.validator.idx.bonded
+        None => state.validator.idx.bonded
         .range(deps.storage,None,None,Order::Descending)
         .last()
         .unwrap()
-        .unwrap();       
-    } else {
-        let excluded_address = excluded_address.unwrap();
+        .unwrap(),
         // validator_result = state.validator.idx.bonded
         // .range(deps.storage,None,None,Order::Ascending)
         // .filter(|item| item.as_ref().unwrap().0 != excluded_address)
         // .take(1)
         // .collect();
-        validator_result = state.validator.idx.bonded
+        Some(excluded_address) => state.validator.idx.bonded
         .range(deps.storage,None,None,Order::Descending)
         .filter(|item| item.as_ref().unwrap().0 != excluded_address)
         .last()
         .unwrap()
-        .unwrap();
-    }
+        .unwrap(),
+    };
 
         //let vec_validator_address = validator_result?;
         // let validator_address = &vec_validator_address[0].0;    
@@ -158,7 +217,7 @@ pub fn chosen_validator (deps: Deps, excluded_address: Option<String>) -> Result
 }
 
 
-pub fn execute_unbond(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128, amount: Uint128) -> Result<Response, ContractError> {
+pub fn execute_unbond(mut deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128, amount: Uint128) -> Result<Response, ContractError> {
     let agent = AGENT.load(deps.storage)?;
     if info.sender != agent {
         return Err(ContractError::Unauthorized {});
@@ -173,6 +232,9 @@ pub fn execute_unbond(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint12
     if nft_amount_bonded != amount {
         return Err(ContractError::RequestUnbondAmountMismatch { nft_id: key.to_string(), requested: amount.to_string(), balance: nft_amount_bonded.to_string() });
     }
+    // Rewards so far belong to the tokens bonded before this unbond. The NFT keeps its pending rewards
+    let (reward_msgs, rewards) = accrue_rewards(deps.branch(), &env)?;
+    settle_nft_rewards(deps.storage, &key)?;
     NFT_BONDED.remove(deps.storage, &key);
 
     // Returns the denomination that can be bonded (if there are multiple native tokens on the chain)
@@ -191,36 +253,26 @@ pub fn execute_unbond(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint12
     .collect();
 
     let state = State::new();
-    for i in 0..vec_address_coin.len() {
+    for (val_address, val_coin) in vec_address_coin.iter() {
         // Remove from the validator info the required amount
-        let val_address =&vec_address_coin[i].0;
-        let val_amount = vec_address_coin[i].1.amount;
+        let val_amount = val_coin.amount;
         let mut validator_info = state.validator.load(deps.storage, val_address)?;
         validator_info.bonded = validator_info.bonded.checked_sub(val_amount.u128()).unwrap();
         validator_info.unbonding = validator_info.unbonding.checked_add(val_amount.u128()).unwrap();
-        state.validator.save(deps.storage,&val_address,&validator_info)?;
+        state.validator.save(deps.storage,val_address,&validator_info)?;
 
-        if NFT_VAL_UNBONDING.has(deps.storage, (&key,val_address)) {
+        if state.claims.has(deps.storage, (&key,val_address)) {
             return Err(ContractError::NFTAlreadyUnbonding { nft_id: key, val_addr: val_address.to_string() })
         }
-        NFT_VAL_UNBONDING.save(deps.storage, (&key,val_address), &val_amount)?;
-
-        CLAIMS.create_claim(
-            deps.storage,
-            &Addr::unchecked(nft_id.to_string()),
-            val_amount,
-            validator_info.unbonding_period.after(&env.block),  
-        )?;
+        let claim = NftClaim {
+            nft_id: key.clone(),
+            validator: val_address.clone(),
+            amount: val_amount,
+            release_at: release_time(&env, &validator_info.unbonding_period)?,
+        };
+        state.claims.save(deps.storage, (&key,val_address), &claim)?;
     }
 
-    // If all validators had the same unbonding_period..... One single entry to CLAIMS could be done
-    // CLAIMS.create_claim(
-    //     deps.storage,
-    //     &Addr::unchecked(nft_id.to_string()),
-    //     amount,
-    //     unbonding_period.after(&env.block),  
-    // )?;
-
     BONDED.update(deps.storage, |total| -> StdResult<_> {
         Ok(total.checked_sub(amount)?)
     })?;   
@@ -230,8 +282,10 @@ pub fn execute_unbond(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint12
     })?; 
 
     let res = Response::new()
+        .add_messages(reward_msgs)
         .add_messages(msgs)
         .add_attribute("action", "unbond")
+        .add_attribute("rewards", rewards)
         .add_attribute("from", nft_id)
         .add_attribute("unbonded", amount);
     Ok(res)
@@ -278,7 +332,7 @@ pub fn chosen_validators_unstake (deps: Deps, amount:Uint128, denom:String, numb
 
         let vec_all_validators = all_validators?;
 
-        let mut remaining_amount = amount.clone();
+        let mut remaining_amount = amount;
         let total_number_validators = vec_all_validators.len();
         let total_number_validators_u64 = total_number_validators as u64;
         let mut i = 0;
@@ -288,7 +342,7 @@ pub fn chosen_validators_unstake (deps: Deps, amount:Uint128, denom:String, numb
 
             if i > total_number_validators - 1 {
                 return Err(ContractError::UnableUnstakeAmount {
-                    amount: amount, number_validators: Uint64::from(total_number_validators_u64)
+                    amount, number_validators: Uint64::from(total_number_validators_u64)
                 });
             }
     
@@ -304,7 +358,7 @@ pub fn chosen_validators_unstake (deps: Deps, amount:Uint128, denom:String, numb
             
             if remaining_amount > vec_all_validators[i].1.amount {
                 vec_planb_validator.push((address.to_string(),coin(*validator_amount, denom)));
-                remaining_amount = remaining_amount - vec_all_validators[i].1.amount;
+                remaining_amount -= vec_all_validators[i].1.amount;
                 i +=1;
             } else {
                 vec_planb_validator.push((address.to_string(),coin(remaining_amount.u128(), denom)));
@@ -326,7 +380,7 @@ pub fn chosen_validators_unstake (deps: Deps, amount:Uint128, denom:String, numb
     // Confirm the vector takes into account exactly the amount required
     if sum != amount.u128() {
         return Err(ContractError::UnableUnstakeAmount {
-            amount: amount, number_validators: Uint64::from(number_validators)
+            amount, number_validators: Uint64::from(number_validators)
         });
     }
 
@@ -339,7 +393,7 @@ pub fn chosen_validators_unstake (deps: Deps, amount:Uint128, denom:String, numb
 pub fn calc_validator_number(number_validators: Uint64, _amount: Uint128) -> StdResult<u64> {
     // Possible number of validators to split the bond is defined by the next vector. 
     // Powers of two, five or product of both to avoid repeating decimals on the amount to split between validators
-    let v = vec![1, 2, 4, 5, 8, 10];  // 16, 20, 25, 32, 40, 50, 64, 80, 100
+    let v = [1, 2, 4, 5, 8, 10];  // 16, 20, 25, 32, 40, 50, 64, 80, 100
     let mut i = v.len();
     while i>1 {
         // At least one token to unbond per validator
@@ -351,6 +405,14 @@ pub fn calc_validator_number(number_validators: Uint64, _amount: Uint128) -> Std
     Ok(1)
 }
 
+// Unbonded tokens are released by the native staking module after a time based unbonding period
+pub fn release_time(env: &Env, unbonding_period: &Duration) -> Result<Timestamp, ContractError> {
+    match unbonding_period {
+        Duration::Time(seconds) => Ok(env.block.time.plus_seconds(*seconds)),
+        Duration::Height(_) => Err(ContractError::InvalidExpiration {}),
+    }
+}
+
 pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128, sender: String, amount: Uint128) -> Result<Response, ContractError> {
     let agent = AGENT.load(deps.storage)?;
     if info.sender != agent {
@@ -359,8 +421,9 @@ pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128
     let sender = deps.api.addr_validate(&sender)?;
     let can_be_bonded_denom = deps.querier.query_bonded_denom()?;
 
-    //let test_query_claim = CLAIMS.query_claims(deps.as_ref(), &Addr::unchecked(nft_id))?;
-    let to_send = CLAIMS.claim_tokens(deps.storage, &Addr::unchecked(nft_id), &env.block, None)?;
+    let key = nft_id.to_string();
+    let mature_claims = mature_claims(deps.storage, &env, &key)?;
+    let to_send : Uint128 = mature_claims.iter().map(|claim| claim.amount).sum();
 
     if to_send == Uint128::zero() {
         return Err(ContractError::NothingToClaim {});
@@ -368,7 +431,7 @@ pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128
 
     // Must make sure that the tokens to be claimed by that nft_id have matured. 
     // This will avoid possible issue with validators with different unbonding periods, with claims maturing time differently
-    if to_send != Uint128::from(amount) {
+    if to_send != amount {
         return Err(ContractError::RequestUnbondAmountMismatch { nft_id: nft_id.to_string(), requested: amount.to_string(), balance: to_send.to_string() });
     }
 
@@ -380,29 +443,7 @@ pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128
     if balance.amount < to_send {
         return Err(ContractError::BalanceTooSmall {});
     }
-
-    UNBONDING.update(deps.storage, |total| -> StdResult<_> {
-        Ok(total.checked_sub(amount)?)
-    })?;
-    
-    TOTAL_CLAIMED.update(deps.storage, |total| -> StdResult<_> {
-        Ok(total.checked_add(to_send)?)
-    })?;
-    
-
-    // NFT_VAL_UNBONDING information let the contract update the unbonding validator info. 
-    let res : StdResult<Vec<_>> = NFT_VAL_UNBONDING
-    .prefix(nft_id.to_string().as_str())
-    .range(deps.storage, None, None, Order::Ascending)
-    .collect();
-    let vec_val_unbonding = res?;
-
-    let state = State::new();
-    for (val_address, unbonding) in vec_val_unbonding {
-        let mut validator_info = state.validator.load(deps.storage, &val_address)?;
-        validator_info.unbonding = validator_info.unbonding.checked_sub(unbonding.u128()).unwrap();
-        state.validator.save(deps.storage,&val_address,&validator_info)?;
-    }   
+    release_claims(deps.storage, &key, mature_claims)?;
 
     // transfer tokens to the sender
     balance.amount = to_send;
@@ -418,6 +459,224 @@ pub fn execute_claim(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128
     Ok(res)
 }
 
+// Bond denom claims of the nft_id, released by the current block time
+fn mature_claims(storage: &dyn Storage, env: &Env, key: &str) -> StdResult<Vec<NftClaim>> {
+    let state = State::new();
+    let res : StdResult<Vec<NftClaim>> = state.claims
+    .prefix(key)
+    .range(storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(_, claim)| claim))
+    .collect();
+    Ok(res?
+    .into_iter()
+    .filter(|claim| claim.release_at <= env.block.time)
+    .collect())
+}
+
+// Removes the claims being paid out. Returns the tokens to send
+fn release_claims(storage: &mut dyn Storage, key: &str, claims: Vec<NftClaim>) -> StdResult<Uint128> {
+    let state = State::new();
+    for claim in claims.iter() {
+        state.claims.remove(storage, (key, &claim.validator))?;
+    }
+    release_unbonding(storage, claims.iter().map(|claim| (claim.validator.as_str(), claim.amount)).collect())
+}
+
+// Moves the released (validator, amount) pairs out of the unbonding totals. Returns the tokens to send
+fn release_unbonding(storage: &mut dyn Storage, released: Vec<(&str, Uint128)>) -> StdResult<Uint128> {
+    let to_send : Uint128 = released.iter().map(|(_, amount)| *amount).sum();
+    UNBONDING.update(storage, |total| -> StdResult<_> {
+        Ok(total.checked_sub(to_send)?)
+    })?;
+    
+    TOTAL_CLAIMED.update(storage, |total| -> StdResult<_> {
+        Ok(total.checked_add(to_send)?)
+    })?;
+
+    // Each claim holds its source validator, which lets the contract update the unbonding validator info. 
+    let state = State::new();
+    for (validator, amount) in released {
+        let mut validator_info = state.validator.load(storage, validator)?;
+        validator_info.unbonding = validator_info.unbonding.checked_sub(amount.u128()).unwrap();
+        state.validator.save(storage, validator, &validator_info)?;
+    }
+    Ok(to_send)
+}
+
+pub fn execute_unbond_denom(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128, denom: String, amount: Uint128) -> Result<Response, ContractError> {
+    let agent = AGENT.load(deps.storage)?;
+    if info.sender != agent {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // Must unbond the total amount of the denom held by the NFT
+    let key = nft_id.to_string();
+    let nft_amount_bonded = NFT_DENOM_BONDED.may_load(deps.storage, (&key, &denom))?
+        .ok_or(ContractError::NFTNotRegistered { nft_id: key.clone() })?;
+    if nft_amount_bonded != amount {
+        return Err(ContractError::RequestUnbondAmountMismatch { nft_id: key, requested: amount.to_string(), balance: nft_amount_bonded.to_string() });
+    }
+    if DENOM_CLAIMS.has(deps.storage, (&key, &denom)) {
+        return Err(ContractError::NFTAlreadyUnbonding { nft_id: key, val_addr: denom })
+    }
+    // A denom can not be removed while its tokens are bonded
+    let adapter = DENOMS.load(deps.storage, &denom)?;
+    NFT_DENOM_BONDED.remove(deps.storage, (&key, &denom));
+    DENOM_BONDED.update(deps.storage, &denom, |bonded| -> StdResult<_> {
+        Ok(bonded.unwrap_or_default().checked_sub(amount)?)
+    })?;
+
+    let claim = DenomClaim {
+        nft_id: key.clone(),
+        denom: denom.clone(),
+        amount,
+        release_at: release_time(&env, adapter.unbonding_period())?,
+    };
+    DENOM_CLAIMS.save(deps.storage, (&key, &denom), &claim)?;
+
+    let mut res = Response::new();
+    if let DenomAdapter::Contract { address, .. } = adapter {
+        let withdraw_msg = AdapterExecuteMsg::Withdraw { amount: coin(amount.u128(), &denom) };
+        res = res.add_message(WasmMsg::Execute { contract_addr: address, msg: to_binary(&withdraw_msg)?, funds: vec![] });
+    }
+    Ok(res
+        .add_attribute("action", "unbond")
+        .add_attribute("from", nft_id)
+        .add_attribute("unbonded", amount)
+        .add_attribute("denom", denom))
+}
+
+pub fn execute_claim_denom(deps: DepsMut, env: Env, info: MessageInfo, nft_id: Uint128, sender: String, denom: String, amount: Uint128) -> Result<Response, ContractError> {
+    let agent = AGENT.load(deps.storage)?;
+    if info.sender != agent {
+        return Err(ContractError::Unauthorized {});
+    }
+    let sender = deps.api.addr_validate(&sender)?;
+
+    let key = nft_id.to_string();
+    let claim = DENOM_CLAIMS.may_load(deps.storage, (&key, &denom))?
+        .filter(|claim| claim.release_at <= env.block.time)
+        .ok_or(ContractError::NothingToClaim {})?;
+    if claim.amount != amount {
+        return Err(ContractError::RequestUnbondAmountMismatch { nft_id: key, requested: amount.to_string(), balance: claim.amount.to_string() });
+    }
+
+    // Withdrawn tokens must be back in the contract balance
+    let balance = deps.querier.query_balance(&env.contract.address, &denom)?;
+    if balance.amount < amount {
+        return Err(ContractError::BalanceTooSmall {});
+    }
+    DENOM_CLAIMS.remove(deps.storage, (&key, &denom));
+
+    Ok(Response::new()
+        .add_message(BankMsg::Send { to_address: sender.to_string(), amount: vec![coin(amount.u128(), &denom)] })
+        .add_attribute("action", "claim")
+        .add_attribute("from", sender)
+        .add_attribute("nft_id", nft_id.to_string())
+        .add_attribute("amount", amount)
+        .add_attribute("denom", denom))
+}
+
+// Tokens of registered denoms bonded by the NFT
+fn nft_denom_bonded(storage: &dyn Storage, nft_id: &str) -> StdResult<Vec<(String, Uint128)>> {
+    NFT_DENOM_BONDED
+        .prefix(nft_id)
+        .range(storage, None, None, Order::Ascending)
+        .collect()
+}
+
+fn is_registered_nft(storage: &dyn Storage, nft_id: &str) -> StdResult<bool> {
+    Ok(NFT_BONDED.has(storage, nft_id) || !nft_denom_bonded(storage, nft_id)?.is_empty())
+}
+
+// Bonded tokens are pooled on the validators, so moving them between NFTs only updates NFT_BONDED.
+// Tokens of registered denoms move along
+pub fn execute_merge(deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Uint128, nft_ids: Vec<Uint128>) -> Result<Response, ContractError> {
+    let agent = AGENT.load(deps.storage)?;
+    if info.sender != agent {
+        return Err(ContractError::Unauthorized {});
+    }
+    let key = nft_id.to_string();
+    if !is_registered_nft(deps.storage, &key)? {
+        return Err(ContractError::NFTNotRegistered { nft_id: key })
+    }
+    let mut nft_amount_bonded = NFT_BONDED.may_load(deps.storage, &key)?.unwrap_or_default();
+
+    let mut nft_rewards = settle_nft_rewards(deps.storage, &key)?;
+    let mut merged_keys : Vec<String> = vec![];
+    for merged_nft_id in nft_ids.iter() {
+        let merged_key = merged_nft_id.to_string();
+        if merged_key == key || merged_keys.contains(&merged_key) {
+            return Err(ContractError::NFTAlreadyRegistered { nft_id: merged_key })
+        }
+        if !is_registered_nft(deps.storage, &merged_key)? {
+            return Err(ContractError::NFTNotRegistered { nft_id: merged_key })
+        }
+        let merged_amount = NFT_BONDED.may_load(deps.storage, &merged_key)?.unwrap_or_default();
+        nft_amount_bonded = nft_amount_bonded.checked_add(merged_amount).map_err(StdError::from)?;
+        merged_keys.push(merged_key);
+    }
+    // Pending rewards of the merged NFTs move with their tokens
+    for merged_key in merged_keys.iter() {
+        let merged_rewards = settle_nft_rewards(deps.storage, merged_key)?;
+        nft_rewards.pending += merged_rewards.pending;
+        NFT_REWARDS.remove(deps.storage, merged_key);
+        NFT_BONDED.remove(deps.storage, merged_key);
+        for (denom, amount) in nft_denom_bonded(deps.storage, merged_key)? {
+            NFT_DENOM_BONDED.update(deps.storage, (&key, &denom), |bonded| -> StdResult<_> {
+                Ok(bonded.unwrap_or_default().checked_add(amount)?)
+            })?;
+            NFT_DENOM_BONDED.remove(deps.storage, (merged_key, &denom));
+        }
+    }
+    NFT_REWARDS.save(deps.storage, &key, &nft_rewards)?;
+    if !nft_amount_bonded.is_zero() {
+        NFT_BONDED.save(deps.storage, &key, &nft_amount_bonded)?;
+    }
+
+    Ok(Response::new()
+        .add_attribute("action", "merge")
+        .add_attribute("into", nft_id)
+        .add_attribute("merged", nft_ids.len().to_string())
+        .add_attribute("bonded", nft_amount_bonded))
+}
+
+pub fn execute_split(deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Uint128, new_nft_ids: Vec<Uint128>, amounts: Vec<Uint128>) -> Result<Response, ContractError> {
+    let agent = AGENT.load(deps.storage)?;
+    if info.sender != agent {
+        return Err(ContractError::Unauthorized {});
+    }
+    let key = nft_id.to_string();
+    let bonded = NFT_BONDED.may_load(deps.storage, &key)?
+        .ok_or(ContractError::NFTNotRegistered { nft_id: key.clone() })?;
+
+    let split_amount = amounts.iter().try_fold(Uint128::zero(), |sum, amount| sum.checked_add(*amount))
+        .map_err(|_| ContractError::InvalidSplit { nft_id: key.clone(), bonded })?;
+    if new_nft_ids.is_empty() || new_nft_ids.len() != amounts.len() || amounts.iter().any(|amount| amount.is_zero()) || split_amount >= bonded {
+        return Err(ContractError::InvalidSplit { nft_id: key, bonded })
+    }
+
+    for (i, new_nft_id) in new_nft_ids.iter().enumerate() {
+        let new_key = new_nft_id.to_string();
+        if NFT_BONDED.has(deps.storage, &new_key) || new_nft_ids[..i].contains(new_nft_id) {
+            return Err(ContractError::NFTAlreadyRegistered { nft_id: new_key })
+        }
+    }
+    // Pending rewards stay on the split NFT. New NFTs accrue from now on
+    settle_nft_rewards(deps.storage, &key)?;
+    for (new_nft_id, amount) in new_nft_ids.iter().zip(amounts.iter()) {
+        settle_nft_rewards(deps.storage, &new_nft_id.to_string())?;
+        NFT_BONDED.save(deps.storage, &new_nft_id.to_string(), amount)?;
+    }
+    NFT_BONDED.save(deps.storage, &key, &(bonded - split_amount))?;
+
+    Ok(Response::new()
+        .add_attribute("action", "split")
+        .add_attribute("from", nft_id)
+        .add_attribute("split", split_amount)
+        .add_attribute("bonded", bonded - split_amount))
+}
+
 pub fn execute_add_validator(deps: DepsMut, _env: Env, info: MessageInfo, validator_address: String, bond_denom: String, unbonding_period: Duration) -> Result<Response, ContractError> {
     let manager = MANAGER.load(deps.storage)?;
 
@@ -448,6 +707,11 @@ pub fn execute_add_validator(deps: DepsMut, _env: Env, info: MessageInfo, valida
         });
     }
 
+    // Claims are released on time. A block height unbonding period can not be tracked
+    if let Duration::Height(_) = unbonding_period {
+        return Err(ContractError::InvalidExpiration {});
+    }
+
     let validator_info = ValidatorInfo{ 
         bond_denom, 
         unbonding_period,
@@ -466,8 +730,65 @@ pub fn execute_add_validator(deps: DepsMut, _env: Env, info: MessageInfo, valida
     .add_attribute("validator_address", validator_address))
 }
 
+pub fn execute_register_denom(deps: DepsMut, _env: Env, info: MessageInfo, denom: String, adapter: DenomAdapter) -> Result<Response, ContractError> {
+    let manager = MANAGER.load(deps.storage)?;
+    if info.sender != manager {
+        return Err(ContractError::Unauthorized {});
+    }
+    // The bond denom is delegated to the validators
+    if denom == deps.querier.query_bonded_denom()? || DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::DenomAlreadyRegistered { denom })
+    }
+    // Claims are released on time. A block height unbonding period can not be tracked
+    if let Duration::Height(_) = adapter.unbonding_period() {
+        return Err(ContractError::InvalidExpiration {});
+    }
+    let adapter = match adapter {
+        DenomAdapter::Contract { address, unbonding_period } => DenomAdapter::Contract { address: deps.api.addr_validate(&address)?.into(), unbonding_period },
+        adapter => adapter,
+    };
+    DENOMS.save(deps.storage, &denom, &adapter)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "register_denom")
+        .add_attribute("denom", denom))
+}
+
+pub fn execute_remove_denom(deps: DepsMut, _env: Env, info: MessageInfo, denom: String) -> Result<Response, ContractError> {
+    let manager = MANAGER.load(deps.storage)?;
+    if info.sender != manager {
+        return Err(ContractError::Unauthorized {});
+    }
+    if !DENOMS.has(deps.storage, &denom) {
+        return Err(ContractError::DenomNotRegistered { denom })
+    }
+    // Bonded tokens need the adapter to be unbonded
+    let bonded = DENOM_BONDED.may_load(deps.storage, &denom)?.unwrap_or_default();
+    if !bonded.is_zero() {
+        return Err(ContractError::DenomStillBonded { denom, bonded })
+    }
+    DENOMS.remove(deps.storage, &denom);
+    DENOM_BONDED.remove(deps.storage, &denom);
+
+    Ok(Response::new()
+        .add_attribute("action", "remove_denom")
+        .add_attribute("denom", denom))
+}
+
+// Hands the contract over to a new agent, only callable by the agent
+pub fn execute_update_agent(deps: DepsMut, info: MessageInfo, agent: String) -> Result<Response, ContractError> {
+    if info.sender != AGENT.load(deps.storage)? {
+        return Err(ContractError::Unauthorized {});
+    }
+    let agent = deps.api.addr_validate(&agent)?;
+    AGENT.save(deps.storage, &agent.to_string())?;
+    Ok(Response::new()
+        .add_attribute("action", "update_agent")
+        .add_attribute("agent", agent))
+}
+
 // Removes a validator. If it has got tokens staked, it redelegates them. If it has not delegated tokens, just removes it from state.
-pub fn execute_remove_validator(deps: DepsMut, env: Env, info: MessageInfo, src_validator_address: String) -> Result<Response, ContractError> {
+pub fn execute_remove_validator(mut deps: DepsMut, env: Env, info: MessageInfo, src_validator_address: String) -> Result<Response, ContractError> {
     let manager = MANAGER.load(deps.storage)?;
     if info.sender != manager {
         return Err(ContractError::Unauthorized {});
@@ -480,7 +801,7 @@ pub fn execute_remove_validator(deps: DepsMut, env: Env, info: MessageInfo, src_
 
     // Contract state and Staking delegation must be aligned
     let src_validator = state.validator.load(deps.storage, &src_validator_address)?;
-    let option_full_delegation = deps.querier.query_delegation(env.contract.address,src_validator_address.clone())?;
+    let option_full_delegation = deps.querier.query_delegation(&env.contract.address,src_validator_address.clone())?;
     let state_amount = Uint128::from(src_validator.bonded);
     let delegation_amount = option_full_delegation.clone().unwrap().amount.amount;
     if  state_amount != delegation_amount {
@@ -489,25 +810,27 @@ pub fn execute_remove_validator(deps: DepsMut, env: Env, info: MessageInfo, src_
 
      let validator_count : u128 = state.validator.idx.bonded
     .range(deps.storage, None, None, Order::Descending)
-    .into_iter()
     .count().try_into().unwrap();
 
     if option_full_delegation.is_some() && validator_count ==1 {
         return Err(ContractError::OnlyOneValidator {})
     } 
 
-    let res:Response;   
-    if option_full_delegation.is_some() && state_amount != Uint128::zero(){
+    // A redelegation withdraws the pending rewards, which must be accrued first
+    let (reward_msgs, rewards) = accrue_rewards(deps.branch(), &env)?;
+
+    let res:Response = match option_full_delegation {
+        Some(full_delegation) if state_amount != Uint128::zero() => {
          // What if the chosen validator is the one we are trying to remove??
         let dst_validator_address = chosen_validator(deps.as_ref(), Some(src_validator_address.clone()))?;
     
         // Update state with redelegated bonded tokens to validator and validator that is removed
         let mut validator_info = state.validator.load(deps.storage, &dst_validator_address)?;
-        validator_info.bonded = validator_info.bonded + state_amount.u128();      
+        validator_info.bonded += state_amount.u128();      
         state.validator.save(deps.storage, &dst_validator_address, &validator_info)?;
         state.validator.remove(deps.storage, &src_validator_address)?;
 
-        let amount = option_full_delegation.unwrap().amount;
+        let amount = full_delegation.amount;
         // When we redelegate, by default all the pending rewards are claimed.
         let msg = StakingMsg::Redelegate { 
             src_validator:src_validator_address.to_string(), 
@@ -515,93 +838,493 @@ pub fn execute_remove_validator(deps: DepsMut, env: Env, info: MessageInfo, src_
             amount: amount.clone() 
         };
 
-        res = Response::new()
+        Response::new()
+        .add_messages(reward_msgs)
         .add_message(msg)
         .add_attribute("action", "remove_validator")
         .add_attribute("address",src_validator_address)
         .add_attribute("redelegated_validator", dst_validator_address)
         .add_attribute("redelegated_denom", amount.denom)
-        .add_attribute("redelegated_amount", amount.amount);
+        .add_attribute("redelegated_amount", amount.amount)
+        },
+        _ => {
+        state.validator.remove(deps.storage, &src_validator_address)?;
+        Response::new()
+        .add_messages(reward_msgs)
+        .add_attribute("action", "remove_validator")
+        .add_attribute("address",src_validator_address)
+        },
+    };
+     Ok(res.add_attribute("rewards", rewards))
+}
+
+// Check if chain delegated tokens by this contract match the value registered in TOTAL_BONDED state
+pub fn execute_bond_check (deps: Deps, env:Env, info: MessageInfo) -> Result<Response, ContractError>{
+    let manager = MANAGER.load(deps.storage)?;
+    if info.sender != manager {
+        return Err(ContractError::Unauthorized {});
+    }
+
+    // total number of tokensdelegated from this address
+    // Expecting all delegations to be of the same denom
+    let total_bonded = get_all_bonded(&deps.querier, &env.contract.address)?;
+
+    let state_total_bonded = BONDED.load(deps.storage)?;
+    if total_bonded != state_total_bonded {
+        return Err(ContractError::BondedDiffer {
+            total_bonded, state_total_bonded
+        });       
+    } 
+    Ok(Response::new()
+    .add_attribute("action", "bond_check")
+    .add_attribute("total_bonded", state_total_bonded))
+}
+
+// get_bonded returns the total amount of delegations from contract to all validators
+// it ensures they are all the same denom
+fn get_all_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, ContractError> {
+    let bonds = querier.query_all_delegations(contract)?;
+    if bonds.is_empty() {
+        return Ok(Uint128::zero());
+    }
+    let denom = bonds[0].amount.denom.as_str();
+    bonds.iter().try_fold(Uint128::zero(), |acc, d| {
+        if d.amount.denom.as_str() != denom {
+            Err(ContractError::DifferentBondDenom {
+                denom1: denom.into(),
+                denom2: d.amount.denom.to_string(),
+            })
+        } else {
+            Ok(acc + d.amount.amount)
+        }
+    })
+}
+
+// Collect pending rewards from all validators
+fn execute_collect_rewards ( deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>{
+    let manager = MANAGER.load(deps.storage)?;
+    if info.sender != manager {
+        return Err(ContractError::Unauthorized {});
+    }
+    // Rewards are withdrawn to this contract and distributed to the NFTs by their bonded tokens
+    let (msgs, rewards) = accrue_rewards(deps, &env)?;
+
+    let res = Response::new()
+        .add_messages(msgs)
+        .add_attribute("action", "withdraw_delegation_rewards")
+        .add_attribute("rewards", rewards);
+    Ok(res)
+}
+
+// Withdraws the rewards of every validator and adds them to REWARD_INDEX. Must run before BONDED changes.
+// Without bonded tokens the rewards go to the treasury
+fn accrue_rewards(deps: DepsMut, env: &Env) -> Result<(Vec<CosmosMsg>, Uint128), ContractError> {
+    let denom = deps.querier.query_bonded_denom()?;
+    let state = State::new();
+    let validators : StdResult<Vec<String>> = state.validator
+        .keys(deps.storage, None, None, Order::Ascending)
+        .collect();
+
+    let mut msgs : Vec<CosmosMsg> = vec![];
+    let mut rewards = Uint128::zero();
+    for validator in validators? {
+        let delegation = match deps.querier.query_delegation(&env.contract.address, &validator)? {
+            Some(delegation) => delegation,
+            None => continue,
+        };
+        let validator_rewards : Uint128 = delegation.accumulated_rewards
+            .iter()
+            .filter(|reward| reward.denom == denom)
+            .map(|reward| reward.amount)
+            .sum();
+        if !validator_rewards.is_zero() {
+            rewards += validator_rewards;
+            msgs.push(DistributionMsg::WithdrawDelegatorReward { validator }.into());
+        }
+    }
+    if rewards.is_zero() {
+        return Ok((msgs, rewards))
+    }
+
+    let bonded = BONDED.load(deps.storage)?;
+    if bonded.is_zero() {
+        msgs.push(BankMsg::Send { to_address: TREASURY.load(deps.storage)?, amount: vec![coin(rewards.u128(), denom)] }.into());
+    } else {
+        let index = REWARD_INDEX.may_load(deps.storage)?.unwrap_or_default() + Decimal::from_ratio(rewards, bonded);
+        REWARD_INDEX.save(deps.storage, &index)?;
+    }
+    Ok((msgs, rewards))
+}
+
+// Moves the rewards accrued by the NFT bonded tokens since its last update to its pending rewards
+fn settle_nft_rewards(storage: &mut dyn Storage, nft_id: &str) -> StdResult<NftRewards> {
+    let nft_rewards = nft_rewards(storage, nft_id)?;
+    NFT_REWARDS.save(storage, nft_id, &nft_rewards)?;
+    Ok(nft_rewards)
+}
+
+fn nft_rewards(storage: &dyn Storage, nft_id: &str) -> StdResult<NftRewards> {
+    let index = REWARD_INDEX.may_load(storage)?.unwrap_or_default();
+    let bonded = NFT_BONDED.may_load(storage, nft_id)?.unwrap_or_default();
+    let mut nft_rewards = NFT_REWARDS.may_load(storage, nft_id)?.unwrap_or_default();
+    nft_rewards.pending += bonded * (index - nft_rewards.index);
+    nft_rewards.index = index;
+    Ok(nft_rewards)
+}
+
+pub fn execute_claim_rewards(deps: DepsMut, _env: Env, info: MessageInfo, nft_id: Uint128, recipient: String) -> Result<Response, ContractError> {
+    let agent = AGENT.load(deps.storage)?;
+    if info.sender != agent {
+        return Err(ContractError::Unauthorized {});
+    }
+    let recipient = deps.api.addr_validate(&recipient)?;
+    let key = nft_id.to_string();
+    let mut nft_rewards = settle_nft_rewards(deps.storage, &key)?;
+    if nft_rewards.pending.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let rewards = nft_rewards.pending;
+    nft_rewards.pending = Uint128::zero();
+    NFT_REWARDS.save(deps.storage, &key, &nft_rewards)?;
+
+    let denom = deps.querier.query_bonded_denom()?;
+    Ok(Response::new()
+        .add_message(BankMsg::Send { to_address: recipient.to_string(), amount: vec![coin(rewards.u128(), denom)] })
+        .add_attribute("action", "claim_rewards")
+        .add_attribute("from", nft_id)
+        .add_attribute("recipient", recipient)
+        .add_attribute("rewards", rewards))
+}
+
+// Liquid staking shares are backed by the LIQUID_POOL position, on the same validators and reward index as the NFTs.
+// The rewards of the pool are bonded again on every liquid bond and unbond, raising the tokens per share
+pub fn execute_bond_liquid(mut deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    let d_coin = one_coin(&info).map_err(payment_error)?;
+    // Registered denoms are only bonded by NFTs
+    if d_coin.denom != deps.querier.query_bonded_denom()? {
+        return Err(ContractError::InvalidCoin {});
+    }
+
+    let (reward_msgs, rewards) = accrue_rewards(deps.branch(), &env)?;
+    let (pool_bonded, compounded) = compound_liquid_pool(deps.storage)?;
+    let supply = LIQUID_SUPPLY.may_load(deps.storage)?.unwrap_or_default();
+    let shares = match supply.is_zero() {
+        true => d_coin.amount,
+        false => d_coin.amount.multiply_ratio(supply, pool_bonded),
+    };
+    if shares.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+
+    let amount = d_coin.amount + compounded;
+    let validator_address = chosen_validator(deps.as_ref(), None)?;
+    add_bonded(deps.storage, &validator_address, LIQUID_POOL, amount)?;
+
+    LIQUID_SUPPLY.save(deps.storage, &(supply + shares))?;
+    LIQUID_BALANCES.update(deps.storage, &info.sender, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(shares)?)
+    })?;
+
+    Ok(Response::new()
+        .add_messages(reward_msgs)
+        .add_message(StakingMsg::Delegate {
+            validator: validator_address.to_string(),
+            amount: coin(amount.u128(), d_coin.denom),
+        })
+        .add_attribute("action", "bond_liquid")
+        .add_attribute("rewards", rewards)
+        .add_attribute("from", info.sender)
+        .add_attribute("bonded", d_coin.amount)
+        .add_attribute("compounded", compounded)
+        .add_attribute("shares", shares)
+        .add_attribute("validator", validator_address))
+}
+
+// Shares are burnt for their part of the pool, undelegated like an NFT unbond.
+// Each unbond gets its own claims, released after the unbonding period of their validators
+pub fn execute_unbond_liquid(mut deps: DepsMut, env: Env, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let balance = LIQUID_BALANCES.may_load(deps.storage, &info.sender)?.unwrap_or_default();
+    if balance < amount {
+        return Err(ContractError::LiquidBalanceTooSmall { balance, amount });
+    }
+
+    let (reward_msgs, rewards) = accrue_rewards(deps.branch(), &env)?;
+    let (pool_bonded, compounded) = compound_liquid_pool(deps.storage)?;
+    let can_be_bonded_denom = deps.querier.query_bonded_denom()?;
+    let mut msgs : Vec<StakingMsg> = vec![];
+    if !compounded.is_zero() {
+        let validator_address = chosen_validator(deps.as_ref(), None)?;
+        add_bonded(deps.storage, &validator_address, LIQUID_POOL, compounded)?;
+        msgs.push(StakingMsg::Delegate { validator: validator_address, amount: coin(compounded.u128(), &can_be_bonded_denom) });
+    }
+
+    let supply = LIQUID_SUPPLY.load(deps.storage)?;
+    let tokens = amount.multiply_ratio(pool_bonded, supply);
+    if tokens.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    LIQUID_SUPPLY.save(deps.storage, &(supply - amount))?;
+    LIQUID_BALANCES.save(deps.storage, &info.sender, &(balance - amount))?;
+    NFT_BONDED.save(deps.storage, LIQUID_POOL, &(pool_bonded - tokens))?;
+
+    let total_number_validators = NUMBER_VALIDATORS.load(deps.storage)?;
+    let number_validators= calc_validator_number(total_number_validators, tokens)?;
+    let vec_address_coin = chosen_validators_unstake(deps.as_ref(), tokens, can_be_bonded_denom, number_validators)?;
+
+    let state = State::new();
+    for (val_address, val_coin) in vec_address_coin.iter() {
+        let val_amount = val_coin.amount;
+        let mut validator_info = state.validator.load(deps.storage, val_address)?;
+        validator_info.bonded = validator_info.bonded.checked_sub(val_amount.u128()).unwrap();
+        validator_info.unbonding = validator_info.unbonding.checked_add(val_amount.u128()).unwrap();
+        state.validator.save(deps.storage,val_address,&validator_info)?;
+
+        // One claim per unbond, so earlier unbonds keep their release time. Unbonds in the same block share the key
+        let release_at = release_time(&env, &validator_info.unbonding_period)?;
+        let key = (&info.sender, release_at.seconds(), val_address.as_str());
+        let claim = match state.liquid_claims.may_load(deps.storage, key)? {
+            Some(claim) => LiquidClaim { amount: claim.amount + val_amount, ..claim },
+            None => LiquidClaim { holder: info.sender.clone(), validator: val_address.clone(), amount: val_amount, release_at },
+        };
+        state.liquid_claims.save(deps.storage, key, &claim)?;
+        msgs.push(StakingMsg::Undelegate { validator: val_address.clone(), amount: val_coin.clone() });
+    }
+
+    BONDED.update(deps.storage, |total| -> StdResult<_> {
+        Ok(total.checked_sub(tokens)?)
+    })?;   
+
+    UNBONDING.update(deps.storage, |total| -> StdResult<_> {
+        Ok(total.checked_add(tokens)?)
+    })?; 
+
+    Ok(Response::new()
+        .add_messages(reward_msgs)
+        .add_messages(msgs)
+        .add_attribute("action", "unbond_liquid")
+        .add_attribute("rewards", rewards)
+        .add_attribute("from", info.sender)
+        .add_attribute("compounded", compounded)
+        .add_attribute("shares", amount)
+        .add_attribute("unbonded", tokens))
+}
+
+pub fn execute_claim_liquid(deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError> {
+    // Keys are ordered by release time, so the mature claims come first
+    let state = State::new();
+    let mature_claims : Vec<LiquidClaim> = state.liquid_claims
+    .sub_prefix(&info.sender)
+    .range(deps.storage, None, None, Order::Ascending)
+    .map(|item| item.map(|(_, claim)| claim))
+    .take_while(|item| item.as_ref().map_or(true, |claim| claim.release_at <= env.block.time))
+    .collect::<StdResult<_>>()?;
+    let to_send : Uint128 = mature_claims.iter().map(|claim| claim.amount).sum();
+    if to_send.is_zero() {
+        return Err(ContractError::NothingToClaim {});
+    }
+    let mut balance = deps
+        .querier
+        .query_balance(&env.contract.address, deps.querier.query_bonded_denom()?)?;
+    if balance.amount < to_send {
+        return Err(ContractError::BalanceTooSmall {});
+    }
+    for claim in mature_claims.iter() {
+        state.liquid_claims.remove(deps.storage, (&info.sender, claim.release_at.seconds(), &claim.validator))?;
+    }
+    release_unbonding(deps.storage, mature_claims.iter().map(|claim| (claim.validator.as_str(), claim.amount)).collect())?;
+
+    balance.amount = to_send;
+    Ok(Response::new()
+        .add_message(BankMsg::Send {
+            to_address: info.sender.to_string(),
+            amount: vec![balance],
+        })
+        .add_attribute("action", "claim_liquid")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", to_send))
+}
+
+pub fn execute_transfer(deps: DepsMut, _env: Env, info: MessageInfo, recipient: String, amount: Uint128) -> Result<Response, ContractError> {
+    let recipient = deps.api.addr_validate(&recipient)?;
+    move_shares(deps.storage, &info.sender, &recipient, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "transfer")
+        .add_attribute("from", info.sender)
+        .add_attribute("to", recipient)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_send(deps: DepsMut, _env: Env, info: MessageInfo, contract: String, amount: Uint128, msg: Binary) -> Result<Response, ContractError> {
+    let contract = deps.api.addr_validate(&contract)?;
+    move_shares(deps.storage, &info.sender, &contract, amount)?;
+
+    let receive_msg = Cw20ReceiveMsg { sender: info.sender.to_string(), amount, msg };
+    Ok(Response::new()
+        .add_message(receive_msg.into_cosmos_msg(&contract)?)
+        .add_attribute("action", "send")
+        .add_attribute("from", info.sender)
+        .add_attribute("to", contract)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_burn(deps: DepsMut, _env: Env, info: MessageInfo, amount: Uint128) -> Result<Response, ContractError> {
+    burn_shares(deps.storage, &info.sender, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "burn")
+        .add_attribute("from", info.sender)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_increase_allowance(deps: DepsMut, env: Env, info: MessageInfo, spender: String, amount: Uint128, expires: Option<cw20::Expiration>) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    LIQUID_ALLOWANCES.update(deps.storage, (&info.sender, &spender), |allowance| -> Result<_, ContractError> {
+        let mut allowance = allowance.unwrap_or_default();
+        if let Some(expires) = expires {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            allowance.expires = expires;
+        }
+        allowance.allowance = allowance.allowance.checked_add(amount).map_err(StdError::from)?;
+        Ok(allowance)
+    })?;
+
+    Ok(Response::new()
+        .add_attribute("action", "increase_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("amount", amount))
+}
+
+pub fn execute_decrease_allowance(deps: DepsMut, env: Env, info: MessageInfo, spender: String, amount: Uint128, expires: Option<cw20::Expiration>) -> Result<Response, ContractError> {
+    let spender = deps.api.addr_validate(&spender)?;
+    if spender == info.sender {
+        return Err(ContractError::CannotSetOwnAccount {});
+    }
+    let key = (&info.sender, &spender);
+    let mut allowance = LIQUID_ALLOWANCES.load(deps.storage, key).map_err(|_| ContractError::NoAllowance {})?;
+    if amount < allowance.allowance {
+        allowance.allowance -= amount;
+        if let Some(expires) = expires {
+            if expires.is_expired(&env.block) {
+                return Err(ContractError::InvalidExpiration {});
+            }
+            allowance.expires = expires;
+        }
+        LIQUID_ALLOWANCES.save(deps.storage, key, &allowance)?;
     } else {
-        state.validator.remove(deps.storage, &src_validator_address)?;
-        res = Response::new()
-        .add_attribute("action", "remove_validator")
-        .add_attribute("address",src_validator_address)
+        LIQUID_ALLOWANCES.remove(deps.storage, key);
     }
-     Ok(res)
+
+    Ok(Response::new()
+        .add_attribute("action", "decrease_allowance")
+        .add_attribute("owner", info.sender)
+        .add_attribute("spender", spender)
+        .add_attribute("amount", amount))
 }
 
-// Check if chain delegated tokens by this contract match the value registered in TOTAL_BONDED state
-pub fn execute_bond_check (deps: Deps, env:Env, info: MessageInfo) -> Result<Response, ContractError>{
-    let manager = MANAGER.load(deps.storage)?;
-    if info.sender != manager {
-        return Err(ContractError::Unauthorized {});
-    }
+pub fn execute_transfer_from(deps: DepsMut, env: Env, info: MessageInfo, owner: String, recipient: String, amount: Uint128) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let recipient = deps.api.addr_validate(&recipient)?;
+    deduct_allowance(deps.storage, &env, &owner, &info.sender, amount)?;
+    move_shares(deps.storage, &owner, &recipient, amount)?;
 
-    // total number of tokensdelegated from this address
-    // Expecting all delegations to be of the same denom
-    let total_bonded = get_all_bonded(&deps.querier, &env.contract.address)?;
+    Ok(Response::new()
+        .add_attribute("action", "transfer_from")
+        .add_attribute("from", owner)
+        .add_attribute("to", recipient)
+        .add_attribute("by", info.sender)
+        .add_attribute("amount", amount))
+}
 
-    let state_total_bonded = BONDED.load(deps.storage)?;
-    if total_bonded != state_total_bonded {
-        return Err(ContractError::BondedDiffer {
-            total_bonded: total_bonded, state_total_bonded: state_total_bonded
-        });       
-    } 
+pub fn execute_send_from(deps: DepsMut, env: Env, info: MessageInfo, owner: String, contract: String, amount: Uint128, msg: Binary) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let contract = deps.api.addr_validate(&contract)?;
+    deduct_allowance(deps.storage, &env, &owner, &info.sender, amount)?;
+    move_shares(deps.storage, &owner, &contract, amount)?;
+
+    // The hook names the spender as sender, like cw20-base
+    let receive_msg = Cw20ReceiveMsg { sender: info.sender.to_string(), amount, msg };
     Ok(Response::new()
-    .add_attribute("action", "bond_check")
-    .add_attribute("total_bonded", state_total_bonded))
+        .add_message(receive_msg.into_cosmos_msg(&contract)?)
+        .add_attribute("action", "send_from")
+        .add_attribute("from", owner)
+        .add_attribute("to", contract)
+        .add_attribute("by", info.sender)
+        .add_attribute("amount", amount))
 }
 
-// get_bonded returns the total amount of delegations from contract to all validators
-// it ensures they are all the same denom
-fn get_all_bonded(querier: &QuerierWrapper, contract: &Addr) -> Result<Uint128, ContractError> {
-    let bonds = querier.query_all_delegations(contract)?;
-    if bonds.is_empty() {
-        return Ok(Uint128::zero());
-    }
-    let denom = bonds[0].amount.denom.as_str();
-    bonds.iter().fold(Ok(Uint128::zero()), |racc, d| {
-        let acc = racc?;
-        if d.amount.denom.as_str() != denom {
-            Err(ContractError::DifferentBondDenom {
-                denom1: denom.into(),
-                denom2: d.amount.denom.to_string(),
-            })
-        } else {
-            Ok(acc + d.amount.amount)
-        }
-    })
+pub fn execute_burn_from(deps: DepsMut, env: Env, info: MessageInfo, owner: String, amount: Uint128) -> Result<Response, ContractError> {
+    let owner = deps.api.addr_validate(&owner)?;
+    deduct_allowance(deps.storage, &env, &owner, &info.sender, amount)?;
+    burn_shares(deps.storage, &owner, amount)?;
+
+    Ok(Response::new()
+        .add_attribute("action", "burn_from")
+        .add_attribute("from", owner)
+        .add_attribute("by", info.sender)
+        .add_attribute("amount", amount))
 }
 
-// Collect pending rewards from all validators
-fn execute_collect_rewards ( deps: DepsMut, _env: Env, info: MessageInfo) -> Result<Response, ContractError>{
-    let manager = MANAGER.load(deps.storage)?;
-    if info.sender != manager {
-        return Err(ContractError::Unauthorized {});
+// Lowers the allowance owner granted to spender by amount, failing if it is expired or too small
+fn deduct_allowance(storage: &mut dyn Storage, env: &Env, owner: &Addr, spender: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    let mut allowance = LIQUID_ALLOWANCES.may_load(storage, (owner, spender))?.ok_or(ContractError::NoAllowance {})?;
+    if allowance.expires.is_expired(&env.block) {
+        return Err(ContractError::AllowanceExpired {});
     }
-    // Any validator rewards have been previosly and automatically claimed when 'bonded change' occurred on any registered validator
-    let state = State::new();
-    let msgs : StdResult<Vec<DistributionMsg>> = state.validator.idx
-        .bonded
-        .range(deps.storage,None, None, Order::Descending)
-        .filter(|item|
-            item.as_ref().unwrap().1.bonded > 0)
-        .map(|item| 
-            Ok(DistributionMsg::WithdrawDelegatorReward { validator: item.unwrap().0 }))
-        .collect();
+    allowance.allowance = allowance.allowance.checked_sub(amount).map_err(|_| ContractError::NoAllowance {})?;
+    LIQUID_ALLOWANCES.save(storage, (owner, spender), &allowance)?;
+    Ok(())
+}
 
-    let treasury_addr = TREASURY.load(deps.storage)?;
+// Shares are destroyed without unbonding, so the pool tokens they stood for go to the remaining shares
+fn burn_shares(storage: &mut dyn Storage, from: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let balance = LIQUID_BALANCES.may_load(storage, from)?.unwrap_or_default();
+    if balance < amount {
+        return Err(ContractError::LiquidBalanceTooSmall { balance, amount });
+    }
+    LIQUID_BALANCES.save(storage, from, &(balance - amount))?;
+    LIQUID_SUPPLY.update(storage, |supply| -> StdResult<_> {
+        Ok(supply.checked_sub(amount)?)
+    })?;
+    Ok(())
+}
 
-   // QUESTION: Setting the address to receive the rewards. Do this affect who does receive the unbonding tokens?
-   let msg_set_withdraw_address = DistributionMsg::SetWithdrawAddress { address: treasury_addr };
+fn move_shares(storage: &mut dyn Storage, from: &Addr, to: &Addr, amount: Uint128) -> Result<(), ContractError> {
+    if amount.is_zero() {
+        return Err(ContractError::InvalidZeroAmount {});
+    }
+    let balance = LIQUID_BALANCES.may_load(storage, from)?.unwrap_or_default();
+    if balance < amount {
+        return Err(ContractError::LiquidBalanceTooSmall { balance, amount });
+    }
+    LIQUID_BALANCES.save(storage, from, &(balance - amount))?;
+    LIQUID_BALANCES.update(storage, to, |balance| -> StdResult<_> {
+        Ok(balance.unwrap_or_default().checked_add(amount)?)
+    })?;
+    Ok(())
+}
 
-    let msgs = msgs?;
-    let res = Response::new()
-        .add_message(msg_set_withdraw_address)
-        .add_messages(msgs)
-        .add_attribute("action", "withdraw_delegation_rewards");
-    Ok(res)
+// Moves the pending rewards of the liquid staking pool to its bonded tokens, to be delegated by the caller.
+// Returns the pool tokens, rewards included, and the rewards
+fn compound_liquid_pool(storage: &mut dyn Storage) -> StdResult<(Uint128, Uint128)> {
+    let mut pool_rewards = settle_nft_rewards(storage, LIQUID_POOL)?;
+    let compounded = pool_rewards.pending;
+    pool_rewards.pending = Uint128::zero();
+    NFT_REWARDS.save(storage, LIQUID_POOL, &pool_rewards)?;
+    let bonded = NFT_BONDED.may_load(storage, LIQUID_POOL)?.unwrap_or_default();
+    Ok((bonded + compounded, compounded))
 }
 
 fn _execute_transfer_balance (deps: DepsMut, env: Env, info: MessageInfo) -> Result<Response, ContractError>{
@@ -631,8 +1354,12 @@ fn _execute_transfer_balance (deps: DepsMut, env: Env, info: MessageInfo) -> Res
 pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
     let state = State::new();
     match msg {
-        // Returns #[returns(ClaimsResponse)]
-        QueryMsg::Claims { nft_id } => {to_binary(&CLAIMS.query_claims(deps, &Addr::unchecked(nft_id))?)},
+        // Returns #[returns(NftClaimsResponse)]
+        QueryMsg::Claims { nft_id, start_after, limit } => to_binary(&query_claims(deps, nft_id, start_after, limit)?),
+        QueryMsg::DenomClaims { nft_id, start_after, limit } => to_binary(&query_denom_claims(deps, nft_id, start_after, limit)?),
+        QueryMsg::LiquidClaims { address, start_after, limit } => to_binary(&query_liquid_claims(deps, address, start_after, limit)?),
+        QueryMsg::MatureClaims { as_of, start_after, limit } => to_binary(&query_mature_claims(deps, env, as_of, start_after, limit)?),
+        QueryMsg::UpcomingReleases { until } => to_binary(&query_upcoming_releases(deps, env, until)?),
         // [returns(Validator_Info)]
         QueryMsg::ValidatorInfo {address} => to_binary(&state.validator.load(deps.storage,&address)?),
         // [returns(Validator_Deposits)]
@@ -647,7 +1374,171 @@ pub fn query(deps: Deps, env: Env, msg: QueryMsg) -> StdResult<Binary> {
         QueryMsg::AllDelegations {  } => to_binary(&deps.querier.query_all_delegations(env.contract.address)?),
         QueryMsg::DelegationOnValidator { address } => to_binary(&deps.querier.query_delegation(env.contract.address, address)?),
         QueryMsg::BondedByNFT { nft_id } => to_binary(&NFT_BONDED.may_load(deps.storage,&nft_id)?.unwrap_or_default()),
+        QueryMsg::BondedCoinsByNFT { nft_id } => to_binary(&query_bonded_coins(deps, nft_id)?),
+        QueryMsg::Denoms {  } => to_binary(&query_denoms(deps)?),
+        QueryMsg::Allocation { amount } => to_binary(&query_allocation(deps, amount)?),
+        QueryMsg::RewardsByNFT { nft_id } => to_binary(&nft_rewards(deps.storage, &nft_id)?.pending),
+        QueryMsg::Balance { address } => to_binary(&query_balance(deps, address)?),
+        QueryMsg::TokenInfo {  } => to_binary(&TokenInfoResponse {
+            name: LIQUID_NAME.to_string(),
+            symbol: LIQUID_SYMBOL.to_string(),
+            decimals: LIQUID_DECIMALS,
+            total_supply: LIQUID_SUPPLY.may_load(deps.storage)?.unwrap_or_default(),
+        }),
+        QueryMsg::LiquidPool {  } => to_binary(&query_liquid_pool(deps)?),
+        QueryMsg::Allowance { owner, spender } => to_binary(&query_allowance(deps, owner, spender)?),
+        QueryMsg::AllAllowances { owner, start_after, limit } => to_binary(&query_all_allowances(deps, owner, start_after, limit)?),
+        QueryMsg::AllAccounts { start_after, limit } => to_binary(&query_all_accounts(deps, start_after, limit)?),
+    }
+}
+
+pub fn query_claims(deps: Deps, nft_id: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<NftClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let state = State::new();
+    let claims : StdResult<Vec<NftClaim>> = state.claims
+    .prefix(&nft_id)
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, claim)| claim))
+    .collect();
+    Ok(NftClaimsResponse { claims: claims? })
+}
+
+pub fn query_denom_claims(deps: Deps, nft_id: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<DenomClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start = start_after.as_deref().map(Bound::exclusive);
+    let claims : StdResult<Vec<DenomClaim>> = DENOM_CLAIMS
+    .prefix(&nft_id)
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, claim)| claim))
+    .collect();
+    Ok(DenomClaimsResponse { claims: claims? })
+}
+
+pub fn query_liquid_claims(deps: Deps, address: String, start_after: Option<LiquidClaimCursor>, limit: Option<u32>) -> StdResult<LiquidClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let address = deps.api.addr_validate(&address)?;
+    let start = start_after
+        .as_ref()
+        .map(|cursor| Bound::exclusive((cursor.release_at.seconds(), cursor.validator.as_str())));
+    let claims : StdResult<Vec<LiquidClaim>> = State::new().liquid_claims
+    .sub_prefix(&address)
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(_, claim)| claim))
+    .collect();
+    Ok(LiquidClaimsResponse { claims: claims? })
+}
+
+pub fn query_mature_claims(deps: Deps, env: Env, as_of: Option<Timestamp>, start_after: Option<ClaimCursor>, limit: Option<u32>) -> StdResult<NftClaimsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let as_of = as_of.unwrap_or(env.block.time);
+    let state = State::new();
+    let start = start_after
+        .as_ref()
+        .map(|cursor| Bound::exclusive((cursor.release_at.seconds(), (cursor.nft_id.as_str(), cursor.validator.as_str()))));
+    let claims : StdResult<Vec<NftClaim>> = state.claims.idx.release
+    .range(deps.storage, start, None, Order::Ascending)
+    .map(|item| item.map(|(_, claim)| claim))
+    .take_while(|item| item.as_ref().map_or(true, |claim| claim.release_at <= as_of))
+    .take(limit)
+    .collect();
+    Ok(NftClaimsResponse { claims: claims? })
+}
+
+pub fn query_upcoming_releases(deps: Deps, env: Env, until: Timestamp) -> StdResult<UpcomingReleasesResponse> {
+    let state = State::new();
+    let start = Some(PrefixBound::exclusive(env.block.time.seconds()));
+    let end = Some(PrefixBound::inclusive(until.seconds()));
+    // Both the nft_id and the liquid staking claims come back from the staking module, aggregated by release time
+    let mut amounts : BTreeMap<Timestamp, Uint128> = BTreeMap::new();
+    for item in state.claims.idx.release.prefix_range_raw(deps.storage, start.clone(), end.clone(), Order::Ascending) {
+        let (_, claim) = item?;
+        *amounts.entry(claim.release_at).or_default() += claim.amount;
+    }
+    for item in state.liquid_claims.idx.release.prefix_range_raw(deps.storage, start, end, Order::Ascending) {
+        let (_, claim) = item?;
+        *amounts.entry(claim.release_at).or_default() += claim.amount;
+    }
+    let releases : Vec<Release> = amounts
+        .into_iter()
+        .map(|(release_at, amount)| Release { release_at, amount })
+        .collect();
+    let total = releases.iter().map(|release| release.amount).sum();
+    Ok(UpcomingReleasesResponse { releases, total })
+}
+
+pub fn query_balance(deps: Deps, address: String) -> StdResult<BalanceResponse> {
+    let address = deps.api.addr_validate(&address)?;
+    let balance = LIQUID_BALANCES.may_load(deps.storage, &address)?.unwrap_or_default();
+    Ok(BalanceResponse { balance })
+}
+
+pub fn query_allowance(deps: Deps, owner: String, spender: String) -> StdResult<AllowanceResponse> {
+    let owner = deps.api.addr_validate(&owner)?;
+    let spender = deps.api.addr_validate(&spender)?;
+    Ok(LIQUID_ALLOWANCES.may_load(deps.storage, (&owner, &spender))?.unwrap_or_default())
+}
+
+pub fn query_all_allowances(deps: Deps, owner: String, start_after: Option<String>, limit: Option<u32>) -> StdResult<AllAllowancesResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let owner = deps.api.addr_validate(&owner)?;
+    let start_after = start_after.map(|spender| deps.api.addr_validate(&spender)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let allowances : StdResult<Vec<AllowanceInfo>> = LIQUID_ALLOWANCES
+    .prefix(&owner)
+    .range(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|(spender, allowance)| AllowanceInfo { spender: spender.to_string(), allowance: allowance.allowance, expires: allowance.expires }))
+    .collect();
+    Ok(AllAllowancesResponse { allowances: allowances? })
+}
+
+pub fn query_all_accounts(deps: Deps, start_after: Option<String>, limit: Option<u32>) -> StdResult<AllAccountsResponse> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT).min(MAX_LIMIT) as usize;
+    let start_after = start_after.map(|address| deps.api.addr_validate(&address)).transpose()?;
+    let start = start_after.as_ref().map(Bound::exclusive);
+    let accounts : StdResult<Vec<String>> = LIQUID_BALANCES
+    .keys(deps.storage, start, None, Order::Ascending)
+    .take(limit)
+    .map(|item| item.map(|address| address.to_string()))
+    .collect();
+    Ok(AllAccountsResponse { accounts: accounts? })
+}
+
+pub fn query_liquid_pool(deps: Deps) -> StdResult<LiquidPoolResponse> {
+    let bonded = NFT_BONDED.may_load(deps.storage, LIQUID_POOL)?.unwrap_or_default();
+    let rewards = nft_rewards(deps.storage, LIQUID_POOL)?.pending;
+    Ok(LiquidPoolResponse {
+        bonded: bonded + rewards,
+        supply: LIQUID_SUPPLY.may_load(deps.storage)?.unwrap_or_default(),
+    })
+}
+
+pub fn query_bonded_coins(deps: Deps, nft_id: String) -> StdResult<Vec<Coin>> {
+    let mut bonded = vec![];
+    let amount = NFT_BONDED.may_load(deps.storage, &nft_id)?.unwrap_or_default();
+    if !amount.is_zero() {
+        bonded.push(coin(amount.u128(), deps.querier.query_bonded_denom()?));
+    }
+    for (denom, amount) in nft_denom_bonded(deps.storage, &nft_id)? {
+        bonded.push(coin(amount.u128(), denom));
     }
+    Ok(bonded)
+}
+
+pub fn query_denoms(deps: Deps) -> StdResult<DenomsResponse> {
+    let denoms = DENOMS
+        .range(deps.storage, None, None, Order::Ascending)
+        .map(|item| item.and_then(|(denom, adapter)| Ok(DenomInfo {
+            bonded: DENOM_BONDED.may_load(deps.storage, &denom)?.unwrap_or_default(),
+            denom,
+            adapter,
+        })))
+        .collect::<StdResult<Vec<_>>>()?;
+    Ok(DenomsResponse { denoms })
 }
 
 pub fn query_bonded_on_validator(deps: Deps, env: Env,  val_address:String) -> StdResult<Uint128> {
@@ -666,7 +1557,112 @@ fn bonded_on_validator(querier: &QuerierWrapper, delegator: &Addr, validator: &A
     let _denom = full_delegation.amount.denom.as_str();
     let amount = full_delegation.amount.amount;
 
-    Ok(Uint128::from(amount))
+    Ok(amount)
+}
+
+// Rounding leftovers go to the validator with the most bonded tokens
+fn query_allocation(deps: Deps, amount: Uint128) -> StdResult<AllocationResponse> {
+    let bonded = BONDED.may_load(deps.storage)?.unwrap_or_default();
+    if bonded.is_zero() || amount.is_zero() {
+        return Ok(AllocationResponse { allocations: vec![] })
+    }
+    let state = State::new();
+    let mut allocations = state.validator.idx
+        .bonded
+        .range(deps.storage, None, None, Order::Descending)
+        .filter(|item| item.as_ref().map_or(true, |(_, validator_info)| validator_info.bonded > 0))
+        .map(|item| item.map(|(validator, validator_info)| ValidatorAllocation {
+            validator,
+            amount: amount.multiply_ratio(validator_info.bonded, bonded),
+        }))
+        .collect::<StdResult<Vec<_>>>()?;
+    let allocated : Uint128 = allocations.iter().map(|allocation| allocation.amount).sum();
+    if let Some(allocation) = allocations.first_mut() {
+        allocation.amount += amount.saturating_sub(allocated);
+    }
+    allocations.retain(|allocation| !allocation.amount.is_zero());
+    Ok(AllocationResponse { allocations })
+}
+
+#[cfg_attr(not(feature = "library"), entry_point)]
+pub fn migrate(deps: DepsMut, env: Env, _msg: MigrateMsg) -> Result<Response, ContractError> {
+    // Only the legacy version of this contract is migrated, once
+    let version = get_contract_version(deps.storage)?;
+    if version.contract != CONTRACT_NAME || version.version == CONTRACT_VERSION {
+        return Err(ContractError::InvalidMigration { contract: version.contract, version: version.version });
+    }
+    set_contract_version(deps.storage, CONTRACT_NAME, CONTRACT_VERSION)?;
+
+    // Move cw_controllers claims, keyed by Addr::unchecked(nft_id), into State.claims.
+    // Claims are rebuilt from NFT_VAL_UNBONDING. A legacy unbond undelegated the whole NFT at once,
+    // so each validator released its tokens one unbonding period after the unbond time of the NFT
+    let legacy : StdResult<Vec<(Addr, Vec<cw_controllers::Claim>)>> = LEGACY_CLAIMS
+    .range(deps.storage, None, None, Order::Ascending)
+    .collect();
+    let legacy = legacy?;
+
+    let state = State::new();
+    let mut migrated = 0u64;
+    for (nft_addr, claims) in legacy {
+        let nft_id = nft_addr.to_string();
+        let res : StdResult<Vec<(String, Uint128)>> = NFT_VAL_UNBONDING
+        .prefix(&nft_id)
+        .range(deps.storage, None, None, Order::Ascending)
+        .collect();
+        let val_unbonding = res?;
+        LEGACY_CLAIMS.remove(deps.storage, &nft_addr);
+        if claims.is_empty() {
+            continue;
+        }
+
+        let mut first_release : Option<Timestamp> = None;
+        for claim in claims.iter() {
+            let release_at = match claim.release_at {
+                Expiration::AtTime(time) => time,
+                _ => return Err(ContractError::InvalidExpiration {}),
+            };
+            first_release = Some(first_release.map_or(release_at, |first| first.min(release_at)));
+        }
+        let mut periods = vec![];
+        for (validator, _) in val_unbonding.iter() {
+            match state.validator.load(deps.storage, validator)?.unbonding_period {
+                Duration::Time(seconds) => periods.push(seconds),
+                Duration::Height(_) => return Err(ContractError::InvalidExpiration {}),
+            }
+        }
+        let legacy_amount : Uint128 = claims.iter().map(|claim| claim.amount).sum();
+        let unbonding_amount : Uint128 = val_unbonding.iter().map(|(_, amount)| *amount).sum();
+        let shortest_period = periods.iter().min();
+        let (first_release, shortest_period) = match (first_release, shortest_period) {
+            (Some(first_release), Some(shortest_period)) if legacy_amount == unbonding_amount => (first_release, *shortest_period),
+            _ => return Err(ContractError::CustomError { val: format!("Legacy claims of nft_id {} do not match its unbonding validators", nft_id) }),
+        };
+        let unbonded_at = first_release.minus_seconds(shortest_period);
+
+        for ((validator, amount), period) in val_unbonding.into_iter().zip(periods) {
+            let release_at = unbonded_at.plus_seconds(period);
+            let nft_claim = NftClaim { nft_id: nft_id.clone(), validator: validator.clone(), amount, release_at };
+            state.claims.save(deps.storage, (&nft_id, &validator), &nft_claim)?;
+            migrated += 1;
+        }
+    }
+
+    // Entries of already claimed NFTs were never removed
+    let res : StdResult<Vec<(String, String)>> = NFT_VAL_UNBONDING
+    .keys(deps.storage, None, None, Order::Ascending)
+    .collect();
+    for (nft_id, validator) in res? {
+        NFT_VAL_UNBONDING.remove(deps.storage, (&nft_id, &validator));
+    }
+
+    // Rewards were withdrawn to the treasury. They are now distributed to the NFTs from this contract
+    let msg_set_withdraw_address = DistributionMsg::SetWithdrawAddress { address: env.contract.address.to_string() };
+
+    Ok(Response::new()
+        .add_message(msg_set_withdraw_address)
+        .add_attribute("action", "migrate")
+        .add_attribute("migrated_claims", migrated.to_string())
+    )
 }
 
 // *****************************************************************************************************************************
@@ -685,6 +1681,7 @@ mod tests {
     };
     use cw_controllers::Claim;
     use cw_utils::{Duration, DAY, HOUR, WEEK};
+    use crate::msg::{ClaimCursor, NftClaimsResponse, Release, UpcomingReleasesResponse};
 
     const MANAGER1: &str = "manager";
     const AGENT1: &str = "agent";
@@ -699,7 +1696,7 @@ mod tests {
     const VALIDATOR3: &str = "validator3";
 
     const USER1: &str = "bob";
-    const _USER2: &str = "jane";
+    const USER2: &str = "jane";
 
 
 
@@ -741,6 +1738,12 @@ mod tests {
     }
 
     // just a test helper, forgive the panic
+    fn mock_env_height(height: u64) -> Env {
+        let mut env = mock_env();
+        env.block.height = height;
+        env
+    }
+
     fn later(env: &Env, delta: Duration) -> Env {
         let time_delta = match delta {
             Duration::Time(t) => t,
@@ -751,11 +1754,16 @@ mod tests {
         res
     }
 
-    fn get_claims(deps: Deps, addr: &str) -> Vec<Claim> {
-        CLAIMS
-            .query_claims(deps, &Addr::unchecked(addr))
-            .unwrap()
-            .claims
+    fn get_claims(deps: Deps, nft_id: &str) -> Vec<NftClaim> {
+        let msg = QueryMsg::Claims { nft_id: nft_id.to_string(), start_after: None, limit: None };
+        let res : NftClaimsResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.claims
+    }
+
+    fn get_liquid_claims(deps: Deps, address: &str, start_after: Option<LiquidClaimCursor>) -> Vec<LiquidClaim> {
+        let msg = QueryMsg::LiquidClaims { address: address.to_string(), start_after, limit: None };
+        let res : LiquidClaimsResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+        res.claims
     }
 
     fn register_3_validators (mut deps: DepsMut, env:Env, info:MessageInfo, ) {
@@ -790,7 +1798,7 @@ mod tests {
 
         if val1_amount > 0 {
             let balance = coins(val1_amount, "ustake");
-            let info = mock_info(&info.sender.to_string(), &balance);  
+            let info = mock_info(info.sender.as_ref(), &balance);  
             let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID1) };
             let res = execute(deps.branch(), env.clone(), info.clone(), msg).unwrap();
             assert_eq!(res.attributes[0], ("action", "bond"));
@@ -798,7 +1806,7 @@ mod tests {
 
         if val2_amount > 0 {
             let balance = coins(val2_amount, "ustake");
-            let info = mock_info(&info.sender.to_string(), &balance);  
+            let info = mock_info(info.sender.as_ref(), &balance);  
             let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID2) };
             let res = execute(deps.branch(), env.clone(), info.clone(), msg).unwrap();
             assert_eq!(res.attributes[0], ("action", "bond"));
@@ -806,7 +1814,7 @@ mod tests {
 
         if val3_amount > 0 {
             let balance = coins(val3_amount, "ustake");
-            let info = mock_info(&info.sender.to_string(), &balance);  
+            let info = mock_info(info.sender.as_ref(), &balance);  
             let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID3) };
             let res = execute(deps.branch(), env.clone(), info.clone(), msg).unwrap();
             assert_eq!(res.attributes[0], ("action", "bond"));
@@ -907,7 +1915,7 @@ mod tests {
 
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
         
         let msg = QueryMsg::ValidatorInfo { address: VALIDATOR1.to_string() };
@@ -939,7 +1947,7 @@ mod tests {
 
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
 
         let balance = [coin(10, "random"), coin(100, "ustake")];
@@ -1016,9 +2024,9 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 500,300, 200);
         check_bonding_on_validators(deps.as_ref(), 
             500, 
@@ -1138,9 +2146,9 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 500,300, 200);
         check_bonding_on_validators(deps.as_ref(), 
             500, 
@@ -1179,12 +2187,18 @@ mod tests {
 
         // At this point, two claims were created for validator1 and validator2, each with respective unbonding period
         let claimer = NFT_ID3.to_string();
+        let release_at = env.block.time.plus_seconds(7 * 24 * 60 * 60);
         let original_claims = vec![
-            Claim {amount: Uint128::from(100u128),release_at: (WEEK).after(&env.block)},
-            Claim {amount: Uint128::from(100u128),release_at: (WEEK).after(&env.block)},           
+            NftClaim {nft_id: claimer.clone(), validator: VALIDATOR1.to_string(), amount: Uint128::from(100u128), release_at},
+            NftClaim {nft_id: claimer.clone(), validator: VALIDATOR2.to_string(), amount: Uint128::from(100u128), release_at},           
             ];
         assert_eq!(original_claims, get_claims(deps.as_ref(), &claimer));
 
+        // Paginated by validator address
+        let msg = QueryMsg::Claims { nft_id: claimer.clone(), start_after: Some(VALIDATOR1.to_string()), limit: Some(1) };
+        let res : NftClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.claims, vec![original_claims[1].clone()]);
+
         // Just before a week,  the contract has NOT received the 200 unstaked tokens
         let env_not_claim_ready = later(&env, DAY);
         deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(0, "ustake"));
@@ -1204,6 +2218,7 @@ mod tests {
         let msg = ExecuteMsg::Claim { nft_id: Uint128::from(NFT_ID3), sender: USER1.to_string(), amount: Uint128::from(200u128)};
         let res = execute(deps.as_mut(), env_claim_ready.clone(), info.clone(), msg).unwrap();
         assert_eq!(res.attributes[0], ("action", "claim"));
+        assert!(get_claims(deps.as_ref(), &claimer).is_empty());
 
         check_bonding_on_validators(deps.as_ref(), 
         400, 
@@ -1305,6 +2320,501 @@ mod tests {
         );
     }
 
+    #[test]
+    fn mature_claims_upcoming_releases() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let info = mock_info(MANAGER1, &[]); 
+        register_3_validators(deps.as_mut(), env.clone(), info.clone());
+        let info = mock_info(AGENT1, &[]); 
+        nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 500,300, 200);
+
+        // NFT3 unbonds now from VALIDATOR1 and VALIDATOR2, NFT2 a day later from VALIDATOR1
+        let msg = ExecuteMsg::Unbond { nft_id: Uint128::from(NFT_ID3), amount: Uint128::from(200u128)  };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let env_day = later(&env, DAY);
+        let msg = ExecuteMsg::Unbond { nft_id: Uint128::from(NFT_ID2), amount: Uint128::from(300u128)  };
+        execute(deps.as_mut(), env_day.clone(), info.clone(), msg).unwrap();
+
+        let week = 7 * 24 * 60 * 60;
+        let nft3_release = env.block.time.plus_seconds(week);
+        let nft2_release = env_day.block.time.plus_seconds(week);
+
+        // Nothing has matured yet
+        let msg = QueryMsg::MatureClaims { as_of: None, start_after: None, limit: None };
+        let res : NftClaimsResponse = from_binary(&query(deps.as_ref(), env_day.clone(), msg).unwrap()).unwrap();
+        assert!(res.claims.is_empty());
+
+        // After a week only NFT3 claims have matured
+        let msg = QueryMsg::MatureClaims { as_of: Some(nft3_release), start_after: None, limit: None };
+        let res : NftClaimsResponse = from_binary(&query(deps.as_ref(), env_day.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.claims.len(), 2);
+        assert!(res.claims.iter().all(|claim| claim.nft_id == NFT_ID3.to_string()));
+
+        // Ordered by release time and limited
+        let msg = QueryMsg::MatureClaims { as_of: Some(nft2_release), start_after: None, limit: Some(3) };
+        let res : NftClaimsResponse = from_binary(&query(deps.as_ref(), env_day.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res.claims.len(), 3);
+        assert_eq!(res.claims[2].nft_id, NFT_ID2.to_string());
+
+        // Next page after the last claim
+        let last = &res.claims[1];
+        let start_after = ClaimCursor { release_at: last.release_at, nft_id: last.nft_id.clone(), validator: last.validator.clone() };
+        let msg = QueryMsg::MatureClaims { as_of: Some(nft2_release), start_after: Some(start_after), limit: None };
+        let page : NftClaimsResponse = from_binary(&query(deps.as_ref(), env_day.clone(), msg).unwrap()).unwrap();
+        assert_eq!(page.claims.len(), 2);
+        assert_eq!(page.claims[0], res.claims[2]);
+        assert!(page.claims.iter().all(|claim| claim.nft_id == NFT_ID2.to_string()));
+
+        let msg = QueryMsg::UpcomingReleases { until: nft2_release };
+        let res : UpcomingReleasesResponse = from_binary(&query(deps.as_ref(), env_day.clone(), msg).unwrap()).unwrap();
+        assert_eq!(res, UpcomingReleasesResponse { 
+            releases: vec![
+                Release { release_at: nft3_release, amount: Uint128::from(200u128) },
+                Release { release_at: nft2_release, amount: Uint128::from(300u128) },
+            ], 
+            total: Uint128::from(500u128) 
+        });
+
+        // Already released claims are not upcoming
+        let msg = QueryMsg::UpcomingReleases { until: nft2_release };
+        let res : UpcomingReleasesResponse = from_binary(&query(deps.as_ref(), later(&env, (WEEK + HOUR).unwrap()), msg).unwrap()).unwrap();
+        assert_eq!(res.total, Uint128::from(300u128));
+    }
+
+    #[test]
+    fn merge_split_bonded() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        let info = mock_info(MANAGER1, &[]); 
+        register_3_validators(deps.as_mut(), env.clone(), info.clone());
+        let info = mock_info(AGENT1, &[]); 
+        nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 500,300, 200);
+        let bonded_by_nft = |deps: Deps, nft_id: u128| -> Uint128 {
+            let msg = QueryMsg::BondedByNFT { nft_id: nft_id.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // Only the agent
+        let msg = ExecuteMsg::Merge { nft_id: Uint128::from(NFT_ID1), nft_ids: vec![Uint128::from(NFT_ID2)] };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {  });
+
+        let msg = ExecuteMsg::Merge { nft_id: Uint128::from(NFT_ID1), nft_ids: vec![Uint128::from(NFT_ID2), Uint128::from(4u128)] };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::NFTNotRegistered { nft_id: "4".to_string() });
+
+        let msg = ExecuteMsg::Merge { nft_id: Uint128::from(NFT_ID1), nft_ids: vec![Uint128::from(NFT_ID2), Uint128::from(NFT_ID3)] };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(bonded_by_nft(deps.as_ref(), NFT_ID1), Uint128::from(1000u128));
+        assert_eq!(bonded_by_nft(deps.as_ref(), NFT_ID2), Uint128::zero());
+        assert_eq!(bonded_by_nft(deps.as_ref(), NFT_ID3), Uint128::zero());
+
+        // Split must leave tokens on the NFT and can not reuse registered NFTs
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(4u128)], amounts: vec![Uint128::from(1000u128)] };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSplit { nft_id: NFT_ID1.to_string(), bonded: Uint128::from(1000u128) });
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(4u128)], amounts: vec![] };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(4u128), Uint128::from(5u128)], amounts: vec![Uint128::MAX, Uint128::from(2u128)] };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidSplit { nft_id: NFT_ID1.to_string(), bonded: Uint128::from(1000u128) });
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(NFT_ID1)], amounts: vec![Uint128::from(100u128)] };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::NFTAlreadyRegistered { nft_id: NFT_ID1.to_string() });
+
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(4u128), Uint128::from(5u128)], amounts: vec![Uint128::from(100u128), Uint128::from(300u128)] };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(bonded_by_nft(deps.as_ref(), NFT_ID1), Uint128::from(600u128));
+        assert_eq!(bonded_by_nft(deps.as_ref(), 4), Uint128::from(100u128));
+        assert_eq!(bonded_by_nft(deps.as_ref(), 5), Uint128::from(300u128));
+
+        // Validators are untouched
+        check_bonding_on_validators(deps.as_ref(), 500, 300, 200, 0, 0, 0);
+    }
+
+    #[test]
+    fn registered_denoms() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        register_3_validators(deps.as_mut(), env.clone(), info.clone());
+        nft123_bond_on_validators(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), 500, 0, 0);
+
+        // Only the manager registers denoms other than the bond denom, released on time
+        let custody = DenomAdapter::Custody { unbonding_period: WEEK };
+        let msg = ExecuteMsg::RegisterDenom { denom: "stlsd".to_string(), adapter: custody.clone() };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::Unauthorized {  });
+        let msg = ExecuteMsg::RegisterDenom { denom: "ustake".to_string(), adapter: custody.clone() };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::DenomAlreadyRegistered { denom: "ustake".to_string() });
+        let msg = ExecuteMsg::RegisterDenom { denom: "stlsd".to_string(), adapter: DenomAdapter::Custody { unbonding_period: Duration::Height(100) } };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExpiration {  });
+        let msg = ExecuteMsg::RegisterDenom { denom: "stlsd".to_string(), adapter: custody.clone() };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let adapter = DenomAdapter::Contract { address: "adapter".to_string(), unbonding_period: DAY };
+        let msg = ExecuteMsg::RegisterDenom { denom: "ibc/ATOM".to_string(), adapter: adapter.clone() };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        // Unregistered denoms are rejected. Adapter contracts receive their tokens
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID1) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &coins(100, "uother")), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidCoin {  });
+        let res = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &coins(200, "stlsd")), msg.clone()).unwrap();
+        assert!(res.messages.is_empty());
+        let res = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &coins(100, "ibc/ATOM")), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: "adapter".to_string(), msg: to_binary(&AdapterExecuteMsg::Deposit {  }).unwrap(), funds: coins(100, "ibc/ATOM") }));
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID2) };
+        execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &coins(50, "stlsd")), msg).unwrap();
+
+        // Registered denoms move on merge
+        let msg = ExecuteMsg::Merge { nft_id: Uint128::from(NFT_ID1), nft_ids: vec![Uint128::from(NFT_ID2)] };
+        execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), msg).unwrap();
+        let bonded_coins = |deps: Deps, nft_id: u128| -> Vec<Coin> {
+            let msg = QueryMsg::BondedCoinsByNFT { nft_id: nft_id.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+        assert_eq!(bonded_coins(deps.as_ref(), NFT_ID1), vec![coin(500, "ustake"), coin(100, "ibc/ATOM"), coin(250, "stlsd")]);
+        assert_eq!(bonded_coins(deps.as_ref(), NFT_ID2), vec![]);
+        let res : DenomsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::Denoms {  }).unwrap()).unwrap();
+        assert_eq!(res.denoms, vec![
+            DenomInfo { denom: "ibc/ATOM".to_string(), adapter, bonded: Uint128::from(100u128) },
+            DenomInfo { denom: "stlsd".to_string(), adapter: custody, bonded: Uint128::from(250u128) },
+        ]);
+        let msg = ExecuteMsg::RemoveDenom { denom: "stlsd".to_string() };
+        let err = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap_err();
+        assert_eq!(err, ContractError::DenomStillBonded { denom: "stlsd".to_string(), bonded: Uint128::from(250u128) });
+
+        // Unbonding withdraws the tokens from the adapter, claimable after its unbonding period
+        let msg = ExecuteMsg::UnbondDenom { nft_id: Uint128::from(NFT_ID1), denom: "ibc/ATOM".to_string(), amount: Uint128::from(50u128) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::RequestUnbondAmountMismatch { nft_id: NFT_ID1.to_string(), requested: "50".to_string(), balance: "100".to_string() });
+        let msg = ExecuteMsg::UnbondDenom { nft_id: Uint128::from(NFT_ID1), denom: "ibc/ATOM".to_string(), amount: Uint128::from(100u128) };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), msg).unwrap();
+        let withdraw_msg = AdapterExecuteMsg::Withdraw { amount: coin(100, "ibc/ATOM") };
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: "adapter".to_string(), msg: to_binary(&withdraw_msg).unwrap(), funds: vec![] }));
+        let release_at = later(&env, DAY).block.time;
+        let denom_claims = query_denom_claims(deps.as_ref(), "1".to_string(), None, None).unwrap();
+        assert_eq!(denom_claims.claims, vec![DenomClaim { nft_id: "1".to_string(), denom: "ibc/ATOM".to_string(), amount: Uint128::from(100u128), release_at }]);
+        // Not part of the validator claims, the bond denom releases nor claims
+        assert_eq!(get_claims(deps.as_ref(), "1"), vec![]);
+        let res = query_mature_claims(deps.as_ref(), later(&env, DAY), None, None, None).unwrap();
+        assert_eq!(res.claims, vec![]);
+        let res = query_upcoming_releases(deps.as_ref(), env.clone(), release_at).unwrap();
+        assert_eq!(res.total, Uint128::zero());
+        let msg = ExecuteMsg::Claim { nft_id: Uint128::from(NFT_ID1), sender: USER1.to_string(), amount: Uint128::from(100u128) };
+        let err = execute(deps.as_mut(), later(&env, DAY), mock_info(AGENT1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {  });
+
+        let msg = ExecuteMsg::ClaimDenom { nft_id: Uint128::from(NFT_ID1), sender: USER1.to_string(), denom: "ibc/ATOM".to_string(), amount: Uint128::from(100u128) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(AGENT1, &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {  });
+        let err = execute(deps.as_mut(), later(&env, DAY), mock_info(AGENT1, &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BalanceTooSmall {  });
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(100, "ibc/ATOM"));
+        let res = execute(deps.as_mut(), later(&env, DAY), mock_info(AGENT1, &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: USER1.to_string(), amount: coins(100, "ibc/ATOM") }));
+        assert_eq!(query_denom_claims(deps.as_ref(), "1".to_string(), None, None).unwrap().claims, vec![]);
+
+        // Denoms without bonded tokens can be removed
+        let msg = ExecuteMsg::RemoveDenom { denom: "ibc/ATOM".to_string() };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::DenomNotRegistered { denom: "ibc/ATOM".to_string() });
+    }
+
+    #[test]
+    fn liquid_staking() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        register_3_validators(deps.as_mut(), env.clone(), info);
+        let liquid_balance = |deps: Deps, address: &str| -> Uint128 {
+            let res : BalanceResponse = from_binary(&query(deps, mock_env(), QueryMsg::Balance { address: address.to_string() }).unwrap()).unwrap();
+            res.balance
+        };
+
+        // Anyone bonds the bond denom, minting one share per token into an empty pool
+        let msg = ExecuteMsg::BondLiquid {  };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &coins(1000, "stlsd")), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::InvalidCoin {  });
+        let res = execute(deps.as_mut(), env.clone(), mock_info(USER1, &coins(1000, "ustake")), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Staking(StakingMsg::Delegate { validator: VALIDATOR1.to_string(), amount: coin(1000, "ustake") }));
+        assert_eq!(liquid_balance(deps.as_ref(), USER1), Uint128::from(1000u128));
+        let res : TokenInfoResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::TokenInfo {  }).unwrap()).unwrap();
+        assert_eq!((res.symbol.as_str(), res.total_supply), (LIQUID_SYMBOL, Uint128::from(1000u128)));
+        // Same pool accounting as the NFTs
+        check_bonding_on_validators(deps.as_ref(), 1000, 0, 0, 0, 0, 0);
+        assert_eq!(from_binary::<Uint128>(&query(deps.as_ref(), env.clone(), QueryMsg::Bonded {  }).unwrap()).unwrap(), Uint128::from(1000u128));
+
+        // Shares move like cw20 tokens
+        let msg = ExecuteMsg::Transfer { recipient: USER2.to_string(), amount: Uint128::from(2000u128) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::LiquidBalanceTooSmall { balance: Uint128::from(1000u128), amount: Uint128::from(2000u128) });
+        let msg = ExecuteMsg::Transfer { recipient: USER2.to_string(), amount: Uint128::from(400u128) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap();
+        let msg = ExecuteMsg::Send { contract: "market".to_string(), amount: Uint128::from(100u128), msg: to_binary("list").unwrap() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(USER2, &[]), msg).unwrap();
+        let receive_msg = Cw20ReceiveMsg { sender: USER2.to_string(), amount: Uint128::from(100u128), msg: to_binary("list").unwrap() };
+        assert_eq!(res.messages[0].msg, CosmosMsg::Wasm(WasmMsg::Execute { contract_addr: "market".to_string(), msg: receive_msg.into_binary().unwrap(), funds: vec![] }));
+        assert_eq!(liquid_balance(deps.as_ref(), USER1), Uint128::from(600u128));
+        assert_eq!(liquid_balance(deps.as_ref(), USER2), Uint128::from(300u128));
+        assert_eq!(liquid_balance(deps.as_ref(), "market"), Uint128::from(100u128));
+
+        // Unbonding burns the shares into a claim of the holder
+        let msg = ExecuteMsg::UnbondLiquid { amount: Uint128::from(700u128) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::LiquidBalanceTooSmall { balance: Uint128::from(600u128), amount: Uint128::from(700u128) });
+        let msg = ExecuteMsg::UnbondLiquid { amount: Uint128::from(400u128) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap();
+        let env_day = later(&env, DAY);
+        let msg = ExecuteMsg::UnbondLiquid { amount: Uint128::from(200u128) };
+        execute(deps.as_mut(), env_day.clone(), mock_info(USER1, &[]), msg).unwrap();
+        // One claim per unbond: the later unbond does not delay the earlier tokens
+        let first = LiquidClaim { holder: Addr::unchecked(USER1), validator: VALIDATOR1.to_string(), amount: Uint128::from(400u128), release_at: later(&env, WEEK).block.time };
+        let second = LiquidClaim { holder: Addr::unchecked(USER1), validator: VALIDATOR1.to_string(), amount: Uint128::from(200u128), release_at: later(&env_day, WEEK).block.time };
+        assert_eq!(get_liquid_claims(deps.as_ref(), USER1, None), vec![first.clone(), second.clone()]);
+        let cursor = LiquidClaimCursor { release_at: first.release_at, validator: first.validator.clone() };
+        assert_eq!(get_liquid_claims(deps.as_ref(), USER1, Some(cursor)), vec![second.clone()]);
+        // Kept out of the nft_id claim queries
+        assert_eq!(get_claims(deps.as_ref(), USER1), vec![]);
+        let res : NftClaimsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::MatureClaims { as_of: Some(later(&env_day, WEEK).block.time), start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.claims, vec![]);
+        let res : UpcomingReleasesResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::UpcomingReleases { until: later(&env_day, WEEK).block.time }).unwrap()).unwrap();
+        // but still counted in the tokens coming back from the staking module
+        assert_eq!(res.releases, vec![Release { release_at: first.release_at, amount: Uint128::from(400u128) }, Release { release_at: second.release_at, amount: Uint128::from(200u128) }]);
+        assert_eq!(res.total, Uint128::from(600u128));
+        let res : LiquidPoolResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::LiquidPool {  }).unwrap()).unwrap();
+        assert_eq!(res, LiquidPoolResponse { bonded: Uint128::from(400u128), supply: Uint128::from(400u128) });
+        check_bonding_on_validators(deps.as_ref(), 400, 0, 0, 600, 0, 0);
+
+        // Each claim is released after its own unbonding period
+        let msg = ExecuteMsg::ClaimLiquid {  };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {  });
+        let err = execute(deps.as_mut(), later(&env, WEEK), mock_info(USER1, &[]), msg.clone()).unwrap_err();
+        assert_eq!(err, ContractError::BalanceTooSmall {  });
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(400, "ustake"));
+        let res = execute(deps.as_mut(), later(&env, WEEK), mock_info(USER1, &[]), msg.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: USER1.to_string(), amount: coins(400, "ustake") }));
+        assert_eq!(get_liquid_claims(deps.as_ref(), USER1, None), vec![second]);
+        check_bonding_on_validators(deps.as_ref(), 400, 0, 0, 200, 0, 0);
+        deps.querier.update_balance(MOCK_CONTRACT_ADDR, coins(200, "ustake"));
+        let res = execute(deps.as_mut(), later(&env_day, WEEK), mock_info(USER1, &[]), msg).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: USER1.to_string(), amount: coins(200, "ustake") }));
+        assert_eq!(get_liquid_claims(deps.as_ref(), USER1, None), vec![]);
+        check_bonding_on_validators(deps.as_ref(), 400, 0, 0, 0, 0, 0);
+    }
+
+    #[test]
+    fn liquid_staking_allowances() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        register_3_validators(deps.as_mut(), env.clone(), info);
+        let liquid_balance = |deps: Deps, address: &str| -> Uint128 {
+            let res : BalanceResponse = from_binary(&query(deps, mock_env(), QueryMsg::Balance { address: address.to_string() }).unwrap()).unwrap();
+            res.balance
+        };
+        let allowance = |deps: Deps| -> AllowanceResponse {
+            from_binary(&query(deps, mock_env(), QueryMsg::Allowance { owner: USER1.to_string(), spender: USER2.to_string() }).unwrap()).unwrap()
+        };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &coins(1000, "ustake")), ExecuteMsg::BondLiquid {  }).unwrap();
+
+        // USER1 lets USER2 spend 300 shares until 10 blocks later
+        let expires = cw20::Expiration::AtHeight(env.block.height + 10);
+        let msg = ExecuteMsg::IncreaseAllowance { spender: USER1.to_string(), amount: Uint128::from(300u128), expires: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::CannotSetOwnAccount {  });
+        let msg = ExecuteMsg::IncreaseAllowance { spender: USER2.to_string(), amount: Uint128::from(300u128), expires: Some(cw20::Expiration::AtHeight(env.block.height)) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::InvalidExpiration {  });
+        let msg = ExecuteMsg::IncreaseAllowance { spender: USER2.to_string(), amount: Uint128::from(300u128), expires: Some(expires) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap();
+        assert_eq!(allowance(deps.as_ref()), AllowanceResponse { allowance: Uint128::from(300u128), expires });
+        let res : AllAllowancesResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AllAllowances { owner: USER1.to_string(), start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.allowances, vec![AllowanceInfo { spender: USER2.to_string(), allowance: Uint128::from(300u128), expires }]);
+
+        // The spender moves the shares of the owner within the allowance
+        let msg = ExecuteMsg::TransferFrom { owner: USER1.to_string(), recipient: USER2.to_string(), amount: Uint128::from(200u128) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER2, &[]), msg).unwrap();
+        let msg = ExecuteMsg::SendFrom { owner: USER1.to_string(), contract: "market".to_string(), amount: Uint128::from(50u128), msg: to_binary("list").unwrap() };
+        let res = execute(deps.as_mut(), env.clone(), mock_info(USER2, &[]), msg).unwrap();
+        let receive_msg = Cw20ReceiveMsg { sender: USER2.to_string(), amount: Uint128::from(50u128), msg: to_binary("list").unwrap() };
+        assert_eq!(res.messages[0].msg, receive_msg.into_cosmos_msg("market").unwrap());
+        let msg = ExecuteMsg::BurnFrom { owner: USER1.to_string(), amount: Uint128::from(100u128) };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {  });
+        let msg = ExecuteMsg::BurnFrom { owner: USER1.to_string(), amount: Uint128::from(10u128) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER2, &[]), msg).unwrap();
+        assert_eq!(allowance(deps.as_ref()).allowance, Uint128::from(40u128));
+        assert_eq!(liquid_balance(deps.as_ref(), USER1), Uint128::from(740u128));
+        assert_eq!(liquid_balance(deps.as_ref(), USER2), Uint128::from(200u128));
+        assert_eq!(liquid_balance(deps.as_ref(), "market"), Uint128::from(50u128));
+        let msg = ExecuteMsg::TransferFrom { owner: USER1.to_string(), recipient: USER2.to_string(), amount: Uint128::from(10u128) };
+        let err = execute(deps.as_mut(), mock_env_height(env.block.height + 10), mock_info(USER2, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::AllowanceExpired {  });
+
+        // Decreasing below zero removes the allowance
+        let msg = ExecuteMsg::DecreaseAllowance { spender: USER2.to_string(), amount: Uint128::from(100u128), expires: None };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap();
+        assert_eq!(allowance(deps.as_ref()), AllowanceResponse::default());
+        let msg = ExecuteMsg::DecreaseAllowance { spender: USER2.to_string(), amount: Uint128::from(100u128), expires: None };
+        let err = execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap_err();
+        assert_eq!(err, ContractError::NoAllowance {  });
+
+        // Burnt shares leave their tokens to the remaining shares
+        let msg = ExecuteMsg::Burn { amount: Uint128::from(240u128) };
+        execute(deps.as_mut(), env.clone(), mock_info(USER1, &[]), msg).unwrap();
+        let res : LiquidPoolResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::LiquidPool {  }).unwrap()).unwrap();
+        assert_eq!(res, LiquidPoolResponse { bonded: Uint128::from(1000u128), supply: Uint128::from(750u128) });
+        let res : AllAccountsResponse = from_binary(&query(deps.as_ref(), env.clone(), QueryMsg::AllAccounts { start_after: None, limit: None }).unwrap()).unwrap();
+        assert_eq!(res.accounts, vec![USER1.to_string(), USER2.to_string(), "market".to_string()]);
+    }
+
+    #[test]
+    fn merge_split_rewards() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+
+        register_3_validators(deps.as_mut(), env.clone(), info);
+        let info = mock_info(AGENT1, &[]); 
+        nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 500,300, 200);
+        let rewards_by_nft = |deps: Deps, nft_id: u128| -> Uint128 {
+            let msg = QueryMsg::RewardsByNFT { nft_id: nft_id.to_string() };
+            from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap()
+        };
+
+        // 0.1 rewards per bonded token
+        REWARD_INDEX.save(deps.as_mut().storage, &Decimal::percent(10)).unwrap();
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID1), Uint128::from(50u128));
+
+        // Merged rewards move to the kept NFT
+        let msg = ExecuteMsg::Merge { nft_id: Uint128::from(NFT_ID1), nft_ids: vec![Uint128::from(NFT_ID2)] };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID1), Uint128::from(80u128));
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID2), Uint128::zero());
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID3), Uint128::from(20u128));
+
+        // New NFTs only accrue rewards after the split
+        let msg = ExecuteMsg::Split { nft_id: Uint128::from(NFT_ID1), new_nft_ids: vec![Uint128::from(4u128)], amounts: vec![Uint128::from(200u128)] };
+        execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        assert_eq!(rewards_by_nft(deps.as_ref(), 4), Uint128::zero());
+        REWARD_INDEX.save(deps.as_mut().storage, &Decimal::percent(20)).unwrap();
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID1), Uint128::from(140u128));
+        assert_eq!(rewards_by_nft(deps.as_ref(), 4), Uint128::from(20u128));
+
+        // Claimed rewards are sent to the recipient
+        let msg = ExecuteMsg::ClaimRewards { nft_id: Uint128::from(NFT_ID1), recipient: USER1.to_string() };
+        let res = execute(deps.as_mut(), env.clone(), info.clone(), msg.clone()).unwrap();
+        assert_eq!(res.messages[0].msg, CosmosMsg::Bank(BankMsg::Send { to_address: USER1.to_string(), amount: coins(140, "ustake") }));
+        assert_eq!(rewards_by_nft(deps.as_ref(), NFT_ID1), Uint128::zero());
+        let err = execute(deps.as_mut(), env, info, msg).unwrap_err();
+        assert_eq!(err, ContractError::NothingToClaim {});
+    }
+
+    #[test]
+    fn allocation_pro_rata() {
+        let mut deps = mock_dependencies();
+        let info = mock_info(MANAGER1, &[]);
+        let env = mock_env();
+        deps.querier
+            .update_staking("ustake", &[sample_validator(VALIDATOR1),sample_validator(VALIDATOR2),sample_validator(VALIDATOR3)], &[]);
+
+        let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
+        instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
+        let allocation = |deps: Deps, amount: u128| -> Vec<(String, u128)> {
+            let msg = QueryMsg::Allocation { amount: Uint128::from(amount) };
+            let res : AllocationResponse = from_binary(&query(deps, mock_env(), msg).unwrap()).unwrap();
+            res.allocations.into_iter().map(|allocation| (allocation.validator, allocation.amount.u128())).collect()
+        };
+        assert!(allocation(deps.as_ref(), 100).is_empty());
+
+        register_3_validators(deps.as_mut(), env.clone(), info);
+        nft123_bond_on_validators(deps.as_mut(), env, mock_info(AGENT1, &[]), 500,300, 200);
+        assert_eq!(allocation(deps.as_ref(), 100), vec![(VALIDATOR1.to_string(), 50), (VALIDATOR2.to_string(), 30), (VALIDATOR3.to_string(), 20)]);
+        // Leftovers to the validator with the most bonded tokens
+        assert_eq!(allocation(deps.as_ref(), 7), vec![(VALIDATOR1.to_string(), 4), (VALIDATOR2.to_string(), 2), (VALIDATOR3.to_string(), 1)]);
+    }
+
+    #[test]
+    fn migrate_legacy_claims() {
+        let mut deps = mock_dependencies();
+        let env = mock_env();
+        let state = State::new();
+        for (validator, unbonding_period) in [(VALIDATOR1, WEEK), (VALIDATOR2, DAY), (VALIDATOR3, WEEK)] {
+            let validator_info = ValidatorInfo { bond_denom: "ustake".to_string(), unbonding_period, bonded: 0, unbonding: 0 };
+            state.validator.save(deps.as_mut().storage, validator, &validator_info).unwrap();
+        }
+        let week_release = later(&env, WEEK).block.time;
+        let day_release = later(&env, DAY).block.time;
+
+        // NFT3 unbonding equal amounts from validators of different unbonding periods, NFT1 already claimed
+        LEGACY_CLAIMS.save(deps.as_mut().storage, &Addr::unchecked("3"), &vec![
+            Claim { amount: Uint128::from(50u128), release_at: Expiration::AtTime(week_release) },
+            Claim { amount: Uint128::from(50u128), release_at: Expiration::AtTime(day_release) },
+        ]).unwrap();
+        LEGACY_CLAIMS.save(deps.as_mut().storage, &Addr::unchecked("1"), &vec![]).unwrap();
+        NFT_VAL_UNBONDING.save(deps.as_mut().storage, ("3", VALIDATOR1), &Uint128::from(50u128)).unwrap();
+        NFT_VAL_UNBONDING.save(deps.as_mut().storage, ("3", VALIDATOR2), &Uint128::from(50u128)).unwrap();
+        NFT_VAL_UNBONDING.save(deps.as_mut().storage, ("1", VALIDATOR3), &Uint128::from(500u128)).unwrap();
+
+        // Only the legacy version of this contract
+        set_contract_version(deps.as_mut().storage, "crates.io:other", "0.1.0").unwrap();
+        let err = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMigration { contract: "crates.io:other".to_string(), version: "0.1.0".to_string() });
+        set_contract_version(deps.as_mut().storage, CONTRACT_NAME, "0.1.0").unwrap();
+        let res = migrate(deps.as_mut(), env.clone(), MigrateMsg {}).unwrap();
+        assert_eq!(res.attributes[1], ("migrated_claims", "2"));
+        let err = migrate(deps.as_mut(), env, MigrateMsg {}).unwrap_err();
+        assert_eq!(err, ContractError::InvalidMigration { contract: CONTRACT_NAME.to_string(), version: CONTRACT_VERSION.to_string() });
+
+        assert_eq!(get_claims(deps.as_ref(), "3"), vec![
+            NftClaim { nft_id: "3".to_string(), validator: VALIDATOR1.to_string(), amount: Uint128::from(50u128), release_at: week_release },
+            NftClaim { nft_id: "3".to_string(), validator: VALIDATOR2.to_string(), amount: Uint128::from(50u128), release_at: day_release },
+        ]);
+        assert!(get_claims(deps.as_ref(), "1").is_empty());
+        assert!(LEGACY_CLAIMS.is_empty(deps.as_ref().storage));
+        assert!(NFT_VAL_UNBONDING.is_empty(deps.as_ref().storage));
+    }
+
     #[test]
     fn remove_validators() {
         let mut deps = mock_dependencies();
@@ -1314,10 +2824,10 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         mocking_set_validators_delegations(&mut deps.querier, 600, 300, 200);
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 600,300, 200);
 
         check_bonding_on_validators(deps.as_ref(), 
@@ -1330,7 +2840,7 @@ mod tests {
         );
 
         // Removing VALIDATOR3, with the least amount of tokens will make the contract choose the second validator with the least amount of tokens
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         let msg = ExecuteMsg::RemoveValidator { address: VALIDATOR3.to_string() };
         let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         assert_eq!(res.attributes[0], ("action", "remove_validator"));
@@ -1453,13 +2963,13 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         mocking_set_validators_delegations(&mut deps.querier, 600, 300, 200);
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 600,300, 200);
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         let msg = ExecuteMsg::BondCheck {  };
         let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         assert_eq!(res.attributes[0], ("action", "bond_check"));
@@ -1475,13 +2985,13 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         mocking_set_validators_delegations(&mut deps.querier, 600, 300, 200);
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 600,300, 200);
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         let msg = ExecuteMsg::CollectAngelRewards {  };
         let res = execute(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
         assert_eq!(res.attributes[0], ("action", "withdraw_delegation_rewards"));
@@ -1496,14 +3006,14 @@ mod tests {
         let msg = InstantiateMsg {agent: AGENT1.into(),manager: MANAGER1.into(),treasury: TREASURY1.into(),};
         instantiate(deps.as_mut(), env.clone(), info.clone(), msg).unwrap();
 
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         mocking_set_validators_delegations(&mut deps.querier, 600, 300, 200);
         register_3_validators(deps.as_mut(), env.clone(), info.clone());
-        let info = mock_info(&AGENT1, &[]); 
+        let info = mock_info(AGENT1, &[]); 
         nft123_bond_on_validators(deps.as_mut(), env.clone(), info.clone(), 600,300, 200);
 
         let env_later = later(&env, (WEEK + HOUR).unwrap());
-        let info = mock_info(&MANAGER1, &[]); 
+        let info = mock_info(MANAGER1, &[]); 
         let msg = ExecuteMsg::CollectAngelRewards {  };
         let res = execute(deps.as_mut(), env_later.clone(), info.clone(), msg).unwrap();
         assert_eq!(res.attributes[0], ("action", "withdraw_delegation_rewards"));
@@ -75,6 +75,12 @@ pub enum ContractError {
     #[error("NFT {nft_id} not registered")]
     NFTNotRegistered { nft_id: String },
 
+    #[error("NFT {nft_id} already registered")]
+    NFTAlreadyRegistered { nft_id: String },
+
+    #[error("Split of NFT {nft_id} must leave part of its {bonded} bonded tokens and move non zero amounts")]
+    InvalidSplit { nft_id: String, bonded: Uint128 },
+
     #[error("NFT {nft_id} is currently unbonding on validator {val_addr}")]
     NFTAlreadyUnbonding { nft_id: String, val_addr: String },
 
@@ -84,6 +90,27 @@ pub enum ContractError {
     #[error("Only one validator registered. Its delegations can not be redelegated")]
     OnlyOneValidator { },
 
+    #[error("Denom {denom} has already been registered to this contract")]
+    DenomAlreadyRegistered { denom: String },
+
+    #[error("Denom {denom} not registered")]
+    DenomNotRegistered { denom: String },
+
+    #[error("Denom {denom} still has {bonded} tokens bonded")]
+    DenomStillBonded { denom: String, bonded: Uint128 },
+
+    #[error("Liquid staking balance {balance} is below {amount}")]
+    LiquidBalanceTooSmall { balance: Uint128, amount: Uint128 },
+
+    #[error("No allowance for this account")]
+    NoAllowance {},
+
+    #[error("Allowance is expired")]
+    AllowanceExpired {},
+
+    #[error("Can not migrate from {contract} version {version}")]
+    InvalidMigration { contract: String, version: String },
+
     #[error("Custom Error val: {val:?}")]
     CustomError { val: String },
 }
\ No newline at end of file
@@ -3,9 +3,11 @@ mod tests{
     use crate::{helpers::StakingContract, state::ValidatorInfo};
     use cosmwasm_std::{coin,Addr, Coin, Empty, Uint128, Decimal, Validator, FullDelegation, StdResult};
     use cosmwasm_std::testing::mock_env;
-    use cw_multi_test::{App,AppBuilder,Contract,ContractWrapper,Executor,StakingInfo};
+    use cw_multi_test::{App,AppBuilder,Contract,ContractWrapper,Executor,StakingInfo,StakingSudo,SudoMsg};
     use cw_utils::WEEK;
-    use crate::msg::{ExecuteMsg,InstantiateMsg,QueryMsg};
+    use cw20::BalanceResponse;
+    use crate::msg::{ExecuteMsg,InstantiateMsg,QueryMsg,LiquidPoolResponse};
+    use crate::error::ContractError;
 
     const NATIVE_DENOM: &str = "ujunox";
     const MANAGER1: &str = "juno148v3g2dpjeq6hwnlagmvq8pnqe5r9wjcrvel8u";
@@ -144,11 +146,11 @@ mod tests{
         val2: String,
         val3: String,
     ) {
-        let msg = ExecuteMsg::AddValidator { address: val1.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        let msg = ExecuteMsg::AddValidator { address: val1, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
         app.execute_contract(sender.clone(), staking_contract.addr(), &msg, &[]).unwrap();
-        let msg = ExecuteMsg::AddValidator { address: val2.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        let msg = ExecuteMsg::AddValidator { address: val2, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
         app.execute_contract(sender.clone(), staking_contract.addr(), &msg, &[]).unwrap();
-        let msg = ExecuteMsg::AddValidator { address: val3.into(), bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
+        let msg = ExecuteMsg::AddValidator { address: val3, bond_denom: NATIVE_DENOM.into(), unbonding_period: WEEK };
         app.execute_contract(sender.clone(), staking_contract.addr(), &msg, &[]).unwrap();
     }
 
@@ -164,6 +166,12 @@ mod tests{
             .unwrap()
     }
 
+    fn get_rewards_by_nft(app: &App, staking_contract: &StakingContract, nft_id:String) -> Uint128 {
+        app.wrap()
+            .query_wasm_smart(staking_contract.addr(), &QueryMsg::RewardsByNFT { nft_id })
+            .unwrap()
+    }
+
     fn get_bonded_on_validator(app: &App, staking_contract: &StakingContract, validator:&str) -> StdResult<Uint128> {
         let delegation = app.wrap()
             .query_wasm_smart(staking_contract.addr(), &QueryMsg::BondedOnValidator { address: validator.to_string() })
@@ -240,11 +248,11 @@ mod tests{
         assert_eq!(total_rewards,Uint128::from(60u128));
 
         // VALIDATOR1 has got 600 tokens staked
-       let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR1).unwrap();
+       let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR1).unwrap();
        assert_eq!(full_delegation.amount.amount,Uint128::from(600u128));
-       let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR2).unwrap();
+       let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR2).unwrap();
        assert_eq!(full_delegation.amount.amount,Uint128::from(400u128));
-       let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR3).unwrap();
+       let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR3).unwrap();
        assert_eq!(full_delegation.amount.amount,Uint128::from(200u128));
  
        // No upbonding or rewards have been received by contract
@@ -256,17 +264,24 @@ mod tests{
        let msg = ExecuteMsg::Unbond { nft_id: Uint128::from(NFT_ID1), amount: Uint128::from(600u128) };
         app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[]).unwrap();
  
+        // Unbonding withdraws the rewards of all validators to the contract before the bonded tokens change
+        let balance = get_balance(&app, staking_contract.addr().to_string(), NATIVE_DENOM.to_string());
+        assert_eq!(balance.amount, Uint128::from(120u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID1.to_string()), Uint128::from(60u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID2.to_string()), Uint128::from(40u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID3.to_string()), Uint128::from(20u128));
+
         // QUESTION: THIS SHOULD GIVE A BALANCE OF THE UNBONDED TOKENS RECEIVED BY THE CONTRACT AFTER THE UNBONDING PERIOD
         // app.update_block(|block| block.time = block.time.plus_seconds( 3 ));
         // let balance = get_balance(&app, staking_contract.addr().to_string(), NATIVE_DENOM.to_string());
         // assert_ne!(balance.amount, Uint128::zero());
 
         // After Unbonding, the tokens delegated have changed
-        let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR1).unwrap();
+        let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR1).unwrap();
         assert_eq!(full_delegation.amount.amount,Uint128::from(300u128));
-        let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR2).unwrap();
+        let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR2).unwrap();
         assert_eq!(full_delegation.amount.amount,Uint128::from(100u128));
-        let full_delegation = query_module_delegation(&app, &staking_contract.addr().as_str(), VALIDATOR3).unwrap();
+        let full_delegation = query_module_delegation(&app, staking_contract.addr().as_str(), VALIDATOR3).unwrap();
         assert_eq!(full_delegation.amount.amount,Uint128::from(200u128));
         // Same as previous, but data queried from the contract itself (as opposed to querying the network as before)
         let bonded_validator = get_bonded_on_validator(&app, &staking_contract, VALIDATOR1).unwrap();
@@ -293,20 +308,92 @@ mod tests{
         // let balance = get_balance(&app, AGENT1.to_string(), NATIVE_DENOM.to_string());
         // println!(">>>>>>>>>>>>>> AGENT1 balance after claiming: {:?}", balance);
 
+        // Another year of rewards, only for the tokens still bonded
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        let msg = ExecuteMsg::CollectAngelRewards {  };
+        app.execute_contract(Addr::unchecked(MANAGER1), staking_contract.addr(), &msg, &[]).unwrap();
         let balance = get_balance(&app, staking_contract.addr().to_string(), NATIVE_DENOM.to_string());
-        assert_eq!(balance.amount, Uint128::zero());
-        let rewards = query_rewards(&app, staking_contract.addr().as_str(), VALIDATOR1);
-        assert_eq!(rewards,Some(Uint128::from(60u128)));
-        let rewards = query_rewards(&app, staking_contract.addr().as_str(), VALIDATOR2);
-        assert_eq!(rewards,Some(Uint128::from(40u128)));
-        let rewards = query_rewards(&app, staking_contract.addr().as_str(), VALIDATOR3);
-        assert_eq!(rewards,Some(Uint128::from(20u128)));
-
-        //Collect the rewards can not be tested because of: Unsupported distribution message: SetWithdrawAddress { address: ..}
-        // Tested on this contract without SetWithdrawAddress works for the following code
-        // let msg = ExecuteMsg::CollectAngelRewards {  };
-        // app.execute_contract(Addr::unchecked(MANAGER1), staking_contract.addr(), &msg, &[]).unwrap();
-        // let balance = get_balance(&app, staking_contract.addr().to_string(), NATIVE_DENOM.to_string());
-        // assert_eq!(balance.amount, Uint128::from(120u128));
+        assert_eq!(balance.amount, Uint128::from(180u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID1.to_string()), Uint128::from(60u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID2.to_string()), Uint128::from(80u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID3.to_string()), Uint128::from(40u128));
+
+        // Only the agent claims rewards
+        let msg = ExecuteMsg::ClaimRewards { nft_id: Uint128::from(NFT_ID2), recipient: USER1.to_string() };
+        app.execute_contract(Addr::unchecked(MANAGER1), staking_contract.addr(), &msg, &[]).unwrap_err();
+        app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[]).unwrap();
+        let balance = get_balance(&app, USER1.to_string(), NATIVE_DENOM.to_string());
+        assert_eq!(balance.amount, Uint128::from(2080u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID2.to_string()), Uint128::zero());
+        let err = app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), ContractError::NothingToClaim {}.to_string());
+    }
+
+    #[test]
+    fn liquid_staking_compounds() {
+        let (mut app, code_id) = store_code();
+        let staking_contract = staking_angel_instantiate(&mut app, code_id, AGENT1.into(), MANAGER1.into(), TREASURY1.into());
+        add_3_validators(&mut app, &staking_contract, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let liquid_pool = |app: &App| -> LiquidPoolResponse {
+            app.wrap().query_wasm_smart(staking_contract.addr(), &QueryMsg::LiquidPool {  }).unwrap()
+        };
+
+        // NFTs and the liquid staking pool share the validators and the rewards
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID1) };
+        app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM)]).unwrap();
+        let msg = ExecuteMsg::BondLiquid {  };
+        app.execute_contract(Addr::unchecked(USER1), staking_contract.addr(), &msg, &[coin(1000, NATIVE_DENOM)]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID2) };
+        app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[coin(500, NATIVE_DENOM)]).unwrap();
+        let msg = ExecuteMsg::BondLiquid {  };
+        assert_eq!(liquid_pool(&app), LiquidPoolResponse { bonded: Uint128::from(1000u128), supply: Uint128::from(1000u128) });
+
+        // A year of rewards. The pool rewards are bonded again, so later shares cost more tokens
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        app.execute_contract(Addr::unchecked(USER1), staking_contract.addr(), &msg, &[coin(550, NATIVE_DENOM)]).unwrap();
+        assert_eq!(liquid_pool(&app), LiquidPoolResponse { bonded: Uint128::from(1650u128), supply: Uint128::from(1500u128) });
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID1.to_string()), Uint128::from(100u128));
+        let total_bonded : Uint128 = app.wrap().query_wasm_smart(staking_contract.addr(), &QueryMsg::Bonded {  }).unwrap();
+        assert_eq!(total_bonded, Uint128::from(3150u128));
+
+        // All the shares are redeemed for the whole pool
+        let msg = ExecuteMsg::UnbondLiquid { amount: Uint128::from(1500u128) };
+        app.execute_contract(Addr::unchecked(USER1), staking_contract.addr(), &msg, &[]).unwrap();
+        let res : BalanceResponse = app.wrap().query_wasm_smart(staking_contract.addr(), &QueryMsg::Balance { address: USER1.to_string() }).unwrap();
+        assert_eq!(res.balance, Uint128::zero());
+        assert_eq!(liquid_pool(&app), LiquidPoolResponse { bonded: Uint128::zero(), supply: Uint128::zero() });
+        assert_eq!(get_bonded_by_nft(&app, &staking_contract, NFT_ID1.to_string()), Uint128::from(1000u128));
+
+        let msg = ExecuteMsg::ClaimLiquid {  };
+        let err = app.execute_contract(Addr::unchecked(USER1), staking_contract.addr(), &msg, &[]).unwrap_err();
+        assert_eq!(err.root_cause().to_string(), ContractError::NothingToClaim {}.to_string());
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 7));
+        app.sudo(SudoMsg::Staking(StakingSudo::ProcessQueue {})).unwrap();
+        app.execute_contract(Addr::unchecked(USER1), staking_contract.addr(), &msg, &[]).unwrap();
+        let balance = get_balance(&app, USER1.to_string(), NATIVE_DENOM.to_string());
+        assert_eq!(balance.amount, Uint128::from(2100u128));
+    }
+
+    #[test]
+    fn remove_validator_with_pending_rewards() {
+        let (mut app, code_id) = store_code();
+        let staking_contract = staking_angel_instantiate(&mut app, code_id, AGENT1.into(), MANAGER1.into(), TREASURY1.into());
+        add_3_validators(&mut app, &staking_contract, Addr::unchecked(MANAGER1), VALIDATOR1.into(), VALIDATOR2.into(), VALIDATOR3.into());
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID1) };
+        app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[coin(600, NATIVE_DENOM)]).unwrap();
+        let msg = ExecuteMsg::Bond { nft_id: Uint128::from(NFT_ID2) };
+        app.execute_contract(Addr::unchecked(AGENT1), staking_contract.addr(), &msg, &[coin(400, NATIVE_DENOM)]).unwrap();
+
+        // A year of rewards is pending on the removed validator when its tokens are redelegated
+        app.update_block(|block| block.time = block.time.plus_seconds(60 * 60 * 24 * 365));
+        let msg = ExecuteMsg::RemoveValidator { address: VALIDATOR1.to_string() };
+        let res = app.execute_contract(Addr::unchecked(MANAGER1), staking_contract.addr(), &msg, &[]).unwrap();
+        let wasm_event = res.events.iter().find(|event| event.ty == "wasm").unwrap();
+        assert!(wasm_event.attributes.iter().any(|attr| attr.key == "rewards" && attr.value == "100"));
+        assert_eq!(get_validator_info(&app, &staking_contract, VALIDATOR3.into()).bonded, 600u128);
+        let balance = get_balance(&app, staking_contract.addr().to_string(), NATIVE_DENOM.to_string());
+        assert_eq!(balance.amount, Uint128::from(100u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID1.to_string()), Uint128::from(60u128));
+        assert_eq!(get_rewards_by_nft(&app, &staking_contract, NFT_ID2.to_string()), Uint128::from(40u128));
     }
-}
\ No newline at end of file
+}
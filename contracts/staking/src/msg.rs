@@ -1,8 +1,8 @@
 use cosmwasm_schema::{cw_serde, QueryResponses};
-use cosmwasm_std::{Uint128,Coin, FullDelegation, Delegation};
-pub use cw_controllers::ClaimsResponse;
+use cosmwasm_std::{Binary, Uint128,Coin, FullDelegation, Delegation, Timestamp};
+use cw20::{AllAccountsResponse, AllAllowancesResponse, AllowanceResponse, BalanceResponse, Expiration, TokenInfoResponse};
 use cw_utils::Duration;
-use crate::state::{ValidatorInfo};
+use crate::state::{ValidatorInfo, NftClaim, DenomClaim, LiquidClaim, DenomAdapter};
 
 #[cw_serde]
 pub struct InstantiateMsg {
@@ -11,26 +11,85 @@ pub struct InstantiateMsg {
    pub treasury: String,
 }
 
+#[cw_serde]
+pub struct MigrateMsg {}
+
 #[cw_serde]
 pub enum ExecuteMsg {
-    /// Bond will bond all staking tokens sent with the message
+    /// Bond will bond all staking tokens sent with the message. Tokens of a registered denom go to its adapter
     Bond {nft_id: Uint128},
     /// Unbond staking tokens set by amount
     Unbond { nft_id: Uint128, amount: Uint128 },
     /// Claim is used to claim native tokens previously "unbonded" after the chain-defined unbonding period
     Claim {nft_id: Uint128 , sender: String, amount:Uint128},
+    /// Unbond the tokens of a registered denom, withdrawing them from its adapter
+    UnbondDenom { nft_id: Uint128, denom: String, amount: Uint128 },
+    /// Claim the tokens of a registered denom after the adapter unbonding period
+    ClaimDenom { nft_id: Uint128, sender: String, denom: String, amount: Uint128 },
+    /// Moves the bonded tokens of nft_ids into nft_id. Delegations are not changed
+    Merge {nft_id: Uint128, nft_ids: Vec<Uint128>},
+    /// Moves amounts[i] of the bonded tokens of nft_id into new_nft_ids[i]. Delegations are not changed
+    Split {nft_id: Uint128, new_nft_ids: Vec<Uint128>, amounts: Vec<Uint128>},
+    /// Sends the rewards accrued by nft_id to recipient
+    ClaimRewards {nft_id: Uint128, recipient: String},
     AddValidator {address: String, bond_denom: String, unbonding_period: Duration},
     RemoveValidator {address: String},
+    /// Accepts a denom other than the bond denom, bonded through adapter. Its tokens earn no staking rewards
+    /// on this contract and are left out of the reward index
+    RegisterDenom { denom: String, adapter: DenomAdapter },
+    /// Stops accepting a registered denom. None of its tokens can be bonded
+    RemoveDenom { denom: String },
     BondCheck {},
     CollectAngelRewards {},    
+    /// Bonds the bond denom tokens sent into the liquid staking pool, minting shares to the sender
+    BondLiquid {},
+    /// Burns amount shares of the sender for their pool tokens, claimable with ClaimLiquid after the unbonding period
+    UnbondLiquid { amount: Uint128 },
+    /// Sends the tokens released from the liquid staking claims of the sender
+    ClaimLiquid {},
+    /// Moves liquid staking shares to recipient, as a cw20 Transfer
+    Transfer { recipient: String, amount: Uint128 },
+    /// Moves liquid staking shares to contract and calls its Receive, as a cw20 Send
+    Send { contract: String, amount: Uint128, msg: Binary },
+    /// Destroys liquid staking shares of the sender without unbonding, as a cw20 Burn.
+    /// Their part of the pool goes to the remaining shares
+    Burn { amount: Uint128 },
+    /// Allows spender to move amount more liquid staking shares of the sender, as a cw20 IncreaseAllowance
+    IncreaseAllowance { spender: String, amount: Uint128, expires: Option<Expiration> },
+    /// Lowers the allowance of spender by amount, removing it when it reaches zero, as a cw20 DecreaseAllowance
+    DecreaseAllowance { spender: String, amount: Uint128, expires: Option<Expiration> },
+    /// Moves liquid staking shares of owner to recipient using the allowance of the sender, as a cw20 TransferFrom
+    TransferFrom { owner: String, recipient: String, amount: Uint128 },
+    /// Moves liquid staking shares of owner to contract using the allowance of the sender and calls its Receive, as a cw20 SendFrom
+    SendFrom { owner: String, contract: String, amount: Uint128, msg: Binary },
+    /// Destroys liquid staking shares of owner using the allowance of the sender, as a cw20 BurnFrom
+    BurnFrom { owner: String, amount: Uint128 },
+    /// Hands the contract over to a new agent, can only be called by the agent
+    UpdateAgent { agent: String },
 }
 
 #[cw_serde]
 #[derive(QueryResponses)]
 pub enum QueryMsg {
-    /// Claims shows the number of tokens this address can access when they are done unbonding.
-    #[returns(ClaimsResponse)]
-    Claims { nft_id: String },
+    /// Claims shows the tokens this nft_id can access when they are done unbonding, one claim per validator.
+    /// start_after is a validator address
+    #[returns(NftClaimsResponse)]
+    Claims { nft_id: String, start_after: Option<String>, limit: Option<u32> },
+    /// Claims of every nft_id released on or before as_of (current block time by default), ordered by release time.
+    /// start_after is the last claim of the previous page
+    #[returns(NftClaimsResponse)]
+    MatureClaims { as_of: Option<Timestamp>, start_after: Option<ClaimCursor>, limit: Option<u32> },
+    /// Claims of registered denoms of this nft_id, one per denom. start_after is a denom
+    #[returns(DenomClaimsResponse)]
+    DenomClaims { nft_id: String, start_after: Option<String>, limit: Option<u32> },
+    /// Claims of UnbondLiquid of this holder, one per unbond and validator, ordered by release time.
+    /// start_after is the last claim of the previous page
+    #[returns(LiquidClaimsResponse)]
+    LiquidClaims { address: String, start_after: Option<LiquidClaimCursor>, limit: Option<u32> },
+    /// Bond denom tokens the staking module will release from now until the given time, aggregated by release time.
+    /// Includes the liquid staking claims
+    #[returns(UpcomingReleasesResponse)]
+    UpcomingReleases { until: Timestamp },
     #[returns(ValidatorInfo)]
     ValidatorInfo {address: String},
     #[returns(Uint128)]
@@ -44,7 +103,19 @@ pub enum QueryMsg {
     #[returns(Uint128)]
     BondedOnValidator {address: String},  
     #[returns(Uint128)]
-    BondedByNFT {nft_id: String},     
+    BondedByNFT {nft_id: String},
+    /// Tokens of every denom bonded by nft_id, the bond denom first
+    #[returns(Vec<Coin>)]
+    BondedCoinsByNFT {nft_id: String},
+    /// Registered denoms and their bonded tokens
+    #[returns(DenomsResponse)]
+    Denoms {},
+    /// Share of amount in the pooled delegations, pro rata of the tokens bonded on each validator
+    #[returns(AllocationResponse)]
+    Allocation {amount: Uint128},
+    /// Rewards accrued by nft_id and not claimed yet
+    #[returns(Uint128)]
+    RewardsByNFT {nft_id: String},     
     #[returns(String)]
     Agent {},   
     #[returns(String)]
@@ -55,5 +126,104 @@ pub enum QueryMsg {
     DelegationOnValidator {address:String},        
     #[returns(Vec<Delegation>)]
     AllDelegations {},
-         
-}
\ No newline at end of file
+    /// Liquid staking shares held by address, as a cw20 Balance
+    #[returns(BalanceResponse)]
+    Balance { address: String },
+    /// Liquid staking share token, as a cw20 TokenInfo
+    #[returns(TokenInfoResponse)]
+    TokenInfo {},
+    /// Liquid staking shares of owner spender can move, as a cw20 Allowance
+    #[returns(AllowanceResponse)]
+    Allowance { owner: String, spender: String },
+    /// Allowances granted by owner, as a cw20 AllAllowances. start_after is a spender address
+    #[returns(AllAllowancesResponse)]
+    AllAllowances { owner: String, start_after: Option<String>, limit: Option<u32> },
+    /// Holders of liquid staking shares, as a cw20 AllAccounts. start_after is a holder address
+    #[returns(AllAccountsResponse)]
+    AllAccounts { start_after: Option<String>, limit: Option<u32> },
+    /// Tokens backing the liquid staking shares
+    #[returns(LiquidPoolResponse)]
+    LiquidPool {},
+}
+
+/// Interface of the adapter contracts of registered denoms
+#[cw_serde]
+pub enum AdapterExecuteMsg {
+    /// Takes the tokens sent with the message
+    Deposit {},
+    /// Sends amount back to the sender once withdrawn
+    Withdraw { amount: Coin },
+}
+
+#[cw_serde]
+pub struct LiquidPoolResponse {
+    /// Bond denom tokens of the pool, with the rewards not compounded yet
+    pub bonded: Uint128,
+    /// Shares minted
+    pub supply: Uint128,
+}
+
+#[cw_serde]
+pub struct DenomInfo {
+    pub denom: String,
+    pub adapter: DenomAdapter,
+    pub bonded: Uint128,
+}
+
+#[cw_serde]
+pub struct DenomsResponse {
+    pub denoms: Vec<DenomInfo>,
+}
+
+/// Position of a claim in the release index
+#[cw_serde]
+pub struct ClaimCursor {
+    pub release_at: Timestamp,
+    pub nft_id: String,
+    pub validator: String,
+}
+
+#[cw_serde]
+pub struct NftClaimsResponse {
+    pub claims: Vec<NftClaim>,
+}
+
+#[cw_serde]
+pub struct DenomClaimsResponse {
+    pub claims: Vec<DenomClaim>,
+}
+
+/// Position of a claim in the liquid claims of a holder
+#[cw_serde]
+pub struct LiquidClaimCursor {
+    pub release_at: Timestamp,
+    pub validator: String,
+}
+
+#[cw_serde]
+pub struct LiquidClaimsResponse {
+    pub claims: Vec<LiquidClaim>,
+}
+
+#[cw_serde]
+pub struct ValidatorAllocation {
+    pub validator: String,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct AllocationResponse {
+    pub allocations: Vec<ValidatorAllocation>,
+}
+
+#[cw_serde]
+pub struct Release {
+    pub release_at: Timestamp,
+    pub amount: Uint128,
+}
+
+#[cw_serde]
+pub struct UpcomingReleasesResponse {
+    pub releases: Vec<Release>,
+    pub total: Uint128,
+}
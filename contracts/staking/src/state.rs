@@ -1,6 +1,7 @@
 use cosmwasm_schema::cw_serde;
-use cosmwasm_std::{Uint128, Uint64};
-use cw_controllers::Claims;
+use cosmwasm_std::{Addr, Decimal, Timestamp, Uint128, Uint64};
+use cw_controllers::Claim;
+use cw20::AllowanceResponse;
 use cw_storage_plus::{Item, MultiIndex, Index, IndexList, IndexedMap, Map};
 use cw_utils::Duration;
 
@@ -18,9 +19,14 @@ pub const UNBONDING: Item<Uint128> = Item::new("unbonding");
 // QUESTION: Using Uint128 as key on a map --> doesn't satisfy `cosmwasm_std::Uint128: PrimaryKey`
 // key: nft_id  - Track changes from Bonding to Unbonding. 
 pub const NFT_BONDED: Map<&str,Uint128> = Map::new("nft_bonded");
-// key: nft_id, validator address  - Track changes from Unbonding to Claiming. Needed to udpate unbonding validator info when claiming
+// Legacy. key: nft_id, validator address. Replaced by State.claims, only read by migrate
 pub const NFT_VAL_UNBONDING: Map<(&str,&str),Uint128> = Map::new("nft_unbonding");
 
+// Rewards per bonded token, accumulated since the first reward collection
+pub const REWARD_INDEX: Item<Decimal> = Item::new("reward_index");
+// key: nft_id
+pub const NFT_REWARDS: Map<&str, NftRewards> = Map::new("nft_rewards");
+
 // All bonded and claimed 
 pub const TOTAL_BONDED: Item<Uint128> = Item::new("total_bonded");
 pub const TOTAL_CLAIMED: Item<Uint128> = Item::new("total_claimed");
@@ -32,8 +38,26 @@ pub const AGENT: Item<String> = Item::new("relayer");
 pub const MANAGER: Item<String> = Item::new("manager");
 pub const TREASURY: Item<String> = Item::new("treasury");
 
-// Claims(Map<&Addr, Vec<Claim>>)      struct Claim {amount: Uint128,release_at: Expiration,}
-pub const CLAIMS: Claims = Claims::new("claims");
+// key: denom. Registered denoms bonded through an adapter instead of being delegated
+pub const DENOMS: Map<&str, DenomAdapter> = Map::new("denoms");
+// key: denom. Tokens of the registered denom currently bonded
+pub const DENOM_BONDED: Map<&str, Uint128> = Map::new("denom_bonded");
+// key: (nft_id, denom). Tokens of registered denoms bonded by the nft_id
+pub const NFT_DENOM_BONDED: Map<(&str,&str), Uint128> = Map::new("nft_denom_bonded");
+// key: (nft_id, denom). Claims of registered denoms, kept apart from the validator claims
+pub const DENOM_CLAIMS: Map<(&str,&str), DenomClaim> = Map::new("denom_claims");
+
+// Key of the pooled position backing the liquid staking shares in NFT_BONDED and NFT_REWARDS. Never a numeric nft_id
+pub const LIQUID_POOL: &str = "liquid_pool";
+// Liquid staking shares minted by BondLiquid and not burnt yet
+pub const LIQUID_SUPPLY: Item<Uint128> = Item::new("liquid_supply");
+// key: holder address. Liquid staking shares held
+pub const LIQUID_BALANCES: Map<&Addr, Uint128> = Map::new("liquid_balances");
+// key: (owner, spender). Liquid staking shares of owner spender can move
+pub const LIQUID_ALLOWANCES: Map<(&Addr, &Addr), AllowanceResponse> = Map::new("liquid_allowances");
+
+// Legacy cw_controllers::Claims namespace keyed by Addr::unchecked(nft_id). Replaced by State.claims, only read by migrate
+pub const LEGACY_CLAIMS: Map<&Addr, Vec<Claim>> = Map::new("claims");
 
 #[cw_serde]
 pub struct ValidatorInfo{
@@ -60,10 +84,97 @@ impl<'a> IndexList<ValidatorInfo> for ValidatorIndexes<'a> {
     }
 }
 
+/// Rewards of a nft_id. pending holds the rewards accrued until REWARD_INDEX was equal to index
+#[cw_serde]
+#[derive(Default)]
+pub struct NftRewards {
+    pub index: Decimal,
+    pub pending: Uint128,
+}
+
+/// Staking path of a registered denom, such as a liquid staking derivative or an IBC denom
+#[cw_serde]
+pub enum DenomAdapter {
+    /// Tokens are held by this contract. Liquid staking derivatives accrue their staking rewards in their price
+    Custody { unbonding_period: Duration },
+    /// Tokens are deposited on the adapter contract, which sends them back on withdraw
+    Contract { address: String, unbonding_period: Duration },
+}
+
+impl DenomAdapter {
+    pub fn unbonding_period(&self) -> &Duration {
+        match self {
+            DenomAdapter::Custody { unbonding_period } => unbonding_period,
+            DenomAdapter::Contract { unbonding_period, .. } => unbonding_period,
+        }
+    }
+}
+
+/// Tokens undelegated from one validator on behalf of one nft_id, released after the validator unbonding period
+#[cw_serde]
+pub struct NftClaim {
+    pub nft_id: String,
+    pub validator: String,
+    pub amount: Uint128,
+    pub release_at: Timestamp,
+}
+
+/// Tokens undelegated from one validator by UnbondLiquid on behalf of a liquid staking holder, released after the validator unbonding period
+#[cw_serde]
+pub struct LiquidClaim {
+    pub holder: Addr,
+    pub validator: String,
+    pub amount: Uint128,
+    pub release_at: Timestamp,
+}
+
+/// Tokens of a registered denom withdrawn from its adapter on behalf of one nft_id, released after the adapter unbonding period
+#[cw_serde]
+pub struct DenomClaim {
+    pub nft_id: String,
+    pub denom: String,
+    pub amount: Uint128,
+    pub release_at: Timestamp,
+}
+
+pub struct ClaimIndexes<'a> {
+    // release time in seconds. Iterating it returns claims ordered by expiry
+    pub release: MultiIndex<'a, u64, NftClaim, (&'a str, &'a str)>,
+}
+
+impl<'a> IndexList<NftClaim> for ClaimIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<NftClaim>> + '_> {
+        let v: Vec<&dyn Index<NftClaim>> = vec![&self.release];
+        Box::new(v.into_iter())
+    }
+}
+
+pub struct LiquidClaimIndexes<'a> {
+    // release time in seconds. Iterating it returns claims ordered by expiry
+    pub release: MultiIndex<'a, u64, LiquidClaim, (&'a Addr, u64, &'a str)>,
+}
+
+impl<'a> IndexList<LiquidClaim> for LiquidClaimIndexes<'a> {
+    fn get_indexes(&'_ self) -> Box<dyn Iterator<Item = &'_ dyn Index<LiquidClaim>> + '_> {
+        let v: Vec<&dyn Index<LiquidClaim>> = vec![&self.release];
+        Box::new(v.into_iter())
+    }
+}
+
 pub struct State <'a>
 {
     // pk: validator address
     pub validator: IndexedMap<'a, &'a str, ValidatorInfo, ValidatorIndexes<'a>>,
+    // pk: (nft_id, validator address)
+    pub claims: IndexedMap<'a, (&'a str, &'a str), NftClaim, ClaimIndexes<'a>>,
+    // pk: (holder, release time in seconds, validator address). Claims of UnbondLiquid, kept apart from the nft_id claims
+    pub liquid_claims: IndexedMap<'a, (&'a Addr, u64, &'a str), LiquidClaim, LiquidClaimIndexes<'a>>,
+}
+
+impl<'a> Default for State<'a> {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl<'a> State<'a>
@@ -74,10 +185,22 @@ impl<'a> State<'a>
             validator: IndexedMap::new(
                 "validatorinfo",
             ValidatorIndexes { 
-                bonded: MultiIndex::new(|_pk,d| d.bonded.clone(),"validatorinfo","validatorinfo__bonded"),
-                unbonding: MultiIndex::new(|_pk,d| d.unbonding.clone(),"validatorinfo","validatorinfo__claimed"),
+                bonded: MultiIndex::new(|_pk,d| d.bonded,"validatorinfo","validatorinfo__bonded"),
+                unbonding: MultiIndex::new(|_pk,d| d.unbonding,"validatorinfo","validatorinfo__claimed"),
+                },
+            ),
+            claims: IndexedMap::new(
+                "nft_claims",
+            ClaimIndexes {
+                release: MultiIndex::new(|_pk,d| d.release_at.seconds(),"nft_claims","nft_claims__release"),
+                },
+            ),
+            liquid_claims: IndexedMap::new(
+                "liquid_claims",
+            LiquidClaimIndexes {
+                release: MultiIndex::new(|_pk,d| d.release_at.seconds(),"liquid_claims","liquid_claims__release"),
                 },
-            )
+            ),
         }
     }
 }